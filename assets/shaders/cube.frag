@@ -0,0 +1,15 @@
+#version 330 core
+out vec4 final_color;
+
+//uniform vec4 ourColor;
+// built in datatype for texture objects
+uniform sampler2D texture1;
+uniform sampler2D texture2;
+
+in vec2 texCoord;
+
+void main() {
+    final_color = mix(texture(texture1, texCoord), texture(texture2, vec2(texCoord.x, 1.0 - texCoord.y)), 0.2);
+    //final_color = texture(texture1, texCoord) * vertexColor;
+    //final_color = ourColor;
+}
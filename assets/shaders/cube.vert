@@ -0,0 +1,15 @@
+#version 330 core
+layout (location = 0) in vec3 pos;
+layout (location = 1) in vec2 textureCoord;
+
+uniform mat4 model;
+uniform mat4 view;
+uniform mat4 projection;
+
+out vec2 texCoord;
+
+void main() {
+    //gl_Position = vec4(pos.x, pos.y, pos.z, 1.0);
+    gl_Position = projection * view * model * vec4(pos, 1.0);
+    texCoord = textureCoord;
+}
@@ -0,0 +1,12 @@
+#version 330 core
+out vec4 final_color;
+
+uniform sampler2D texture1;
+uniform sampler2D texture2;
+
+in vec2 texCoord;
+in vec4 tint;
+
+void main() {
+    final_color = mix(texture(texture1, texCoord), texture(texture2, vec2(texCoord.x, 1.0 - texCoord.y)), 0.2) * tint;
+}
@@ -0,0 +1,18 @@
+#version 330 core
+layout (location = 0) in vec3 pos;
+layout (location = 1) in vec2 textureCoord;
+// Per instance, the matrix takes up locations 4 to 7
+layout (location = 4) in mat4 model;
+layout (location = 8) in vec4 color;
+
+uniform mat4 view;
+uniform mat4 projection;
+
+out vec2 texCoord;
+out vec4 tint;
+
+void main() {
+    gl_Position = projection * view * model * vec4(pos, 1.0);
+    texCoord = textureCoord;
+    tint = color;
+}
@@ -0,0 +1,139 @@
+#pragma once
+// Phong and Blinn-Phong lighting, set from Rust by `lighting::Lights` and
+// `lighting::Material`. The limits are defined by `LightLimits::define`.
+
+#ifndef MAX_DIRECTIONAL_LIGHTS
+#define MAX_DIRECTIONAL_LIGHTS 1
+#endif
+#ifndef MAX_POINT_LIGHTS
+#define MAX_POINT_LIGHTS 4
+#endif
+#ifndef MAX_SPOT_LIGHTS
+#define MAX_SPOT_LIGHTS 2
+#endif
+
+struct Material {
+    sampler2D diffuse_map;
+    sampler2D specular_map;
+    bool has_diffuse_map;
+    bool has_specular_map;
+    vec3 diffuse;
+    vec3 specular;
+    float shininess;
+};
+
+struct DirectionalLight {
+    vec3 direction;
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+};
+
+struct PointLight {
+    vec3 position;
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    float constant;
+    float linear;
+    float quadratic;
+};
+
+struct SpotLight {
+    vec3 position;
+    vec3 direction;
+    // Cosines of the cone angles
+    float inner_cutoff;
+    float outer_cutoff;
+    vec3 ambient;
+    vec3 diffuse;
+    vec3 specular;
+    float constant;
+    float linear;
+    float quadratic;
+};
+
+uniform Material material;
+uniform DirectionalLight directional_lights[MAX_DIRECTIONAL_LIGHTS];
+uniform PointLight point_lights[MAX_POINT_LIGHTS];
+uniform SpotLight spot_lights[MAX_SPOT_LIGHTS];
+uniform int directional_light_count;
+uniform int point_light_count;
+uniform int spot_light_count;
+uniform bool blinn_phong;
+uniform vec3 view_position;
+
+// The surface colors at one point, maps and constant colors combined
+struct Surface {
+    vec3 diffuse;
+    vec3 specular;
+};
+
+float specular_factor(vec3 to_light, vec3 normal, vec3 to_view) {
+    if (blinn_phong) {
+        vec3 halfway = normalize(to_light + to_view);
+        // The halfway vector is closer to the normal than the reflection is
+        // to the view, so the exponent is scaled to keep highlights similar
+        return pow(max(dot(normal, halfway), 0.0), material.shininess * 4.0);
+    }
+    vec3 reflected = reflect(-to_light, normal);
+    return pow(max(dot(to_view, reflected), 0.0), material.shininess);
+}
+
+vec3 shade(vec3 to_light, vec3 normal, vec3 to_view, Surface surface,
+           vec3 ambient, vec3 diffuse, vec3 specular) {
+    float lambert = max(dot(normal, to_light), 0.0);
+    return ambient * surface.diffuse
+        + diffuse * lambert * surface.diffuse
+        + specular * specular_factor(to_light, normal, to_view) * surface.specular;
+}
+
+float attenuation(float distance, float constant, float linear, float quadratic) {
+    return 1.0 / (constant + linear * distance + quadratic * distance * distance);
+}
+
+// Lighting of a fragment at `position` in world space, from every light
+vec3 compute_lighting(vec3 position, vec3 normal, vec2 uv) {
+    Surface surface;
+    surface.diffuse = material.diffuse;
+    if (material.has_diffuse_map) {
+        surface.diffuse *= texture(material.diffuse_map, uv).rgb;
+    }
+    surface.specular = material.specular;
+    if (material.has_specular_map) {
+        surface.specular *= texture(material.specular_map, uv).rgb;
+    }
+
+    normal = normalize(normal);
+    vec3 to_view = normalize(view_position - position);
+    vec3 color = vec3(0.0);
+
+    for (int i = 0; i < directional_light_count && i < MAX_DIRECTIONAL_LIGHTS; i++) {
+        DirectionalLight light = directional_lights[i];
+        color += shade(-light.direction, normal, to_view, surface,
+                       light.ambient, light.diffuse, light.specular);
+    }
+
+    for (int i = 0; i < point_light_count && i < MAX_POINT_LIGHTS; i++) {
+        PointLight light = point_lights[i];
+        vec3 to_light = light.position - position;
+        float fade = attenuation(length(to_light), light.constant, light.linear, light.quadratic);
+        color += fade * shade(normalize(to_light), normal, to_view, surface,
+                              light.ambient, light.diffuse, light.specular);
+    }
+
+    for (int i = 0; i < spot_light_count && i < MAX_SPOT_LIGHTS; i++) {
+        SpotLight light = spot_lights[i];
+        vec3 to_light = light.position - position;
+        float fade = attenuation(length(to_light), light.constant, light.linear, light.quadratic);
+        // 1 inside the inner cone, 0 outside the outer one
+        float theta = dot(normalize(-to_light), normalize(light.direction));
+        float cone = clamp((theta - light.outer_cutoff) / (light.inner_cutoff - light.outer_cutoff),
+                           0.0, 1.0);
+        vec3 lit = shade(normalize(to_light), normal, to_view, surface,
+                         vec3(0.0), light.diffuse, light.specular);
+        color += fade * (light.ambient * surface.diffuse + cone * lit);
+    }
+
+    return color;
+}
@@ -0,0 +1,12 @@
+#version 330 core
+#include "lighting.glsl"
+
+out vec4 final_color;
+
+in vec3 worldPosition;
+in vec3 worldNormal;
+in vec2 texCoord;
+
+void main() {
+    final_color = vec4(compute_lighting(worldPosition, worldNormal, texCoord), 1.0);
+}
@@ -0,0 +1,21 @@
+#version 330 core
+layout (location = 0) in vec3 pos;
+layout (location = 1) in vec2 textureCoord;
+layout (location = 2) in vec3 normal;
+
+uniform mat4 model;
+uniform mat3 normal_matrix;
+uniform mat4 view;
+uniform mat4 projection;
+
+out vec3 worldPosition;
+out vec3 worldNormal;
+out vec2 texCoord;
+
+void main() {
+    vec4 world = model * vec4(pos, 1.0);
+    gl_Position = projection * view * world;
+    worldPosition = world.xyz;
+    worldNormal = normal_matrix * normal;
+    texCoord = textureCoord;
+}
@@ -0,0 +1,408 @@
+use glam::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+
+/// What the render loop needs from a camera, whichever kind is active.
+pub trait Camera {
+    /// The world to view space matrix.
+    fn view_matrix(&self) -> Mat4;
+    /// The view to clip space matrix for the given width / height ratio.
+    fn projection_matrix(&self, aspect: f32) -> Mat4;
+    /// Where the camera is in world space.
+    fn position(&self) -> Vec3;
+    /// Turns the camera by a mouse motion in pixels, positive `y_offset` is downwards.
+    fn process_mouse(&mut self, x_offset: f32, y_offset: f32);
+    /// Moves the camera for `delta_time` seconds in the given direction.
+    fn process_movement(&mut self, direction: CameraMovement, delta_time: f32);
+    /// Zooms in by `amount` mouse wheel steps, a negative amount zooms out.
+    fn zoom(&mut self, amount: f32);
+}
+
+/// Directions a camera can be moved in, relative to where it looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMovement {
+    Forward,
+    Backward,
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A first person camera that flies freely in the direction it looks.
+///
+/// Angles are in degrees. A yaw of -90 looks down the negative z axis, the
+/// default forward direction of OpenGL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlyCamera {
+    pub position: Vec3,
+    pub world_up: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Vertical field of view.
+    pub fov: f32,
+    /// Units per second.
+    pub speed: f32,
+    /// Degrees per pixel of mouse motion.
+    pub sensitivity: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            world_up: Vec3::Y,
+            yaw: -90.0,
+            pitch: 0.0,
+            fov: Self::MAX_FOV,
+            speed: 10.0,
+            sensitivity: 0.1,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+}
+
+impl FlyCamera {
+    /// Looking straight up or down would flip the view, so pitch stops short of it.
+    pub const MAX_PITCH: f32 = 89.0;
+    pub const MIN_FOV: f32 = 1.0;
+    pub const MAX_FOV: f32 = 45.0;
+
+    /// Makes a camera at the given position looking down the negative z axis.
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            ..Self::default()
+        }
+    }
+
+    /// The direction the camera looks in, unit length.
+    pub fn front(&self) -> Vec3 {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        Vec3::new(
+            yaw.cos() * pitch.cos(),
+            pitch.sin(),
+            yaw.sin() * pitch.cos(),
+        )
+        .normalize()
+    }
+
+    /// The direction to the right of the camera, unit length.
+    pub fn right(&self) -> Vec3 {
+        self.front().cross(self.world_up).normalize()
+    }
+
+    /// The up direction of the camera, unit length.
+    pub fn up(&self) -> Vec3 {
+        self.right().cross(self.front())
+    }
+
+    /// Turns the camera by a mouse motion in pixels. Positive `y_offset` is
+    /// downwards, like SDL reports it, and turns the camera down.
+    pub fn process_mouse(&mut self, x_offset: f32, y_offset: f32) {
+        self.yaw += x_offset * self.sensitivity;
+        self.pitch =
+            (self.pitch - y_offset * self.sensitivity).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// Moves the camera for `delta_time` seconds in the given direction.
+    pub fn process_movement(&mut self, direction: CameraMovement, delta_time: f32) {
+        let distance = self.speed * delta_time;
+        let offset = match direction {
+            CameraMovement::Forward => self.front(),
+            CameraMovement::Backward => -self.front(),
+            CameraMovement::Right => self.right(),
+            CameraMovement::Left => -self.right(),
+            CameraMovement::Up => self.world_up,
+            CameraMovement::Down => -self.world_up,
+        };
+        self.position += offset * distance;
+    }
+
+    /// Narrows the field of view by `amount` degrees, a negative amount widens it.
+    pub fn zoom(&mut self, amount: f32) {
+        self.fov = (self.fov - amount).clamp(Self::MIN_FOV, Self::MAX_FOV);
+    }
+
+    /// The world to view space matrix.
+    ///
+    /// Built from the camera basis: the transposed rotation of the basis
+    /// vectors times the translation to the camera position.
+    pub fn view_matrix(&self) -> Mat4 {
+        // The camera looks down its negative z axis, so z points backwards.
+        let direction = -self.front();
+        let right = self.world_up.cross(direction).normalize();
+        let up = direction.cross(right);
+
+        let rotation = Mat4::from_mat3(Mat3::from_cols(right, up, direction).transpose());
+        let mut translation = Mat4::IDENTITY;
+        translation.w_axis = Vec4::from((-self.position, 1.0));
+
+        rotation * translation
+    }
+
+    /// The view to clip space matrix for the given width / height ratio.
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective_rh_gl(self.fov.to_radians(), aspect, self.near, self.far)
+    }
+}
+
+impl Camera for FlyCamera {
+    fn view_matrix(&self) -> Mat4 {
+        self.view_matrix()
+    }
+
+    fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        self.projection_matrix(aspect)
+    }
+
+    fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    fn process_mouse(&mut self, x_offset: f32, y_offset: f32) {
+        self.process_mouse(x_offset, y_offset);
+    }
+
+    fn process_movement(&mut self, direction: CameraMovement, delta_time: f32) {
+        self.process_movement(direction, delta_time);
+    }
+
+    fn zoom(&mut self, amount: f32) {
+        self.zoom(amount);
+    }
+}
+
+/// How mouse motion turns an [`OrbitCamera`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitMode {
+    /// Yaw around the world up axis and pitch above or below the horizon.
+    /// The camera never rolls and can't go over the poles.
+    YawPitch,
+    /// Free rotation like dragging a ball around the target, the camera can roll.
+    Arcball,
+}
+
+/// A camera that circles around a target point, for inspecting a single model.
+///
+/// Angles are in degrees. With a yaw and pitch of 0 the camera sits on the
+/// positive z axis of the target and looks down the negative z axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitCamera {
+    pub target: Vec3,
+    /// Distance from the camera to the target.
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub yaw: f32,
+    /// Angle of the camera above the horizon of the target.
+    pub pitch: f32,
+    /// Vertical field of view.
+    pub fov: f32,
+    /// Degrees per pixel of mouse motion.
+    pub sensitivity: f32,
+    /// Fraction of the distance one wheel step zooms by.
+    pub zoom_speed: f32,
+    /// Fraction of the distance one pixel of panning moves the target by.
+    pub pan_speed: f32,
+    /// Units per second of keyboard movement.
+    pub speed: f32,
+    pub near: f32,
+    pub far: f32,
+    mode: OrbitMode,
+    arcball: Quat,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            target: Vec3::ZERO,
+            distance: 5.0,
+            min_distance: 0.5,
+            max_distance: 50.0,
+            yaw: 0.0,
+            pitch: 0.0,
+            fov: 45.0,
+            sensitivity: 0.3,
+            zoom_speed: 0.1,
+            pan_speed: 0.002,
+            speed: 5.0,
+            near: 0.1,
+            far: 100.0,
+            mode: OrbitMode::YawPitch,
+            arcball: Quat::IDENTITY,
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// Keeps the yaw pitch mode from going over the poles.
+    pub const MAX_PITCH: f32 = 89.0;
+
+    /// Makes a camera orbiting the target at the given distance.
+    pub fn new(target: Vec3, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            ..Self::default()
+        }
+    }
+
+    pub fn mode(&self) -> OrbitMode {
+        self.mode
+    }
+
+    /// Switches how mouse motion turns the camera, keeping the current view.
+    pub fn set_mode(&mut self, mode: OrbitMode) {
+        match (self.mode, mode) {
+            (OrbitMode::YawPitch, OrbitMode::Arcball) => self.arcball = self.orientation(),
+            (OrbitMode::Arcball, OrbitMode::YawPitch) => {
+                // Roll can't be expressed with yaw and pitch, so it is dropped.
+                let offset = self.orientation() * Vec3::Z;
+                self.yaw = offset.x.atan2(offset.z).to_degrees();
+                self.pitch = offset
+                    .y
+                    .clamp(-1.0, 1.0)
+                    .asin()
+                    .to_degrees()
+                    .clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+            }
+            _ => (),
+        }
+        self.mode = mode;
+    }
+
+    /// Rotation of the camera, taking the view space axes to world space.
+    pub fn orientation(&self) -> Quat {
+        match self.mode {
+            OrbitMode::YawPitch => {
+                Quat::from_rotation_y(self.yaw.to_radians())
+                    * Quat::from_rotation_x(-self.pitch.to_radians())
+            }
+            OrbitMode::Arcball => self.arcball,
+        }
+    }
+
+    /// Where the camera is in world space.
+    pub fn eye(&self) -> Vec3 {
+        self.target + self.orientation() * Vec3::Z * self.distance
+    }
+
+    /// The up direction of the camera, unit length.
+    pub fn up(&self) -> Vec3 {
+        self.orientation() * Vec3::Y
+    }
+
+    /// The direction to the right of the camera, unit length.
+    pub fn right(&self) -> Vec3 {
+        self.orientation() * Vec3::X
+    }
+
+    /// Rotates around the target by a mouse motion in pixels.
+    ///
+    /// Dragging moves the scene along with the mouse, as if it was grabbed.
+    pub fn orbit(&mut self, x_offset: f32, y_offset: f32) {
+        match self.mode {
+            OrbitMode::YawPitch => {
+                self.yaw -= x_offset * self.sensitivity;
+                self.pitch = (self.pitch + y_offset * self.sensitivity)
+                    .clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+            }
+            OrbitMode::Arcball => {
+                // Spin around the view space axis perpendicular to the drag.
+                let drag = Vec2::new(x_offset, -y_offset);
+                if drag.length_squared() > 0.0 {
+                    let axis = Vec3::new(drag.y, -drag.x, 0.0).normalize();
+                    let angle = (drag.length() * self.sensitivity).to_radians();
+                    self.arcball = (self.arcball * Quat::from_axis_angle(axis, angle)).normalize();
+                }
+            }
+        }
+    }
+
+    /// Arcball rotation for a drag between two cursor positions, given in
+    /// normalized device coordinates (`[-1, 1]`, y up).
+    ///
+    /// Switches to [`OrbitMode::Arcball`].
+    pub fn arcball_rotate(&mut self, from: Vec2, to: Vec2) {
+        self.set_mode(OrbitMode::Arcball);
+
+        let (from, to) = (arcball_point(from), arcball_point(to));
+        // Turning the scene from `from` to `to` is turning the camera the other way.
+        self.arcball = (self.arcball * Quat::from_rotation_arc(to, from)).normalize();
+    }
+
+    /// Moves the target on the view plane by a mouse motion in pixels, so the
+    /// scene follows the mouse.
+    pub fn pan(&mut self, x_offset: f32, y_offset: f32) {
+        let scale = self.pan_speed * self.distance;
+        self.target += (-self.right() * x_offset + self.up() * y_offset) * scale;
+    }
+
+    /// Moves the camera closer by `amount` wheel steps, a negative amount moves it away.
+    pub fn zoom(&mut self, amount: f32) {
+        self.distance = (self.distance * (1.0 - self.zoom_speed).powf(amount))
+            .clamp(self.min_distance, self.max_distance);
+    }
+
+    /// The world to view space matrix.
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_at_rh(self.eye(), self.target, self.up())
+    }
+
+    /// The view to clip space matrix for the given width / height ratio.
+    pub fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        Mat4::perspective_rh_gl(self.fov.to_radians(), aspect, self.near, self.far)
+    }
+}
+
+/// Maps a point of the screen onto the arcball: a unit sphere in the middle,
+/// blending into a hyperbolic sheet further out so drags outside stay smooth.
+fn arcball_point(point: Vec2) -> Vec3 {
+    let length_squared = point.length_squared();
+    if length_squared <= 0.5 {
+        point.extend((1.0 - length_squared).sqrt())
+    } else {
+        point.extend(0.5 / length_squared.sqrt()).normalize()
+    }
+}
+
+impl Camera for OrbitCamera {
+    fn view_matrix(&self) -> Mat4 {
+        self.view_matrix()
+    }
+
+    fn projection_matrix(&self, aspect: f32) -> Mat4 {
+        self.projection_matrix(aspect)
+    }
+
+    fn position(&self) -> Vec3 {
+        self.eye()
+    }
+
+    fn process_mouse(&mut self, x_offset: f32, y_offset: f32) {
+        self.orbit(x_offset, y_offset);
+    }
+
+    /// Forward and backward move towards and away from the target, the rest pans.
+    fn process_movement(&mut self, direction: CameraMovement, delta_time: f32) {
+        let distance = self.speed * delta_time;
+        match direction {
+            CameraMovement::Forward => {
+                self.distance = (self.distance - distance).max(self.min_distance)
+            }
+            CameraMovement::Backward => {
+                self.distance = (self.distance + distance).min(self.max_distance)
+            }
+            CameraMovement::Right => self.target += self.right() * distance,
+            CameraMovement::Left => self.target -= self.right() * distance,
+            CameraMovement::Up => self.target += self.up() * distance,
+            CameraMovement::Down => self.target -= self.up() * distance,
+        }
+    }
+
+    fn zoom(&mut self, amount: f32) {
+        self.zoom(amount);
+    }
+}
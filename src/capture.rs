@@ -0,0 +1,252 @@
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use image::{ImageError, RgbaImage};
+
+use crate::learn_opengl::read_pixels;
+
+/// How many frames may wait for the encoder before the render thread blocks.
+///
+/// Recordings run on simulated time, so waiting does not change what ends up
+/// in them, it only keeps a slow encoder from eating all the memory.
+const QUEUE_LEN: usize = 8;
+
+/// Errors of writing a capture, reported by [`Capture::finished`].
+#[derive(Debug)]
+pub enum CaptureError {
+    CreateDir { path: PathBuf, error: io::Error },
+    Save { path: PathBuf, error: ImageError },
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaptureError::CreateDir { path, error } => {
+                write!(f, "could not create {}: {}", path.display(), error)
+            }
+            CaptureError::Save { path, error } => {
+                write!(f, "could not save {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl Error for CaptureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CaptureError::CreateDir { error, .. } => Some(error),
+            CaptureError::Save { error, .. } => Some(error),
+        }
+    }
+}
+
+struct Job {
+    image: RgbaImage,
+    path: PathBuf,
+}
+
+struct Recording {
+    dir: PathBuf,
+    frame_rate: f32,
+    frames: u64,
+}
+
+/// Grabs finished frames and writes them as PNGs: single timestamped
+/// screenshots, or numbered sequences while recording.
+///
+/// Call [`Capture::end_frame`] after drawing and before presenting. Encoding
+/// happens on a thread of its own, dropping the capture waits for the frames
+/// still queued.
+pub struct Capture {
+    dir: PathBuf,
+    jobs: Option<SyncSender<Job>>,
+    finished: Receiver<Result<PathBuf, CaptureError>>,
+    encoder: Option<JoinHandle<()>>,
+    screenshot_requested: bool,
+    recording: Option<Recording>,
+}
+
+impl Capture {
+    /// Captures go into `dir`, which is created when the first one is written.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let (jobs, queue) = mpsc::sync_channel::<Job>(QUEUE_LEN);
+        let (report, finished) = mpsc::channel();
+        let encoder = thread::Builder::new()
+            .name("capture encoder".to_string())
+            .spawn(move || {
+                for Job { image, path } in queue {
+                    let result = save(&image, &path).map(|()| path);
+                    // Nobody listening any more is fine, the file is written
+                    let _ = report.send(result);
+                }
+            })
+            .expect("Could not start the capture encoder thread");
+
+        Self {
+            dir: dir.into(),
+            jobs: Some(jobs),
+            finished,
+            encoder: Some(encoder),
+            screenshot_requested: false,
+            recording: None,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Saves the next finished frame as `screenshot-<time>.png`.
+    pub fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+
+    /// Starts saving every finished frame as `frame-000000.png` and up, in a
+    /// new `recording-<time>` directory, and returns that directory.
+    ///
+    /// `frame_rate` is only recorded, the caller has to advance its clock by
+    /// exactly one frame of it per frame, see
+    /// [`FrameClock::set_frame_time`](crate::clock::FrameClock::set_frame_time).
+    pub fn start_recording(&mut self, frame_rate: f32) -> &Path {
+        let dir = self
+            .dir
+            .join(format!("recording-{}", timestamp(SystemTime::now())));
+        &self
+            .recording
+            .insert(Recording {
+                dir,
+                frame_rate,
+                frames: 0,
+            })
+            .dir
+    }
+
+    /// Stops recording and returns how many frames were recorded, `None`
+    /// when there was no recording.
+    pub fn stop_recording(&mut self) -> Option<u64> {
+        self.recording.take().map(|recording| recording.frames)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// The frame rate of the recording, `None` when not recording.
+    pub fn recording_frame_rate(&self) -> Option<f32> {
+        self.recording
+            .as_ref()
+            .map(|recording| recording.frame_rate)
+    }
+
+    /// Whether the frame being drawn is going to be captured.
+    pub fn wants_frame(&self) -> bool {
+        self.screenshot_requested || self.recording.is_some()
+    }
+
+    /// Reads the back buffer of the default framebuffer, if this frame is
+    /// captured at all. Has to be called before the buffers are swapped.
+    pub fn end_frame(&mut self, width: u32, height: u32) {
+        if self.wants_frame() {
+            self.submit(read_pixels(0, 0, width, height));
+        }
+    }
+
+    /// Queues a finished frame for whatever captures are waiting for one.
+    pub fn submit(&mut self, image: RgbaImage) {
+        let mut paths = Vec::new();
+        if self.screenshot_requested {
+            self.screenshot_requested = false;
+            paths.push(
+                self.dir
+                    .join(format!("screenshot-{}.png", timestamp(SystemTime::now()))),
+            );
+        }
+        if let Some(recording) = &mut self.recording {
+            paths.push(
+                recording
+                    .dir
+                    .join(format!("frame-{:06}.png", recording.frames)),
+            );
+            recording.frames += 1;
+        }
+
+        let Some((last, rest)) = paths.split_last() else {
+            return;
+        };
+        for path in rest {
+            self.queue(image.clone(), path.clone());
+        }
+        self.queue(image, last.clone());
+    }
+
+    fn queue(&self, image: RgbaImage, path: PathBuf) {
+        if let Some(jobs) = &self.jobs {
+            jobs.send(Job { image, path })
+                .expect("The capture encoder thread stopped");
+        }
+    }
+
+    /// The captures written since the last call, with their paths.
+    pub fn finished(&self) -> impl Iterator<Item = Result<PathBuf, CaptureError>> + '_ {
+        self.finished.try_iter()
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        // Closing the queue lets the encoder finish what is left and stop
+        self.jobs = None;
+        if let Some(encoder) = self.encoder.take() {
+            let _ = encoder.join();
+        }
+    }
+}
+
+fn save(image: &RgbaImage, path: &Path) -> Result<(), CaptureError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|error| CaptureError::CreateDir {
+            path: dir.to_path_buf(),
+            error,
+        })?;
+    }
+    image.save(path).map_err(|error| CaptureError::Save {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// `YYYYMMDD-HHMMSS-mmm` in UTC, sorting in the order the captures were made.
+pub fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Days to a civil date, after Howard Hinnant's `civil_from_days`
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
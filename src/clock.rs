@@ -0,0 +1,276 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// Where a [`FrameClock`] reads the time from.
+pub trait TimeSource {
+    /// Time since some fixed point, which must never go backwards.
+    fn now(&self) -> Duration;
+}
+
+/// The real, monotonic time since the source was made.
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicTime {
+    start: Instant,
+}
+
+impl Default for MonotonicTime {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl TimeSource for MonotonicTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Time that only moves when told to, for tests and for recording at a fixed rate.
+///
+/// Clones share the same time, so one can be handed to a clock and the other
+/// kept to move it.
+#[derive(Debug, Clone, Default)]
+pub struct ManualTime {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualTime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+
+    pub fn advance_secs(&self, secs: f32) {
+        self.advance(Duration::from_secs_f32(secs));
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// Measures frames and turns them into game time.
+///
+/// Call [`FrameClock::tick`] once at the start of every frame. Game time is
+/// real time scaled by [`FrameClock::time_scale`] and stands still while
+/// paused; animations should read [`FrameClock::time`] or
+/// [`FrameClock::delta`], things that must keep working while paused (like
+/// the camera) [`FrameClock::real_delta`].
+///
+/// [`FrameClock::update`] runs simulation steps of a fixed length for the game
+/// time that passed, and [`FrameClock::alpha`] says how far between the last
+/// two steps the frame is, for interpolating what gets drawn.
+#[derive(Debug, Clone)]
+pub struct FrameClock<T: TimeSource = MonotonicTime> {
+    source: T,
+    last_tick: Duration,
+    real_delta: f32,
+    real_time: f64,
+    delta: f32,
+    time: f64,
+    accumulator: f64,
+    fixed_step: f32,
+    max_delta: f32,
+    frame_time: Option<f32>,
+    time_scale: f32,
+    paused: bool,
+    step_requested: bool,
+    frame: u64,
+}
+
+impl FrameClock<MonotonicTime> {
+    /// A clock running on real time, starting now.
+    pub fn new() -> Self {
+        Self::with_time_source(MonotonicTime::default())
+    }
+}
+
+impl Default for FrameClock<MonotonicTime> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TimeSource> FrameClock<T> {
+    /// Simulation steps per second unless changed with [`FrameClock::set_fixed_step`].
+    pub const DEFAULT_UPDATE_RATE: f32 = 60.0;
+
+    /// A clock reading its time from the given source, starting at its current time.
+    pub fn with_time_source(source: T) -> Self {
+        Self {
+            last_tick: source.now(),
+            source,
+            real_delta: 0.0,
+            real_time: 0.0,
+            delta: 0.0,
+            time: 0.0,
+            accumulator: 0.0,
+            fixed_step: 1.0 / Self::DEFAULT_UPDATE_RATE,
+            max_delta: 0.25,
+            frame_time: None,
+            time_scale: 1.0,
+            paused: false,
+            step_requested: false,
+            frame: 0,
+        }
+    }
+
+    pub fn time_source(&self) -> &T {
+        &self.source
+    }
+
+    /// Starts a new frame, measuring the time since the last one.
+    ///
+    /// Returns the game time delta, the same as [`FrameClock::delta`].
+    pub fn tick(&mut self) -> f32 {
+        let now = self.source.now();
+        let measured = now.saturating_sub(self.last_tick).as_secs_f32();
+        let real_delta = self.frame_time.unwrap_or(measured);
+        self.last_tick = now;
+        self.frame += 1;
+
+        self.real_delta = real_delta;
+        self.real_time += f64::from(real_delta);
+
+        // A long stall, like dragging the window, would otherwise run a burst of
+        // simulation steps to catch up
+        let clamped = real_delta.min(self.max_delta);
+        self.delta = if !self.paused {
+            clamped * self.time_scale
+        } else if self.step_requested {
+            self.step_requested = false;
+            self.fixed_step
+        } else {
+            0.0
+        };
+        self.time += f64::from(self.delta);
+        self.accumulator += f64::from(self.delta);
+        self.delta
+    }
+
+    /// Runs `step` once for every whole fixed step of game time that has built
+    /// up, handing it the step length. Returns how many steps ran.
+    pub fn update(&mut self, mut step: impl FnMut(f32)) -> u32 {
+        let fixed_step = f64::from(self.fixed_step);
+        let mut steps = 0;
+        while self.accumulator >= fixed_step {
+            step(self.fixed_step);
+            self.accumulator -= fixed_step;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// How far the game time is past the last fixed step, as a fraction of a
+    /// step from 0 up to 1.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / f64::from(self.fixed_step)).min(1.0) as f32
+    }
+
+    /// Game time since the last frame in seconds.
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// Real time since the last frame in seconds, ignoring pause and time scale.
+    pub fn real_delta(&self) -> f32 {
+        self.real_delta
+    }
+
+    /// Game time since the clock started in seconds.
+    pub fn time(&self) -> f32 {
+        self.time as f32
+    }
+
+    /// Real time since the clock started in seconds.
+    pub fn real_time(&self) -> f32 {
+        self.real_time as f32
+    }
+
+    /// How many times the clock has ticked.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// The length of a simulation step in seconds.
+    pub fn fixed_step(&self) -> f32 {
+        self.fixed_step
+    }
+
+    pub fn set_fixed_step(&mut self, seconds: f32) {
+        assert!(seconds > 0.0, "the fixed step has to be longer than 0");
+        self.fixed_step = seconds;
+    }
+
+    /// The longest frame in seconds that counts towards game time.
+    pub fn max_delta(&self) -> f32 {
+        self.max_delta
+    }
+
+    pub fn set_max_delta(&mut self, seconds: f32) {
+        self.max_delta = seconds.max(0.0);
+    }
+
+    /// The length every frame pretends to have, `None` when frames are measured.
+    pub fn frame_time(&self) -> Option<f32> {
+        self.frame_time
+    }
+
+    /// Makes every tick count as exactly `seconds` of real time, however long
+    /// it really took, so recordings play back at their own frame rate.
+    /// `None` goes back to measuring.
+    pub fn set_frame_time(&mut self, seconds: Option<f32>) {
+        if let Some(seconds) = seconds {
+            assert!(seconds > 0.0, "the frame time has to be longer than 0");
+        }
+        self.frame_time = seconds;
+    }
+
+    /// How fast game time runs compared to real time.
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Sets how fast game time runs, negative scales count as 0.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.time_scale = scale.max(0.0);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.step_requested = false;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// While paused, lets exactly one fixed step of game time pass on the next tick.
+    pub fn step(&mut self) {
+        if self.paused {
+            self.step_requested = true;
+        }
+    }
+}
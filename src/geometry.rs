@@ -0,0 +1,145 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::{
+    learn_opengl::{GlContext, Mesh, PrimitiveMode},
+    vertex_layout,
+};
+
+pub mod gltf;
+pub mod obj;
+pub mod primitives;
+
+vertex_layout! {
+    /// The vertex generated geometry is made of.
+    #[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+    pub struct Vertex {
+        #[attrib(location = 0)]
+        pub position: Vec3,
+        #[attrib(location = 1)]
+        pub tex_coord: Vec2,
+        #[attrib(location = 2)]
+        /// Unit length, pointing out of the front face.
+        pub normal: Vec3,
+        #[attrib(location = 3)]
+        /// The direction `u` grows in, unit length. `w` is 1 or -1, the sign of
+        /// the bitangent `cross(normal, tangent.xyz) * w` that points the way
+        /// `v` grows in.
+        pub tangent: Vec4,
+    }
+}
+
+vertex_layout! {
+    /// The per-instance attributes for drawing many copies of a mesh with
+    /// [`Mesh::draw_instances`].
+    ///
+    /// The model matrix takes up locations 4 to 7, one column each.
+    #[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+    pub struct Instance {
+        #[attrib(location = 4, divisor = 1)]
+        pub model: Mat4,
+        #[attrib(location = 8, divisor = 1)]
+        /// Multiplies the color of the instance, shaders are free to ignore it.
+        pub color: Vec4,
+    }
+}
+
+impl Instance {
+    /// An untinted instance.
+    pub fn new(model: Mat4) -> Self {
+        Self::with_color(model, Vec4::ONE)
+    }
+
+    pub fn with_color(model: Mat4, color: Vec4) -> Self {
+        Self { model, color }
+    }
+}
+
+/// Indexed triangles on the CPU, ready to be uploaded as a [`Mesh`].
+///
+/// Front faces wind counter-clockwise.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of triangles.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// The vertex positions of every triangle.
+    pub fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|triangle| {
+            [
+                self.vertices[triangle[0] as usize].position,
+                self.vertices[triangle[1] as usize].position,
+                self.vertices[triangle[2] as usize].position,
+            ]
+        })
+    }
+
+    /// Adds a vertex and returns its index.
+    pub fn push_vertex(&mut self, vertex: Vertex) -> u32 {
+        self.vertices.push(vertex);
+        (self.vertices.len() - 1) as u32
+    }
+
+    pub fn push_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.extend_from_slice(&[a, b, c]);
+    }
+
+    /// Fills in the tangents from the texture coordinates and normals.
+    ///
+    /// The directions `u` and `v` grow in are averaged over the triangles
+    /// around each vertex, weighted by their size. Vertices without usable
+    /// texture coordinates get some tangent perpendicular to their normal.
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![Vec3::ZERO; self.vertices.len()];
+        let mut bitangents = vec![Vec3::ZERO; self.vertices.len()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| &self.vertices[triangle[i] as usize]);
+            let (edge1, edge2) = (b.position - a.position, c.position - a.position);
+            let (duv1, duv2) = (b.tex_coord - a.tex_coord, c.tex_coord - a.tex_coord);
+            let determinant = duv1.perp_dot(duv2);
+            if determinant.abs() < f32::EPSILON {
+                continue;
+            }
+            let area = edge1.cross(edge2).length();
+            let dp_du = ((edge1 * duv2.y - edge2 * duv1.y) / determinant).normalize_or_zero();
+            let dp_dv = ((edge2 * duv1.x - edge1 * duv2.x) / determinant).normalize_or_zero();
+            for &index in triangle {
+                tangents[index as usize] += dp_du * area;
+                bitangents[index as usize] += dp_dv * area;
+            }
+        }
+
+        for ((vertex, tangent), bitangent) in self.vertices.iter_mut().zip(tangents).zip(bitangents)
+        {
+            let normal = vertex.normal;
+            // Gram-Schmidt, so the tangent is perpendicular to the normal
+            let mut tangent = (tangent - normal * normal.dot(tangent)).normalize_or_zero();
+            if tangent == Vec3::ZERO {
+                tangent = normal.any_orthonormal_vector();
+            }
+            let handedness = if normal.cross(tangent).dot(bitangent) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = tangent.extend(handedness);
+        }
+    }
+
+    /// Uploads the vertices and indices into a new mesh of triangles.
+    pub fn upload<'gl>(&self, gl: &'gl GlContext) -> Option<Mesh<'gl, Vertex>> {
+        Mesh::with_indices(gl, &self.vertices, &self.indices, PrimitiveMode::Triangles)
+    }
+}
@@ -0,0 +1,724 @@
+//! Importing of glTF 2.0 scenes, from `.gltf` files with external or
+//! base64 embedded buffers and from binary `.glb` files.
+//!
+//! Meshes are read into [`MeshData`], materials keep the parameters of the
+//! PBR metallic-roughness model and refer to textures by index, and textures
+//! know how to build a [`Texture2D`] with their sampler. Only the first set of
+//! texture coordinates is read.
+
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use ::gltf::{
+    image::Format,
+    material::AlphaMode as GltfAlphaMode,
+    mesh::Mode,
+    texture::{MagFilter, MinFilter, WrappingMode},
+    Document, Gltf,
+};
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+use image::{DynamicImage, ImageBuffer};
+
+use super::{MeshData, Vertex};
+use crate::learn_opengl::{
+    GlContext, Mesh, Texture2D, Texture2DBuilder, TextureFilter, TextureWrap,
+};
+
+/// The extensions the importer understands. Scenes that require any other
+/// extension are rejected, scenes that merely use one load with it listed in
+/// [`GltfScene::unsupported_extensions`].
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["KHR_materials_emissive_strength"];
+
+/// Everything that can go wrong while importing a scene.
+#[derive(Debug)]
+pub enum GltfError {
+    /// The scene file could not be read.
+    Read { path: String, error: io::Error },
+    /// The document is invalid, or its buffers or images could not be loaded.
+    Gltf(::gltf::Error),
+    /// The scene needs extensions the importer does not support.
+    UnsupportedExtensions(Vec<String>),
+    /// A primitive made of points or lines, which can't be drawn as triangles.
+    UnsupportedMode {
+        mesh: usize,
+        primitive: usize,
+        mode: Mode,
+    },
+    /// A primitive without vertex positions.
+    MissingPositions { mesh: usize, primitive: usize },
+    /// A vertex attribute of a primitive with another number of elements
+    /// than there are positions.
+    AttributeCount {
+        mesh: usize,
+        primitive: usize,
+        attribute: &'static str,
+        count: usize,
+        positions: usize,
+    },
+    /// An index of a primitive that points past its vertices.
+    IndexOutOfRange {
+        mesh: usize,
+        primitive: usize,
+        index: u32,
+        vertices: usize,
+    },
+    /// A node that is its own ancestor, the hierarchy has to be a tree.
+    NodeCycle { node: usize },
+    /// A node that is the child of more than one node, or a root of the scene
+    /// that is also a child.
+    MultipleParents { node: usize },
+    /// An image whose pixels don't add up to its size.
+    InvalidImage { image: usize },
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfError::Read { path, error } => write!(f, "could not read {}: {}", path, error),
+            GltfError::Gltf(error) => write!(f, "invalid glTF: {}", error),
+            GltfError::UnsupportedExtensions(extensions) => {
+                write!(
+                    f,
+                    "unsupported required extensions: {}",
+                    extensions.join(", ")
+                )
+            }
+            GltfError::UnsupportedMode {
+                mesh,
+                primitive,
+                mode,
+            } => write!(
+                f,
+                "primitive {} of mesh {} is drawn as {:?}, only triangles are supported",
+                primitive, mesh, mode
+            ),
+            GltfError::MissingPositions { mesh, primitive } => {
+                write!(
+                    f,
+                    "primitive {} of mesh {} has no positions",
+                    primitive, mesh
+                )
+            }
+            GltfError::AttributeCount {
+                mesh,
+                primitive,
+                attribute,
+                count,
+                positions,
+            } => write!(
+                f,
+                "primitive {} of mesh {} has {} {} values for {} positions",
+                primitive, mesh, count, attribute, positions
+            ),
+            GltfError::IndexOutOfRange {
+                mesh,
+                primitive,
+                index,
+                vertices,
+            } => write!(
+                f,
+                "primitive {} of mesh {} uses index {}, but has only {} vertices",
+                primitive, mesh, index, vertices
+            ),
+            GltfError::NodeCycle { node } => write!(f, "node {} is its own ancestor", node),
+            GltfError::MultipleParents { node } => {
+                write!(f, "node {} has more than one parent", node)
+            }
+            GltfError::InvalidImage { image } => {
+                write!(f, "image {} has the wrong number of pixels", image)
+            }
+        }
+    }
+}
+
+impl Error for GltfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GltfError::Read { error, .. } => Some(error),
+            GltfError::Gltf(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<::gltf::Error> for GltfError {
+    fn from(error: ::gltf::Error) -> Self {
+        GltfError::Gltf(error)
+    }
+}
+
+/// How the alpha of the base color is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// Alpha is ignored.
+    #[default]
+    Opaque,
+    /// Fragments with an alpha below the cutoff are discarded.
+    Mask,
+    /// Alpha blends with what is behind.
+    Blend,
+}
+
+/// A texture used by a material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureRef {
+    /// Index into [`GltfScene::textures`].
+    pub texture: usize,
+    /// The set of texture coordinates to sample with.
+    pub tex_coord: u32,
+}
+
+impl TextureRef {
+    fn new(info: ::gltf::texture::Info<'_>) -> Self {
+        Self {
+            texture: info.texture().index(),
+            tex_coord: info.tex_coord(),
+        }
+    }
+}
+
+/// A PBR metallic-roughness material.
+///
+/// Base color and emissive textures hold sRGB colors, the other textures
+/// linear data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    /// Linear RGBA, multiplied with the base color texture.
+    pub base_color: Vec4,
+    pub base_color_texture: Option<TextureRef>,
+    /// Multiplied with the blue channel of the metallic-roughness texture.
+    pub metallic: f32,
+    /// Multiplied with the green channel of the metallic-roughness texture.
+    pub roughness: f32,
+    pub metallic_roughness_texture: Option<TextureRef>,
+    pub normal_texture: Option<TextureRef>,
+    /// Scales the X and Y of the normals from the normal texture.
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<TextureRef>,
+    /// How much of the occlusion texture is applied, between 0 and 1.
+    pub occlusion_strength: f32,
+    /// Linear RGB, already multiplied by the emissive strength.
+    pub emissive: Vec3,
+    pub emissive_texture: Option<TextureRef>,
+    pub alpha_mode: AlphaMode,
+    /// Only used by [`AlphaMode::Mask`].
+    pub alpha_cutoff: f32,
+    /// Whether back faces are drawn, lit with flipped normals.
+    pub double_sided: bool,
+}
+
+impl Default for GltfMaterial {
+    /// The material of primitives that don't name one.
+    fn default() -> Self {
+        Self {
+            name: None,
+            base_color: Vec4::ONE,
+            base_color_texture: None,
+            metallic: 1.0,
+            roughness: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            occlusion_texture: None,
+            occlusion_strength: 1.0,
+            emissive: Vec3::ZERO,
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+impl GltfMaterial {
+    fn new(material: ::gltf::Material<'_>) -> Self {
+        let pbr = material.pbr_metallic_roughness();
+        let normal = material.normal_texture();
+        let occlusion = material.occlusion_texture();
+        let emissive_strength = material.emissive_strength().unwrap_or(1.0);
+        Self {
+            name: material.name().map(str::to_string),
+            base_color: Vec4::from_array(pbr.base_color_factor()),
+            base_color_texture: pbr.base_color_texture().map(TextureRef::new),
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            metallic_roughness_texture: pbr.metallic_roughness_texture().map(TextureRef::new),
+            normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+            normal_texture: normal.map(|normal| TextureRef {
+                texture: normal.texture().index(),
+                tex_coord: normal.tex_coord(),
+            }),
+            occlusion_strength: occlusion
+                .as_ref()
+                .map_or(1.0, |occlusion| occlusion.strength()),
+            occlusion_texture: occlusion.map(|occlusion| TextureRef {
+                texture: occlusion.texture().index(),
+                tex_coord: occlusion.tex_coord(),
+            }),
+            emissive: Vec3::from_array(material.emissive_factor()) * emissive_strength,
+            emissive_texture: material.emissive_texture().map(TextureRef::new),
+            alpha_mode: match material.alpha_mode() {
+                GltfAlphaMode::Opaque => AlphaMode::Opaque,
+                GltfAlphaMode::Mask => AlphaMode::Mask,
+                GltfAlphaMode::Blend => AlphaMode::Blend,
+            },
+            alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+            double_sided: material.double_sided(),
+        }
+    }
+}
+
+/// An image together with the way it is sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GltfTexture {
+    /// Index into [`GltfScene::images`].
+    pub image: usize,
+    pub wrap_s: TextureWrap,
+    pub wrap_t: TextureWrap,
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    pub mipmaps: bool,
+}
+
+impl GltfTexture {
+    fn new(texture: ::gltf::Texture<'_>) -> Self {
+        let sampler = texture.sampler();
+        let wrap = |mode| match mode {
+            WrappingMode::ClampToEdge => TextureWrap::ClampToEdge,
+            WrappingMode::MirroredRepeat => TextureWrap::MirroredRepeat,
+            WrappingMode::Repeat => TextureWrap::Repeat,
+        };
+        // The builder picks the mipmap filter to match the texel filter
+        let (min_filter, mipmaps) = match sampler.min_filter() {
+            Some(MinFilter::Nearest) => (TextureFilter::Nearest, false),
+            Some(MinFilter::Linear) => (TextureFilter::Linear, false),
+            Some(MinFilter::NearestMipmapNearest | MinFilter::NearestMipmapLinear) => {
+                (TextureFilter::Nearest, true)
+            }
+            Some(MinFilter::LinearMipmapNearest | MinFilter::LinearMipmapLinear) | None => {
+                (TextureFilter::Linear, true)
+            }
+        };
+        Self {
+            image: texture.source().index(),
+            wrap_s: wrap(sampler.wrap_s()),
+            wrap_t: wrap(sampler.wrap_t()),
+            min_filter,
+            mag_filter: match sampler.mag_filter() {
+                Some(MagFilter::Nearest) => TextureFilter::Nearest,
+                Some(MagFilter::Linear) | None => TextureFilter::Linear,
+            },
+            mipmaps,
+        }
+    }
+
+    /// A texture builder set up with the sampling options of this texture.
+    pub fn builder(&self) -> Texture2DBuilder {
+        Texture2D::builder()
+            .wrap_s(self.wrap_s)
+            .wrap_t(self.wrap_t)
+            .min_filter(self.min_filter)
+            .mag_filter(self.mag_filter)
+            .mipmaps(self.mipmaps)
+    }
+}
+
+/// Triangles drawn with a single material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfPrimitive {
+    pub data: MeshData,
+    /// Index into [`GltfScene::materials`], `None` for the default material.
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+/// A node of the scene hierarchy, placed relative to its parent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    /// Index into [`GltfScene::meshes`].
+    pub mesh: Option<usize>,
+    /// Indices into [`GltfScene::nodes`].
+    pub children: Vec<usize>,
+}
+
+impl GltfNode {
+    /// The transform from the space of this node to that of its parent.
+    pub fn local_transform(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+/// An imported glTF document.
+///
+/// Everything refers to everything else by index, the same way the document does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfScene {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub textures: Vec<GltfTexture>,
+    pub images: Vec<DynamicImage>,
+    pub nodes: Vec<GltfNode>,
+    /// The top level nodes of the default scene, or of the first scene if
+    /// there is no default.
+    pub roots: Vec<usize>,
+    /// Extensions the document uses that were ignored while importing it.
+    pub unsupported_extensions: Vec<String>,
+}
+
+impl GltfScene {
+    /// Imports a `.gltf` or `.glb` file, external buffers and images are
+    /// looked up next to it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GltfError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|error| GltfError::Read {
+            path: path.display().to_string(),
+            error,
+        })?;
+        Self::parse(&bytes, path.parent().unwrap_or(Path::new(".")))
+    }
+
+    /// Imports a document from memory, either JSON or binary glTF. External
+    /// buffers and images are looked up in `base`.
+    pub fn parse(bytes: &[u8], base: &Path) -> Result<Self, GltfError> {
+        let Gltf { document, blob } = Gltf::from_slice_without_validation(bytes)?;
+        let unsupported = |extension: &&str| !SUPPORTED_EXTENSIONS.contains(extension);
+
+        let required: Vec<String> = document
+            .extensions_required()
+            .filter(unsupported)
+            .map(str::to_string)
+            .collect();
+        if !required.is_empty() {
+            return Err(GltfError::UnsupportedExtensions(required));
+        }
+        let unsupported_extensions = document
+            .extensions_used()
+            .filter(unsupported)
+            .map(str::to_string)
+            .collect();
+
+        let document = Document::from_json(document.into_json())?;
+        let buffers = ::gltf::import_buffers(&document, Some(base), blob)?;
+        let images = ::gltf::import_images(&document, Some(base), &buffers)?;
+
+        let mut meshes = Vec::new();
+        for mesh in document.meshes() {
+            let mut primitives = Vec::new();
+            for primitive in mesh.primitives() {
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                let error_location = (mesh.index(), primitive.index());
+                let mut data = read_primitive(&reader, primitive.mode(), error_location)?;
+                // Tangents are ignored when normals are missing
+                if reader.read_tangents().is_none() || reader.read_normals().is_none() {
+                    data.compute_tangents();
+                }
+                primitives.push(GltfPrimitive {
+                    data,
+                    material: primitive.material().index(),
+                });
+            }
+            meshes.push(GltfMesh {
+                name: mesh.name().map(str::to_string),
+                primitives,
+            });
+        }
+
+        let nodes: Vec<GltfNode> = document
+            .nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                GltfNode {
+                    name: node.name().map(str::to_string),
+                    translation: Vec3::from_array(translation),
+                    rotation: Quat::from_array(rotation),
+                    scale: Vec3::from_array(scale),
+                    mesh: node.mesh().map(|mesh| mesh.index()),
+                    children: node.children().map(|child| child.index()).collect(),
+                }
+            })
+            .collect();
+
+        let roots: Vec<usize> = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| scene.nodes().map(|node| node.index()).collect())
+            .unwrap_or_default();
+        check_hierarchy(&nodes, &roots)?;
+
+        Ok(Self {
+            meshes,
+            materials: document.materials().map(GltfMaterial::new).collect(),
+            textures: document.textures().map(GltfTexture::new).collect(),
+            images: images
+                .into_iter()
+                .enumerate()
+                .map(|(image, data)| {
+                    to_dynamic_image(data).ok_or(GltfError::InvalidImage { image })
+                })
+                .collect::<Result<_, _>>()?,
+            nodes,
+            roots,
+            unsupported_extensions,
+        })
+    }
+
+    /// The material of a primitive, the default material if it names none.
+    pub fn material(&self, primitive: &GltfPrimitive) -> GltfMaterial {
+        primitive
+            .material
+            .map(|index| self.materials[index].clone())
+            .unwrap_or_default()
+    }
+
+    /// The transform from the space of every node to world space, indexed
+    /// like [`GltfScene::nodes`]. Nodes outside of the scene get the identity.
+    pub fn world_transforms(&self) -> Vec<Mat4> {
+        let mut transforms = vec![Mat4::IDENTITY; self.nodes.len()];
+        let mut stack: Vec<(usize, Mat4)> = self
+            .roots
+            .iter()
+            .map(|&root| (root, Mat4::IDENTITY))
+            .collect();
+        while let Some((index, parent)) = stack.pop() {
+            let node = &self.nodes[index];
+            transforms[index] = parent * node.local_transform();
+            stack.extend(
+                node.children
+                    .iter()
+                    .map(|&child| (child, transforms[index])),
+            );
+        }
+        transforms
+    }
+
+    /// Every mesh the scene draws, with the world transform to draw it with.
+    pub fn mesh_instances(&self) -> Vec<(usize, Mat4)> {
+        let transforms = self.world_transforms();
+        let mut instances = Vec::new();
+        let mut stack = self.roots.clone();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if let Some(mesh) = node.mesh {
+                instances.push((mesh, transforms[index]));
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        instances
+    }
+
+    /// Uploads every primitive of every mesh, indexed like [`GltfScene::meshes`].
+    pub fn upload_meshes<'gl>(&self, gl: &'gl GlContext) -> Option<Vec<Vec<Mesh<'gl, Vertex>>>> {
+        self.meshes
+            .iter()
+            .map(|mesh| {
+                mesh.primitives
+                    .iter()
+                    .map(|primitive| primitive.data.upload(gl))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Uploads every texture with its sampler, indexed like [`GltfScene::textures`].
+    pub fn upload_textures<'gl>(&self, gl: &'gl GlContext) -> Option<Vec<Texture2D<'gl>>> {
+        self.textures
+            .iter()
+            .map(|texture| texture.builder().build(gl, &self.images[texture.image]))
+            .collect()
+    }
+}
+
+/// Reads a primitive into indexed triangles.
+///
+/// Primitives without normals are flat shaded, as the specification asks,
+/// which takes a vertex per triangle corner.
+fn read_primitive<'a, 's, F>(
+    reader: &::gltf::mesh::Reader<'a, 's, F>,
+    mode: Mode,
+    (mesh, primitive): (usize, usize),
+) -> Result<MeshData, GltfError>
+where
+    F: Clone + Fn(::gltf::Buffer<'a>) -> Option<&'s [u8]>,
+{
+    let positions: Vec<Vec3> = reader
+        .read_positions()
+        .ok_or(GltfError::MissingPositions { mesh, primitive })?
+        .map(Vec3::from_array)
+        .collect();
+    let count = positions.len();
+    let tex_coords: Vec<Vec2> = match reader.read_tex_coords(0) {
+        Some(tex_coords) => tex_coords.into_f32().map(Vec2::from_array).collect(),
+        None => vec![Vec2::ZERO; count],
+    };
+    let tangents: Vec<Vec4> = match reader.read_tangents() {
+        Some(tangents) => tangents.map(Vec4::from_array).collect(),
+        None => vec![Vec4::ZERO; count],
+    };
+    let normals: Option<Vec<Vec3>> = reader
+        .read_normals()
+        .map(|normals| normals.map(Vec3::from_array).collect());
+
+    let attributes = [
+        ("TEXCOORD_0", tex_coords.len()),
+        ("TANGENT", tangents.len()),
+        ("NORMAL", normals.as_ref().map_or(count, Vec::len)),
+    ];
+    for (attribute, attribute_count) in attributes {
+        if attribute_count != count {
+            return Err(GltfError::AttributeCount {
+                mesh,
+                primitive,
+                attribute,
+                count: attribute_count,
+                positions: count,
+            });
+        }
+    }
+
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..count as u32).collect(),
+    };
+    if let Some(&index) = indices.iter().find(|&&index| index as usize >= count) {
+        return Err(GltfError::IndexOutOfRange {
+            mesh,
+            primitive,
+            index,
+            vertices: count,
+        });
+    }
+    let indices = match mode {
+        Mode::Triangles => indices,
+        // Every other triangle of a strip is flipped to keep the winding
+        Mode::TriangleStrip => (0..indices.len().saturating_sub(2))
+            .flat_map(|i| match i % 2 {
+                0 => [indices[i], indices[i + 1], indices[i + 2]],
+                _ => [indices[i + 1], indices[i], indices[i + 2]],
+            })
+            .collect(),
+        Mode::TriangleFan => (1..indices.len().saturating_sub(1))
+            .flat_map(|i| [indices[i], indices[i + 1], indices[0]])
+            .collect(),
+        mode => {
+            return Err(GltfError::UnsupportedMode {
+                mesh,
+                primitive,
+                mode,
+            })
+        }
+    };
+
+    let vertex = |index: usize, normal: Vec3| Vertex {
+        position: positions[index],
+        tex_coord: tex_coords[index],
+        normal,
+        tangent: tangents[index],
+    };
+    let mut data = MeshData::new();
+    match normals {
+        Some(normals) => {
+            data.vertices = (0..count).map(|i| vertex(i, normals[i])).collect();
+            data.indices = indices;
+        }
+        None => {
+            for triangle in indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+                let normal = (positions[b] - positions[a])
+                    .cross(positions[c] - positions[a])
+                    .normalize_or(Vec3::Y);
+                let [a, b, c] = [a, b, c].map(|index| data.push_vertex(vertex(index, normal)));
+                data.push_triangle(a, b, c);
+            }
+        }
+    }
+    Ok(data)
+}
+
+/// Checks that the nodes form trees below the roots, so walking down from
+/// them visits every node at most once.
+fn check_hierarchy(nodes: &[GltfNode], roots: &[usize]) -> Result<(), GltfError> {
+    let mut parents = vec![None; nodes.len()];
+    for (parent, node) in nodes.iter().enumerate() {
+        for &child in &node.children {
+            if parents[child].replace(parent).is_some() {
+                return Err(GltfError::MultipleParents { node: child });
+            }
+        }
+    }
+
+    // With a single parent each, the nodes that can't be reached from a node
+    // without a parent are on a cycle or below one
+    let mut reached = vec![false; nodes.len()];
+    let mut stack: Vec<usize> = (0..nodes.len())
+        .filter(|&node| parents[node].is_none())
+        .collect();
+    while let Some(node) = stack.pop() {
+        reached[node] = true;
+        stack.extend(&nodes[node].children);
+    }
+    if let Some(mut node) = reached.iter().position(|&reached| !reached) {
+        // Going up as many times as there are nodes ends up on the cycle
+        for _ in 0..nodes.len() {
+            if let Some(parent) = parents[node] {
+                node = parent;
+            }
+        }
+        return Err(GltfError::NodeCycle { node });
+    }
+
+    // The scene is the parent of its roots
+    if let Some(&root) = roots.iter().find(|&&root| parents[root].is_some()) {
+        return Err(GltfError::MultipleParents { node: root });
+    }
+    Ok(())
+}
+
+/// Turns the decoded pixels back into the image they were decoded from,
+/// `None` if there are too few pixels for its size.
+fn to_dynamic_image(data: ::gltf::image::Data) -> Option<DynamicImage> {
+    let (width, height) = (data.width, data.height);
+    // 16 bit and float images were turned into bytes in native byte order
+    let u16s = |pixels: &[u8]| bytemuck::pod_collect_to_vec::<u8, u16>(pixels);
+    let f32s = |pixels: &[u8]| bytemuck::pod_collect_to_vec::<u8, f32>(pixels);
+    match data.format {
+        Format::R8 => {
+            ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageLuma8)
+        }
+        Format::R8G8 => {
+            ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageLumaA8)
+        }
+        Format::R8G8B8 => {
+            ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageRgb8)
+        }
+        Format::R8G8B8A8 => {
+            ImageBuffer::from_raw(width, height, data.pixels).map(DynamicImage::ImageRgba8)
+        }
+        Format::R16 => {
+            ImageBuffer::from_raw(width, height, u16s(&data.pixels)).map(DynamicImage::ImageLuma16)
+        }
+        Format::R16G16 => {
+            ImageBuffer::from_raw(width, height, u16s(&data.pixels)).map(DynamicImage::ImageLumaA16)
+        }
+        Format::R16G16B16 => {
+            ImageBuffer::from_raw(width, height, u16s(&data.pixels)).map(DynamicImage::ImageRgb16)
+        }
+        Format::R16G16B16A16 => {
+            ImageBuffer::from_raw(width, height, u16s(&data.pixels)).map(DynamicImage::ImageRgba16)
+        }
+        Format::R32G32B32FLOAT => {
+            ImageBuffer::from_raw(width, height, f32s(&data.pixels)).map(DynamicImage::ImageRgb32F)
+        }
+        Format::R32G32B32A32FLOAT => {
+            ImageBuffer::from_raw(width, height, f32s(&data.pixels)).map(DynamicImage::ImageRgba32F)
+        }
+    }
+}
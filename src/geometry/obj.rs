@@ -0,0 +1,534 @@
+//! Loading of Wavefront OBJ models and their MTL material libraries.
+//!
+//! Supported are positions, texture coordinates, normals, polygonal faces
+//! with positive and negative indices, groups, objects and materials. Lines,
+//! points, curves and smoothing groups are skipped.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use glam::{Vec2, Vec3, Vec4};
+
+use super::{MeshData, Vertex};
+
+/// Everything that can go wrong while loading a model.
+#[derive(Debug)]
+pub enum ObjError {
+    /// A model or material library could not be read.
+    Read { path: String, error: io::Error },
+    /// A line of a model or material library could not be understood.
+    Parse {
+        file: String,
+        line: usize,
+        kind: ObjErrorKind,
+    },
+}
+
+/// What was wrong with a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjErrorKind {
+    /// A statement has fewer values than it needs.
+    MissingValues { statement: String, expected: usize },
+    /// A value that should be a number is not.
+    InvalidNumber(String),
+    /// A face corner that is not `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+    InvalidFaceVertex(String),
+    /// A face refers to an element that has not been defined (yet).
+    IndexOutOfRange { index: i64, count: usize },
+    /// A face with fewer than three corners.
+    FaceTooSmall(usize),
+    /// A material property before the first `newmtl`.
+    NoMaterial,
+}
+
+impl fmt::Display for ObjErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjErrorKind::MissingValues {
+                statement,
+                expected,
+            } => write!(f, "`{}` needs at least {} values", statement, expected),
+            ObjErrorKind::InvalidNumber(value) => write!(f, "{:?} is not a number", value),
+            ObjErrorKind::InvalidFaceVertex(corner) => {
+                write!(f, "{:?} is not a face vertex like v/vt/vn", corner)
+            }
+            ObjErrorKind::IndexOutOfRange { index, count } => {
+                write!(
+                    f,
+                    "index {} is out of range, there are {} so far",
+                    index, count
+                )
+            }
+            ObjErrorKind::FaceTooSmall(corners) => {
+                write!(f, "a face needs at least 3 vertices, not {}", corners)
+            }
+            ObjErrorKind::NoMaterial => f.write_str("material property before `newmtl`"),
+        }
+    }
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Read { path, error } => write!(f, "could not read {}: {}", path, error),
+            ObjError::Parse { file, line, kind } => write!(f, "{}:{}: {}", file, line, kind),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Read { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A material from an MTL library.
+///
+/// Texture paths are as written in the library, relative to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    /// `Ka`
+    pub ambient: Vec3,
+    /// `Kd`
+    pub diffuse: Vec3,
+    /// `Ks`
+    pub specular: Vec3,
+    /// `Ke`
+    pub emissive: Vec3,
+    /// `Ns`, the specular exponent.
+    pub shininess: f32,
+    /// `d`, or 1 - `Tr`.
+    pub opacity: f32,
+    /// `map_Kd`
+    pub diffuse_map: Option<String>,
+    /// `map_Ks`
+    pub specular_map: Option<String>,
+    /// `map_Bump`, `bump` or `norm`
+    pub normal_map: Option<String>,
+}
+
+impl ObjMaterial {
+    /// A material with the defaults of the MTL format.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ambient: Vec3::ZERO,
+            diffuse: Vec3::ONE,
+            specular: Vec3::ZERO,
+            emissive: Vec3::ZERO,
+            shininess: 0.0,
+            opacity: 1.0,
+            diffuse_map: None,
+            specular_map: None,
+            normal_map: None,
+        }
+    }
+}
+
+/// The faces of a model that share a group and a material.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMesh {
+    /// The name of the group, or the object if there is no group, empty if neither.
+    pub name: String,
+    /// The name of the material given by `usemtl`.
+    pub material: Option<String>,
+    pub data: MeshData,
+}
+
+/// A loaded OBJ model, split into meshes wherever the group or material changes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    /// The libraries named by `mtllib`, as written in the model.
+    pub material_libraries: Vec<String>,
+    pub materials: HashMap<String, ObjMaterial>,
+}
+
+impl ObjModel {
+    /// Reads a model and the material libraries it names, which are looked
+    /// up next to it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let mut model = Self::parse(&read(path)?, &path.display().to_string())?;
+
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        for library in model.material_libraries.clone() {
+            let library_path: PathBuf = directory.join(&library);
+            let text = read(&library_path)?;
+            model
+                .materials
+                .extend(parse_mtl(&text, &library_path.display().to_string())?);
+        }
+        Ok(model)
+    }
+
+    /// Parses a model without loading its material libraries. `file` names it
+    /// in errors.
+    pub fn parse(text: &str, file: &str) -> Result<Self, ObjError> {
+        ObjParser::new(file).parse(text)
+    }
+
+    pub fn material(&self, name: &str) -> Option<&ObjMaterial> {
+        self.materials.get(name)
+    }
+
+    /// The material of a mesh, `None` if it has none or it was not in any library.
+    pub fn mesh_material(&self, mesh: &ObjMesh) -> Option<&ObjMaterial> {
+        mesh.material
+            .as_deref()
+            .and_then(|name| self.material(name))
+    }
+}
+
+fn read(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|error| ObjError::Read {
+        path: path.display().to_string(),
+        error,
+    })
+}
+
+/// Parses an MTL library. `file` names it in errors.
+pub fn parse_mtl(text: &str, file: &str) -> Result<HashMap<String, ObjMaterial>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<ObjMaterial> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let error = |kind| ObjError::Parse {
+            file: file.to_string(),
+            line: index + 1,
+            kind,
+        };
+        let statement = Statement::new(line);
+        let Some(keyword) = statement.keyword else {
+            continue;
+        };
+
+        if keyword == "newmtl" {
+            if let Some(material) = current.take() {
+                materials.insert(material.name.clone(), material);
+            }
+            current = Some(ObjMaterial::new(&statement.rest(1).map_err(error)?));
+            continue;
+        }
+
+        let Some(material) = current.as_mut() else {
+            // Only comments and blank lines may come before the first material
+            return Err(error(ObjErrorKind::NoMaterial));
+        };
+        match keyword {
+            "Ka" => material.ambient = statement.vec3().map_err(error)?,
+            "Kd" => material.diffuse = statement.vec3().map_err(error)?,
+            "Ks" => material.specular = statement.vec3().map_err(error)?,
+            "Ke" => material.emissive = statement.vec3().map_err(error)?,
+            "Ns" => material.shininess = statement.float(1).map_err(error)?,
+            "d" => material.opacity = statement.float(1).map_err(error)?,
+            "Tr" => material.opacity = 1.0 - statement.float(1).map_err(error)?,
+            "map_Kd" => material.diffuse_map = Some(statement.map_path().map_err(error)?),
+            "map_Ks" => material.specular_map = Some(statement.map_path().map_err(error)?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => {
+                material.normal_map = Some(statement.map_path().map_err(error)?)
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(material) = current {
+        materials.insert(material.name.clone(), material);
+    }
+    Ok(materials)
+}
+
+/// A line split into its keyword and values, with the comment removed.
+struct Statement<'a> {
+    keyword: Option<&'a str>,
+    values: Vec<&'a str>,
+}
+
+impl<'a> Statement<'a> {
+    fn new(line: &'a str) -> Self {
+        let line = line.split('#').next().unwrap_or("");
+        let mut words = line.split_whitespace();
+        Self {
+            keyword: words.next(),
+            values: words.collect(),
+        }
+    }
+
+    fn missing(&self, expected: usize) -> ObjErrorKind {
+        ObjErrorKind::MissingValues {
+            statement: self.keyword.unwrap_or_default().to_string(),
+            expected,
+        }
+    }
+
+    /// The values joined back together, for names that may contain spaces.
+    fn rest(&self, expected: usize) -> Result<String, ObjErrorKind> {
+        if self.values.len() < expected {
+            return Err(self.missing(expected));
+        }
+        Ok(self.values.join(" "))
+    }
+
+    /// The first `N` values as numbers, of which `required` must be there.
+    fn floats<const N: usize>(&self, required: usize) -> Result<[f32; N], ObjErrorKind> {
+        if self.values.len() < required {
+            return Err(self.missing(required));
+        }
+        let mut floats = [0.0; N];
+        for (float, value) in floats.iter_mut().zip(&self.values) {
+            *float = value
+                .parse()
+                .map_err(|_| ObjErrorKind::InvalidNumber(value.to_string()))?;
+        }
+        Ok(floats)
+    }
+
+    fn float(&self, required: usize) -> Result<f32, ObjErrorKind> {
+        self.floats::<1>(required).map(|[float]| float)
+    }
+
+    fn vec3(&self) -> Result<Vec3, ObjErrorKind> {
+        self.floats(3).map(Vec3::from_array)
+    }
+
+    /// The path of a texture map, which comes after any options like `-bm 1.0`.
+    fn map_path(&self) -> Result<String, ObjErrorKind> {
+        let path = self.values.last().ok_or_else(|| self.missing(1))?;
+        Ok(path.to_string())
+    }
+}
+
+/// One corner of a face, as 0 based indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    tex_coord: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// A mesh being built, with the corners turned into vertices so far.
+struct MeshBuilder {
+    mesh: ObjMesh,
+    vertices: HashMap<Corner, u32>,
+    /// The position of every vertex that had no normal given.
+    missing_normals: Vec<(u32, usize)>,
+}
+
+impl MeshBuilder {
+    fn new(name: &str, material: Option<String>) -> Self {
+        Self {
+            mesh: ObjMesh {
+                name: name.to_string(),
+                material,
+                data: MeshData::new(),
+            },
+            vertices: HashMap::new(),
+            missing_normals: Vec::new(),
+        }
+    }
+
+    /// Gives smooth normals to the vertices without one, averaged over the
+    /// faces around their position and weighted by the face size.
+    fn finish(mut self) -> ObjMesh {
+        let data = &mut self.mesh.data;
+        if !self.missing_normals.is_empty() {
+            let position_of: HashMap<u32, usize> = self.missing_normals.iter().copied().collect();
+            let mut normals: HashMap<usize, Vec3> = HashMap::new();
+            for triangle in data.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| data.vertices[triangle[i] as usize].position);
+                let face = (b - a).cross(c - a);
+                for index in triangle {
+                    if let Some(&position) = position_of.get(index) {
+                        *normals.entry(position).or_default() += face;
+                    }
+                }
+            }
+            for &(vertex, position) in &self.missing_normals {
+                let normal = normals.get(&position).copied().unwrap_or_default();
+                data.vertices[vertex as usize].normal = normal.normalize_or(Vec3::Y);
+            }
+        }
+        data.compute_tangents();
+        self.mesh
+    }
+}
+
+struct ObjParser<'f> {
+    file: &'f str,
+    positions: Vec<Vec3>,
+    tex_coords: Vec<Vec2>,
+    normals: Vec<Vec3>,
+    group: Option<String>,
+    object: Option<String>,
+    material: Option<String>,
+    current: Option<MeshBuilder>,
+    model: ObjModel,
+}
+
+impl<'f> ObjParser<'f> {
+    fn new(file: &'f str) -> Self {
+        Self {
+            file,
+            positions: Vec::new(),
+            tex_coords: Vec::new(),
+            normals: Vec::new(),
+            group: None,
+            object: None,
+            material: None,
+            current: None,
+            model: ObjModel::default(),
+        }
+    }
+
+    fn parse(mut self, text: &str) -> Result<ObjModel, ObjError> {
+        for (index, line) in text.lines().enumerate() {
+            self.parse_line(line).map_err(|kind| ObjError::Parse {
+                file: self.file.to_string(),
+                line: index + 1,
+                kind,
+            })?;
+        }
+        self.finish_mesh();
+        Ok(self.model)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), ObjErrorKind> {
+        let statement = Statement::new(line);
+        match statement.keyword {
+            Some("v") => self.positions.push(statement.vec3()?),
+            Some("vt") => {
+                let [u, v] = statement.floats(1)?;
+                self.tex_coords.push(Vec2::new(u, v));
+            }
+            Some("vn") => self.normals.push(statement.vec3()?),
+            Some("f") => self.parse_face(&statement.values)?,
+            Some("g") => {
+                self.finish_mesh();
+                self.group = Some(statement.rest(0)?);
+            }
+            Some("o") => {
+                self.finish_mesh();
+                self.object = Some(statement.rest(0)?);
+                self.group = None;
+            }
+            Some("usemtl") => {
+                self.finish_mesh();
+                self.material = Some(statement.rest(1)?);
+            }
+            Some("mtllib") => {
+                let libraries = statement.values.iter().map(|library| library.to_string());
+                self.model.material_libraries.extend(libraries);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn finish_mesh(&mut self) {
+        if let Some(builder) = self.current.take() {
+            if !builder.mesh.data.indices.is_empty() {
+                self.model.meshes.push(builder.finish());
+            }
+        }
+    }
+
+    fn parse_face(&mut self, corners: &[&str]) -> Result<(), ObjErrorKind> {
+        if corners.len() < 3 {
+            return Err(ObjErrorKind::FaceTooSmall(corners.len()));
+        }
+        let corners = corners
+            .iter()
+            .map(|corner| self.parse_corner(corner))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let name = self
+            .group
+            .as_deref()
+            .or(self.object.as_deref())
+            .unwrap_or("");
+        let builder = self
+            .current
+            .get_or_insert_with(|| MeshBuilder::new(name, self.material.clone()));
+        let indices = corners
+            .iter()
+            .map(|&corner| {
+                *builder.vertices.entry(corner).or_insert_with(|| {
+                    let vertex = Vertex {
+                        position: self.positions[corner.position],
+                        tex_coord: corner.tex_coord.map_or(Vec2::ZERO, |i| self.tex_coords[i]),
+                        normal: corner
+                            .normal
+                            .map_or(Vec3::ZERO, |i| self.normals[i].normalize_or_zero()),
+                        tangent: Vec4::ZERO,
+                    };
+                    let index = builder.mesh.data.push_vertex(vertex);
+                    if corner.normal.is_none() {
+                        builder.missing_normals.push((index, corner.position));
+                    }
+                    index
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Polygons are split into a fan around their first corner
+        for i in 1..indices.len() - 1 {
+            builder
+                .mesh
+                .data
+                .push_triangle(indices[0], indices[i], indices[i + 1]);
+        }
+        Ok(())
+    }
+
+    fn parse_corner(&self, corner: &str) -> Result<Corner, ObjErrorKind> {
+        let invalid = || ObjErrorKind::InvalidFaceVertex(corner.to_string());
+        let mut parts = corner.split('/');
+        let position = parts
+            .next()
+            .filter(|part| !part.is_empty())
+            .ok_or_else(invalid)?;
+        let tex_coord = parts.next().filter(|part| !part.is_empty());
+        let normal = parts.next().filter(|part| !part.is_empty());
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(Corner {
+            position: resolve_index(position, self.positions.len())?,
+            tex_coord: tex_coord
+                .map(|index| resolve_index(index, self.tex_coords.len()))
+                .transpose()?,
+            normal: normal
+                .map(|index| resolve_index(index, self.normals.len()))
+                .transpose()?,
+        })
+    }
+}
+
+/// Turns a 1 based index, or a negative one counting back from the last
+/// element, into a 0 based one.
+fn resolve_index(index: &str, count: usize) -> Result<usize, ObjErrorKind> {
+    let value: i64 = index
+        .parse()
+        .map_err(|_| ObjErrorKind::InvalidNumber(index.to_string()))?;
+    let resolved = if value < 0 {
+        count as i64 + value
+    } else {
+        value - 1
+    };
+    if value == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(ObjErrorKind::IndexOutOfRange {
+            index: value,
+            count,
+        });
+    }
+    Ok(resolved as usize)
+}
@@ -0,0 +1,430 @@
+//! Generators for basic shapes, centered on the origin with `y` up.
+//!
+//! Every shape comes with texture coordinates, normals and tangents. Curved
+//! shapes wrap their texture once around the `y` axis, starting at `+z` and
+//! going towards `+x`, with `v` growing upwards. Flat faces are mapped as if
+//! looked at from the front with `v` up, or from `-z` for faces facing up or down.
+
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI, TAU},
+};
+
+use glam::{Vec2, Vec3, Vec4};
+
+use super::{MeshData, Vertex};
+
+/// A box with the given edge lengths, every face split into `segments` by
+/// `segments` quads.
+///
+/// Each face has its own vertices, so the edges stay sharp, and the whole
+/// texture.
+pub fn cube(size: Vec3, segments: u32) -> MeshData {
+    let segments = segments.max(1);
+    let half = size / 2.0;
+    let mut mesh = MeshData::new();
+
+    // Normal, and the directions u and v grow in looking at the face from outside
+    let faces = [
+        (Vec3::Z, Vec3::X, Vec3::Y),
+        (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+        (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+        (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+        (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+    ];
+    for (normal, u, v) in faces {
+        let u_axis = u * size;
+        let v_axis = v * size;
+        let corner = normal * half - u_axis / 2.0 - v_axis / 2.0;
+        push_grid(&mut mesh, corner, u_axis, v_axis, segments, segments);
+    }
+    mesh
+}
+
+/// A flat rectangle in the `xz` plane facing up, `size.x` wide along `x` and
+/// `size.y` deep along `z`, split into `segments.x` by `segments.y` quads.
+pub fn plane(size: Vec2, segments: glam::UVec2) -> MeshData {
+    let mut mesh = MeshData::new();
+    let u_axis = Vec3::X * size.x;
+    let v_axis = Vec3::NEG_Z * size.y;
+    let corner = -(u_axis + v_axis) / 2.0;
+    push_grid(
+        &mut mesh,
+        corner,
+        u_axis,
+        v_axis,
+        segments.x.max(1),
+        segments.y.max(1),
+    );
+    mesh
+}
+
+/// A sphere made of `segments` slices around the `y` axis and `rings` stacks
+/// from pole to pole.
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> MeshData {
+    let rings = rings.max(2);
+    let profile = (0..=rings)
+        .map(|ring| {
+            let angle = -FRAC_PI_2 + PI * ring as f32 / rings as f32;
+            let normal = Vec2::new(angle.cos(), angle.sin());
+            ProfilePoint {
+                position: normal * radius,
+                normal,
+                v: ring as f32 / rings as f32,
+            }
+        })
+        .collect::<Vec<_>>();
+    lathe(&profile, segments)
+}
+
+/// A sphere made by splitting the faces of an icosahedron `subdivisions`
+/// times, so its triangles are all about the same size.
+///
+/// The texture is mapped like on [`uv_sphere`]. The seam runs through
+/// triangles, so their vertices past it are doubled with `u` a little over 1,
+/// which needs a repeating texture.
+pub fn icosphere(radius: f32, subdivisions: u32) -> MeshData {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut points: Vec<Vec3> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .into_iter()
+    .map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+    #[rustfmt::skip]
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let point = (points[a as usize] + points[b as usize]).normalize();
+                points.push(point);
+                points.len() as u32 - 1
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut mesh = MeshData::new();
+    mesh.vertices = points
+        .iter()
+        .map(|&normal| {
+            let angle = normal.x.atan2(normal.z);
+            let u = (angle / TAU).rem_euclid(1.0);
+            let v = (normal.y.clamp(-1.0, 1.0).asin() + FRAC_PI_2) / PI;
+            Vertex {
+                position: normal * radius,
+                tex_coord: Vec2::new(u, v),
+                normal,
+                tangent: around_y(angle),
+            }
+        })
+        .collect();
+
+    // The poles have no direction around the axis, they are handled last
+    let is_pole = |vertex: &Vertex| vertex.normal.x.abs() < 1e-6 && vertex.normal.z.abs() < 1e-6;
+
+    // Triangles across the seam get copies of their vertices on the u < 0.5 side
+    // moved past 1, so u runs on instead of jumping back
+    let mut seam_copies = HashMap::new();
+    for triangle in &mut triangles {
+        let us = triangle.map(|index| {
+            let vertex = &mesh.vertices[index as usize];
+            (!is_pole(vertex)).then_some(vertex.tex_coord.x)
+        });
+        let max_u = us.iter().flatten().copied().fold(f32::MIN, f32::max);
+        let min_u = us.iter().flatten().copied().fold(f32::MAX, f32::min);
+        if max_u - min_u <= 0.5 {
+            continue;
+        }
+        for (index, u) in triangle.iter_mut().zip(us) {
+            if u.is_some_and(|u| u < 0.5) {
+                *index = *seam_copies.entry(*index).or_insert_with(|| {
+                    let mut vertex = mesh.vertices[*index as usize];
+                    vertex.tex_coord.x += 1.0;
+                    mesh.push_vertex(vertex)
+                });
+            }
+        }
+    }
+
+    // Every triangle gets its own copy of a pole, facing the middle of the triangle
+    for triangle in &mut triangles {
+        for corner in 0..3 {
+            let pole = mesh.vertices[triangle[corner] as usize];
+            if !is_pole(&pole) {
+                continue;
+            }
+            let others = [triangle[(corner + 1) % 3], triangle[(corner + 2) % 3]];
+            let u = others
+                .iter()
+                .map(|&index| mesh.vertices[index as usize].tex_coord.x)
+                .sum::<f32>()
+                / 2.0;
+            triangle[corner] = mesh.push_vertex(Vertex {
+                tex_coord: Vec2::new(u, pole.tex_coord.y),
+                tangent: around_y(TAU * u),
+                ..pole
+            });
+        }
+    }
+
+    mesh.indices = triangles.into_iter().flatten().collect();
+    mesh
+}
+
+/// A cylinder around the `y` axis, with `segments`
+/// slices around and `stacks` rings along its height, closed by flat caps.
+pub fn cylinder(radius: f32, height: f32, segments: u32, stacks: u32) -> MeshData {
+    let stacks = stacks.max(1);
+    let half = height / 2.0;
+    let profile = (0..=stacks)
+        .map(|stack| {
+            let v = stack as f32 / stacks as f32;
+            ProfilePoint {
+                position: Vec2::new(radius, -half + height * v),
+                normal: Vec2::X,
+                v,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut mesh = lathe(&profile, segments);
+    push_cap(&mut mesh, radius, -half, segments, false);
+    push_cap(&mut mesh, radius, half, segments, true);
+    mesh
+}
+
+/// A cone around the `y` axis with its tip up, `segments` slices around and
+/// `stacks` rings along its height, closed by a flat base.
+pub fn cone(radius: f32, height: f32, segments: u32, stacks: u32) -> MeshData {
+    let stacks = stacks.max(1);
+    let half = height / 2.0;
+    // The side leans in, so its normal leans up
+    let normal = Vec2::new(height, radius).normalize();
+    let profile = (0..=stacks)
+        .map(|stack| {
+            let v = stack as f32 / stacks as f32;
+            ProfilePoint {
+                position: Vec2::new(radius * (1.0 - v), -half + height * v),
+                normal,
+                v,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut mesh = lathe(&profile, segments);
+    push_cap(&mut mesh, radius, -half, segments, false);
+    mesh
+}
+
+/// A ring around the `y` axis, `major_radius` from the axis to the middle of
+/// the tube and `minor_radius` thick, with `segments` slices around the axis
+/// and `sides` around the tube.
+///
+/// `v` starts on the inside bottom of the tube and goes around it outwards.
+pub fn torus(major_radius: f32, minor_radius: f32, segments: u32, sides: u32) -> MeshData {
+    let sides = sides.max(3);
+    let profile = (0..=sides)
+        .map(|side| {
+            let v = side as f32 / sides as f32;
+            let angle = -FRAC_PI_2 + TAU * v;
+            let normal = Vec2::new(angle.cos(), angle.sin());
+            ProfilePoint {
+                position: Vec2::new(major_radius, 0.0) + normal * minor_radius,
+                normal,
+                v,
+            }
+        })
+        .collect::<Vec<_>>();
+    lathe(&profile, segments)
+}
+
+/// A cylinder with half spheres for caps, `height` tall from tip to tip, with
+/// `segments` slices around the `y` axis and `rings` stacks on each half sphere.
+///
+/// `v` grows with the distance along the surface, so the texture is not
+/// stretched on the straight part.
+pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> MeshData {
+    let rings = rings.max(1);
+    let straight = (height - 2.0 * radius).max(0.0);
+    let length = PI * radius + straight;
+
+    // The bottom half sphere, then the top one, the straight part joins them
+    let mut profile = Vec::with_capacity(2 * rings as usize + 2);
+    for (center, from, arc_offset) in [
+        (-straight / 2.0, -FRAC_PI_2, 0.0),
+        (straight / 2.0, 0.0, straight),
+    ] {
+        for ring in 0..=rings {
+            let angle = from + FRAC_PI_2 * ring as f32 / rings as f32;
+            let normal = Vec2::new(angle.cos(), angle.sin());
+            let arc = radius * (angle + FRAC_PI_2) + arc_offset;
+            profile.push(ProfilePoint {
+                position: Vec2::new(0.0, center) + normal * radius,
+                normal,
+                v: arc / length,
+            });
+        }
+    }
+    lathe(&profile, segments)
+}
+
+/// A point of the outline a [`lathe`] spins around the `y` axis, `x` being the
+/// distance from the axis.
+#[derive(Debug, Clone, Copy)]
+struct ProfilePoint {
+    position: Vec2,
+    normal: Vec2,
+    v: f32,
+}
+
+/// The tangent of a surface of revolution at an angle around the `y` axis.
+fn around_y(angle: f32) -> Vec4 {
+    Vec4::new(angle.cos(), 0.0, -angle.sin(), 1.0)
+}
+
+/// Spins an outline, listed bottom to top, around the `y` axis in `segments` slices.
+///
+/// The first and last slice have their own vertices for the texture seam.
+/// Triangles that collapse, like the ones meeting at a pole, are left out.
+fn lathe(profile: &[ProfilePoint], segments: u32) -> MeshData {
+    let segments = segments.max(3);
+    let columns = segments + 1;
+    let mut mesh = MeshData::new();
+
+    for point in profile {
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let angle = TAU * u;
+            let (sin, cos) = angle.sin_cos();
+            mesh.push_vertex(Vertex {
+                position: Vec3::new(
+                    point.position.x * sin,
+                    point.position.y,
+                    point.position.x * cos,
+                ),
+                tex_coord: Vec2::new(u, point.v),
+                normal: Vec3::new(point.normal.x * sin, point.normal.y, point.normal.x * cos)
+                    .normalize(),
+                tangent: around_y(angle),
+            });
+        }
+    }
+
+    for row in 0..profile.len() as u32 - 1 {
+        for segment in 0..segments {
+            let a = row * columns + segment;
+            let b = a + 1;
+            let c = b + columns;
+            let d = a + columns;
+            push_quad(&mut mesh, a, b, c, d);
+        }
+    }
+    mesh
+}
+
+/// A flat disc closing a surface of revolution at height `y`, facing up or down.
+fn push_cap(mesh: &mut MeshData, radius: f32, y: f32, segments: u32, up: bool) {
+    let segments = segments.max(3);
+    let (normal, v_direction) = if up {
+        (Vec3::Y, -1.0)
+    } else {
+        (Vec3::NEG_Y, 1.0)
+    };
+    let vertex = |x: f32, z: f32| Vertex {
+        position: Vec3::new(x, y, z),
+        tex_coord: Vec2::new(
+            0.5 + x / (2.0 * radius),
+            0.5 + v_direction * z / (2.0 * radius),
+        ),
+        normal,
+        tangent: Vec4::new(1.0, 0.0, 0.0, 1.0),
+    };
+
+    let center = mesh.push_vertex(vertex(0.0, 0.0));
+    let first = mesh.vertices.len() as u32;
+    for segment in 0..segments {
+        let (sin, cos) = (TAU * segment as f32 / segments as f32).sin_cos();
+        mesh.push_vertex(vertex(radius * sin, radius * cos));
+    }
+    for segment in 0..segments {
+        let current = first + segment;
+        let next = first + (segment + 1) % segments;
+        if up {
+            mesh.push_triangle(center, current, next);
+        } else {
+            mesh.push_triangle(center, next, current);
+        }
+    }
+}
+
+/// Adds a flat grid spanning `u_axis` and `v_axis` from `corner`, facing
+/// `cross(u_axis, v_axis)`.
+fn push_grid(
+    mesh: &mut MeshData,
+    corner: Vec3,
+    u_axis: Vec3,
+    v_axis: Vec3,
+    columns: u32,
+    rows: u32,
+) {
+    let normal = u_axis.cross(v_axis).normalize();
+    let tangent = u_axis.normalize().extend(1.0);
+    let first = mesh.vertices.len() as u32;
+
+    for row in 0..=rows {
+        for column in 0..=columns {
+            let uv = Vec2::new(column as f32 / columns as f32, row as f32 / rows as f32);
+            mesh.push_vertex(Vertex {
+                position: corner + u_axis * uv.x + v_axis * uv.y,
+                tex_coord: uv,
+                normal,
+                tangent,
+            });
+        }
+    }
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let a = first + row * (columns + 1) + column;
+            let d = a + columns + 1;
+            push_quad(mesh, a, a + 1, d + 1, d);
+        }
+    }
+}
+
+/// Adds a quad given counter-clockwise, as two triangles, leaving out
+/// triangles without area.
+fn push_quad(mesh: &mut MeshData, a: u32, b: u32, c: u32, d: u32) {
+    for [a, b, c] in [[a, b, c], [a, c, d]] {
+        let [pa, pb, pc] = [a, b, c].map(|index| mesh.vertices[index as usize].position);
+        if (pb - pa).cross(pc - pa).length_squared() > f32::EPSILON * f32::EPSILON {
+            mesh.push_triangle(a, b, c);
+        }
+    }
+}
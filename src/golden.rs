@@ -0,0 +1,290 @@
+use std::{
+    env,
+    error::Error,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use image::{ImageError, Rgba, RgbaImage};
+
+/// Setting this environment variable to anything but `0` makes
+/// [`GoldenImages::check`] overwrite the goldens instead of comparing.
+pub const BLESS_VAR: &str = "BLESS_GOLDENS";
+
+/// How far a rendered image may stray from its golden.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Largest difference of a single channel that still counts as the same.
+    pub channel: u8,
+    /// Fraction of the pixels, 0 to 1, that may differ by more than `channel`.
+    pub max_differing_ratio: f64,
+}
+
+impl Default for Tolerance {
+    /// Leaves room for drivers rounding and filtering a little differently,
+    /// but not for anything that moved.
+    fn default() -> Self {
+        Self {
+            channel: 4,
+            max_differing_ratio: 0.001,
+        }
+    }
+}
+
+/// The result of comparing two images of the same size.
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    pub differing_pixels: usize,
+    pub total_pixels: usize,
+    /// Largest difference of any channel of any pixel.
+    pub max_channel_difference: u8,
+    /// Differing pixels in red, brighter the more they differ, over a dimmed
+    /// gray version of the expected image.
+    pub image: RgbaImage,
+}
+
+impl ImageDiff {
+    /// Fraction of the pixels that differ, 0 for empty images.
+    pub fn differing_ratio(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f64 / self.total_pixels as f64
+        }
+    }
+}
+
+/// Compares every channel of every pixel, a pixel differs when any of its
+/// channels is more than `channel_tolerance` apart.
+///
+/// # Panics
+///
+/// If the images are not the same size.
+pub fn compare(actual: &RgbaImage, expected: &RgbaImage, channel_tolerance: u8) -> ImageDiff {
+    assert_eq!(
+        actual.dimensions(),
+        expected.dimensions(),
+        "only images of the same size can be compared"
+    );
+
+    let mut differing_pixels = 0;
+    let mut max_channel_difference = 0;
+    let mut image = RgbaImage::new(expected.width(), expected.height());
+    for ((actual, expected), diff) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(image.pixels_mut())
+    {
+        let difference = (0..4)
+            .map(|channel| actual[channel].abs_diff(expected[channel]))
+            .max()
+            .unwrap();
+        max_channel_difference = max_channel_difference.max(difference);
+
+        *diff = if difference > channel_tolerance {
+            differing_pixels += 1;
+            Rgba([128 + difference / 2, 0, 0, 255])
+        } else {
+            let [r, g, b, _] = expected.0.map(u32::from);
+            let gray = ((r * 3 + g * 6 + b) / 10 / 3) as u8;
+            Rgba([gray, gray, gray, 255])
+        };
+    }
+
+    ImageDiff {
+        differing_pixels,
+        total_pixels: image.width() as usize * image.height() as usize,
+        max_channel_difference,
+        image,
+    }
+}
+
+/// Why a rendered image did not match its golden.
+#[derive(Debug)]
+pub enum GoldenError {
+    /// There is no golden yet, run with [`BLESS_VAR`] set to make one.
+    Missing { golden: PathBuf, actual: PathBuf },
+    /// The images are not the same size.
+    SizeMismatch {
+        name: String,
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    /// More pixels differ than the tolerance allows. The actual, expected and
+    /// diff images were written next to each other.
+    Mismatch {
+        name: String,
+        differing_pixels: usize,
+        total_pixels: usize,
+        max_differing_ratio: f64,
+        output_dir: PathBuf,
+    },
+    /// A golden or one of the failure images could not be read or written.
+    Image { path: PathBuf, error: ImageError },
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenError::Missing { golden, actual } => write!(
+                f,
+                "there is no golden {}, the rendered image is in {}, run with {}=1 to bless it",
+                golden.display(),
+                actual.display(),
+                BLESS_VAR
+            ),
+            GoldenError::SizeMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} is {}x{}, but the golden is {}x{}",
+                name, actual.0, actual.1, expected.0, expected.1
+            ),
+            GoldenError::Mismatch {
+                name,
+                differing_pixels,
+                total_pixels,
+                max_differing_ratio,
+                output_dir,
+            } => write!(
+                f,
+                "{} differs in {} of {} pixels, at most {:.3}% may, see {}",
+                name,
+                differing_pixels,
+                total_pixels,
+                max_differing_ratio * 100.0,
+                output_dir.display()
+            ),
+            GoldenError::Image { path, error } => write!(f, "{}: {}", path.display(), error),
+            GoldenError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+        }
+    }
+}
+
+impl Error for GoldenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GoldenError::Image { error, .. } => Some(error),
+            GoldenError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A directory of golden PNGs that rendered images are checked against.
+///
+/// Failures leave `<name>-actual.png`, `<name>-expected.png` and
+/// `<name>-diff.png` in the output directory.
+#[derive(Debug, Clone)]
+pub struct GoldenImages {
+    dir: PathBuf,
+    output_dir: PathBuf,
+    tolerance: Tolerance,
+    bless: bool,
+}
+
+impl GoldenImages {
+    /// Goldens in `dir`, blessed when [`BLESS_VAR`] is set.
+    pub fn new(dir: impl Into<PathBuf>, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            output_dir: output_dir.into(),
+            tolerance: Tolerance::default(),
+            bless: env::var_os(BLESS_VAR).is_some_and(|value| value != "0"),
+        }
+    }
+
+    pub fn tolerance(self, tolerance: Tolerance) -> Self {
+        Self { tolerance, ..self }
+    }
+
+    /// Overrides [`BLESS_VAR`].
+    pub fn bless(self, bless: bool) -> Self {
+        Self { bless, ..self }
+    }
+
+    /// Where the golden called `name` is stored.
+    pub fn golden_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.png", name))
+    }
+
+    /// Compares `actual` with the golden called `name`, or replaces the
+    /// golden with it when blessing.
+    pub fn check(&self, name: &str, actual: &RgbaImage) -> Result<(), GoldenError> {
+        let golden = self.golden_path(name);
+        if self.bless {
+            create_dir(&self.dir)?;
+            return save(actual, &golden);
+        }
+
+        if !golden.exists() {
+            let actual_path = self.save_output(name, "actual", actual)?;
+            return Err(GoldenError::Missing {
+                golden,
+                actual: actual_path,
+            });
+        }
+        let expected = image::open(&golden)
+            .map_err(|error| GoldenError::Image {
+                path: golden.clone(),
+                error,
+            })?
+            .to_rgba8();
+
+        if expected.dimensions() != actual.dimensions() {
+            self.save_output(name, "actual", actual)?;
+            return Err(GoldenError::SizeMismatch {
+                name: name.to_string(),
+                expected: expected.dimensions(),
+                actual: actual.dimensions(),
+            });
+        }
+
+        let diff = compare(actual, &expected, self.tolerance.channel);
+        if diff.differing_ratio() <= self.tolerance.max_differing_ratio {
+            return Ok(());
+        }
+        self.save_output(name, "actual", actual)?;
+        self.save_output(name, "expected", &expected)?;
+        self.save_output(name, "diff", &diff.image)?;
+        Err(GoldenError::Mismatch {
+            name: name.to_string(),
+            differing_pixels: diff.differing_pixels,
+            total_pixels: diff.total_pixels,
+            max_differing_ratio: self.tolerance.max_differing_ratio,
+            output_dir: self.output_dir.clone(),
+        })
+    }
+
+    fn save_output(
+        &self,
+        name: &str,
+        kind: &str,
+        image: &RgbaImage,
+    ) -> Result<PathBuf, GoldenError> {
+        create_dir(&self.output_dir)?;
+        let path = self.output_dir.join(format!("{}-{}.png", name, kind));
+        save(image, &path)?;
+        Ok(path)
+    }
+}
+
+fn create_dir(dir: &Path) -> Result<(), GoldenError> {
+    fs::create_dir_all(dir).map_err(|error| GoldenError::Io {
+        path: dir.to_path_buf(),
+        error,
+    })
+}
+
+fn save(image: &RgbaImage, path: &Path) -> Result<(), GoldenError> {
+    image.save(path).map_err(|error| GoldenError::Image {
+        path: path.to_path_buf(),
+        error,
+    })
+}
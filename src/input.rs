@@ -0,0 +1,810 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt, fs, io,
+    path::Path,
+};
+
+use glam::Vec2;
+
+mod gamepad;
+pub use gamepad::*;
+
+/// A physical key, numbered like SDL scancodes.
+///
+/// Scancodes name the position of a key rather than what is printed on it,
+/// so `W` is the key above `S` on any keyboard layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scancode(pub i32);
+
+impl Scancode {
+    pub const A: Self = Self(4);
+    pub const C: Self = Self(6);
+    pub const D: Self = Self(7);
+    pub const E: Self = Self(8);
+    pub const Q: Self = Self(20);
+    pub const S: Self = Self(22);
+    pub const W: Self = Self(26);
+    pub const RETURN: Self = Self(40);
+    pub const ESCAPE: Self = Self(41);
+    pub const TAB: Self = Self(43);
+    pub const SPACE: Self = Self(44);
+    pub const RIGHT: Self = Self(79);
+    pub const LEFT: Self = Self(80);
+    pub const DOWN: Self = Self(81);
+    pub const UP: Self = Self(82);
+    pub const LCTRL: Self = Self(224);
+    pub const LSHIFT: Self = Self(225);
+    pub const LALT: Self = Self(226);
+    pub const RCTRL: Self = Self(228);
+    pub const RSHIFT: Self = Self(229);
+    pub const RALT: Self = Self(230);
+
+    /// Looks a key up by the name used in binding files, ignoring case.
+    ///
+    /// Letters and digits are named by themselves, `F1` to `F12` by their
+    /// label, the rest by the names in [`Scancode::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        let upper = name.to_ascii_uppercase();
+        let bytes = upper.as_bytes();
+        match bytes {
+            [letter @ b'A'..=b'Z'] => return Some(Self(4 + (letter - b'A') as i32)),
+            [b'0'] => return Some(Self(39)),
+            [digit @ b'1'..=b'9'] => return Some(Self(30 + (digit - b'1') as i32)),
+            [b'F', _, ..] => {
+                if let Ok(n @ 1..=12) = upper[1..].parse::<i32>() {
+                    return Some(Self(57 + n));
+                }
+            }
+            _ => (),
+        }
+        NAMED_KEYS
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+            .map(|&(_, scancode)| scancode)
+    }
+
+    /// The name [`Scancode::from_name`] accepts, `None` for keys without one.
+    pub fn name(self) -> Option<String> {
+        match self.0 {
+            4..=29 => Some(char::from(b'A' + (self.0 - 4) as u8).to_string()),
+            30..=38 => Some((self.0 - 29).to_string()),
+            39 => Some("0".to_string()),
+            58..=69 => Some(format!("F{}", self.0 - 57)),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|&&(_, scancode)| scancode == self)
+                .map(|(name, _)| name.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Scancode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(&name),
+            None => write!(f, "Scancode({})", self.0),
+        }
+    }
+}
+
+const NAMED_KEYS: &[(&str, Scancode)] = &[
+    ("Return", Scancode::RETURN),
+    ("Escape", Scancode::ESCAPE),
+    ("Backspace", Scancode(42)),
+    ("Tab", Scancode::TAB),
+    ("Space", Scancode::SPACE),
+    ("Minus", Scancode(45)),
+    ("Equals", Scancode(46)),
+    ("LeftBracket", Scancode(47)),
+    ("RightBracket", Scancode(48)),
+    ("Backslash", Scancode(49)),
+    ("Semicolon", Scancode(51)),
+    ("Apostrophe", Scancode(52)),
+    ("Grave", Scancode(53)),
+    ("Comma", Scancode(54)),
+    ("Period", Scancode(55)),
+    ("Slash", Scancode(56)),
+    ("CapsLock", Scancode(57)),
+    ("Insert", Scancode(73)),
+    ("Home", Scancode(74)),
+    ("PageUp", Scancode(75)),
+    ("Delete", Scancode(76)),
+    ("End", Scancode(77)),
+    ("PageDown", Scancode(78)),
+    ("Right", Scancode::RIGHT),
+    ("Left", Scancode::LEFT),
+    ("Down", Scancode::DOWN),
+    ("Up", Scancode::UP),
+    ("LCtrl", Scancode::LCTRL),
+    ("LShift", Scancode::LSHIFT),
+    ("LAlt", Scancode::LALT),
+    ("RCtrl", Scancode::RCTRL),
+    ("RShift", Scancode::RSHIFT),
+    ("RAlt", Scancode::RALT),
+];
+
+/// A mouse button, in the order SDL numbers them starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    X1,
+    X2,
+}
+
+impl MouseButton {
+    const ALL: [(MouseButton, &'static str); 5] = [
+        (MouseButton::Left, "MouseLeft"),
+        (MouseButton::Middle, "MouseMiddle"),
+        (MouseButton::Right, "MouseRight"),
+        (MouseButton::X1, "MouseX1"),
+        (MouseButton::X2, "MouseX2"),
+    ];
+
+    /// The button for an SDL button number, `None` for buttons past `X2`.
+    pub fn from_sdl(button: u8) -> Option<Self> {
+        Self::ALL
+            .get(usize::from(button).checked_sub(1)?)
+            .map(|&(button, _)| button)
+    }
+
+    /// The name used in binding files.
+    pub fn name(self) -> &'static str {
+        Self::ALL[self as usize].1
+    }
+}
+
+/// Anything that can be held down and bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Key(Scancode),
+    Mouse(MouseButton),
+    /// A button on any connected controller.
+    Gamepad(GamepadButton),
+    /// An axis of any connected controller pushed past [`Deadzones::press`],
+    /// in the positive or negative direction.
+    GamepadAxis {
+        axis: GamepadAxis,
+        positive: bool,
+    },
+}
+
+impl Button {
+    /// Looks a button up by its name in a binding file, ignoring case.
+    ///
+    /// Axes are named with a `+` or `-` suffix for their direction, which can
+    /// be left out for triggers.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(button) = MouseButton::ALL
+            .iter()
+            .find(|(_, mouse_name)| mouse_name.eq_ignore_ascii_case(name))
+        {
+            return Some(Button::Mouse(button.0));
+        }
+        if let Some(button) = GamepadButton::from_name(name) {
+            return Some(Button::Gamepad(button));
+        }
+
+        let (axis_name, positive) = match name.as_bytes().last() {
+            Some(b'+') => (&name[..name.len() - 1], Some(true)),
+            Some(b'-') => (&name[..name.len() - 1], Some(false)),
+            _ => (name, None),
+        };
+        if let Some(axis) = GamepadAxis::from_name(axis_name) {
+            return match positive {
+                Some(positive) => Some(Button::GamepadAxis { axis, positive }),
+                None if axis.is_trigger() => Some(Button::GamepadAxis {
+                    axis,
+                    positive: true,
+                }),
+                None => None,
+            };
+        }
+
+        Scancode::from_name(name).map(Button::Key)
+    }
+}
+
+impl From<Scancode> for Button {
+    fn from(scancode: Scancode) -> Self {
+        Button::Key(scancode)
+    }
+}
+
+impl From<MouseButton> for Button {
+    fn from(button: MouseButton) -> Self {
+        Button::Mouse(button)
+    }
+}
+
+impl From<GamepadButton> for Button {
+    fn from(button: GamepadButton) -> Self {
+        Button::Gamepad(button)
+    }
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Button::Key(scancode) => scancode.fmt(f),
+            Button::Mouse(button) => f.write_str(button.name()),
+            Button::Gamepad(button) => f.write_str(button.name()),
+            Button::GamepadAxis { axis, positive } => {
+                let sign = if *positive { "+" } else { "-" };
+                write!(f, "{}{}", axis.name(), sign)
+            }
+        }
+    }
+}
+
+/// Something that drives a named axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AxisSource {
+    /// Reads -1 while `negative` is held, 1 while `positive` is held.
+    Buttons { negative: Button, positive: Button },
+    /// A controller axis, flipped when `inverted` is set.
+    Gamepad { axis: GamepadAxis, inverted: bool },
+}
+
+impl AxisSource {
+    /// Parses `negative/positive` for a button pair, or a controller axis
+    /// name with an optional leading `-` to invert it.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some((negative, positive)) = name.split_once('/') {
+            return Some(AxisSource::Buttons {
+                negative: Button::from_name(negative.trim())?,
+                positive: Button::from_name(positive.trim())?,
+            });
+        }
+        match name.strip_prefix('-') {
+            Some(axis) => GamepadAxis::from_name(axis.trim()).map(|axis| AxisSource::Gamepad {
+                axis,
+                inverted: true,
+            }),
+            None => GamepadAxis::from_name(name).map(|axis| AxisSource::Gamepad {
+                axis,
+                inverted: false,
+            }),
+        }
+    }
+}
+
+impl fmt::Display for AxisSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AxisSource::Buttons { negative, positive } => write!(f, "{}/{}", negative, positive),
+            AxisSource::Gamepad { axis, inverted } => {
+                let sign = if *inverted { "-" } else { "" };
+                write!(f, "{}{}", sign, axis.name())
+            }
+        }
+    }
+}
+
+/// The input events [`Input`] understands, translated from whatever the
+/// windowing library hands out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    Key {
+        scancode: Scancode,
+        pressed: bool,
+        /// Set for the repeats the OS sends while a key is held.
+        repeat: bool,
+    },
+    MouseButton {
+        button: MouseButton,
+        pressed: bool,
+    },
+    /// Relative mouse motion in pixels, positive `y` is downwards.
+    MouseMotion {
+        x: f32,
+        y: f32,
+    },
+    /// Wheel steps, positive `y` is away from the user.
+    MouseWheel {
+        x: f32,
+        y: f32,
+    },
+    /// A controller was plugged in and opened. `id` names it in later events.
+    GamepadAdded {
+        id: i32,
+    },
+    /// A controller was unplugged, everything it held is released.
+    GamepadRemoved {
+        id: i32,
+    },
+    GamepadButton {
+        id: i32,
+        button: GamepadButton,
+        pressed: bool,
+    },
+    /// A controller axis moved, `value` is the raw `i16` SDL reports.
+    GamepadAxis {
+        id: i32,
+        axis: GamepadAxis,
+        value: i16,
+    },
+}
+
+/// Everything that can go wrong while reading action bindings.
+#[derive(Debug)]
+pub enum BindingError {
+    /// The binding file could not be read.
+    Read { path: String, error: io::Error },
+    /// A line that is not of the form `action = button, button`.
+    Malformed { line: usize },
+    /// A button or axis name that is not known.
+    UnknownButton { line: usize, name: String },
+    /// A deadzone that is not a number from 0 up to 1.
+    InvalidDeadzone { line: usize, value: String },
+    /// A press threshold that is not a number above 0 and below 1. At 0 an
+    /// axis at rest would hold both of its directions.
+    InvalidPressThreshold { line: usize, value: String },
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingError::Read { path, error } => write!(f, "could not read {}: {}", path, error),
+            BindingError::Malformed { line } => {
+                write!(f, "line {}: expected `action = button, button`", line)
+            }
+            BindingError::UnknownButton { line, name } => {
+                write!(f, "line {}: unknown button {:?}", line, name)
+            }
+            BindingError::InvalidDeadzone { line, value } => {
+                write!(
+                    f,
+                    "line {}: {:?} is not a deadzone from 0 up to 1",
+                    line, value
+                )
+            }
+            BindingError::InvalidPressThreshold { line, value } => write!(
+                f,
+                "line {}: {:?} is not a press threshold above 0 and below 1",
+                line, value
+            ),
+        }
+    }
+}
+
+impl Error for BindingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BindingError::Read { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Named actions, each bound to one or more buttons, and named axes, each
+/// driven by button pairs or controller axes.
+///
+/// Binding files have one action per line, `#` starts a comment. Axis lines
+/// start with `axis`, deadzone lines with `deadzone`:
+///
+/// ```text
+/// fire = MouseLeft, PadTriggerRight
+/// axis move_forward = S/W, -PadLeftY
+/// deadzone stick = 0.25
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionMap {
+    bindings: HashMap<String, Vec<Button>>,
+    axes: HashMap<String, Vec<AxisSource>>,
+    deadzones: Deadzones,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a button to an action, keeping the buttons already bound to it.
+    pub fn bind(&mut self, action: &str, button: impl Into<Button>) -> &mut Self {
+        let button = button.into();
+        let buttons = self.bindings.entry(action.to_string()).or_default();
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+        self
+    }
+
+    /// Removes every button bound to an action.
+    pub fn unbind(&mut self, action: &str) -> &mut Self {
+        self.bindings.remove(action);
+        self
+    }
+
+    /// Adds a source to an axis, keeping the sources already driving it.
+    pub fn bind_axis(&mut self, axis: &str, source: AxisSource) -> &mut Self {
+        let sources = self.axes.entry(axis.to_string()).or_default();
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+        self
+    }
+
+    /// Removes every source driving an axis.
+    pub fn unbind_axis(&mut self, axis: &str) -> &mut Self {
+        self.axes.remove(axis);
+        self
+    }
+
+    /// The buttons bound to an action, empty for unknown actions.
+    pub fn buttons(&self, action: &str) -> &[Button] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The sources driving an axis, empty for unknown axes.
+    pub fn axis_sources(&self, axis: &str) -> &[AxisSource] {
+        self.axes.get(axis).map_or(&[], Vec::as_slice)
+    }
+
+    /// The names of all bound actions, in no particular order.
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.bindings.keys().map(String::as_str)
+    }
+
+    /// The names of all bound axes, in no particular order.
+    pub fn axes(&self) -> impl Iterator<Item = &str> {
+        self.axes.keys().map(String::as_str)
+    }
+
+    pub fn deadzones(&self) -> Deadzones {
+        self.deadzones
+    }
+
+    pub fn set_deadzones(&mut self, deadzones: Deadzones) -> &mut Self {
+        self.deadzones = deadzones;
+        self
+    }
+
+    /// Parses a binding file. Later lines for the same action or axis add to it.
+    pub fn parse(text: &str) -> Result<Self, BindingError> {
+        let mut map = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, values) = line
+                .split_once('=')
+                .ok_or(BindingError::Malformed { line: line_number })?;
+            let (kind, name) = match name.trim().split_once(char::is_whitespace) {
+                Some((kind @ ("axis" | "deadzone"), name)) => (kind, name.trim()),
+                Some(_) => return Err(BindingError::Malformed { line: line_number }),
+                None => ("action", name.trim()),
+            };
+            if name.is_empty() {
+                return Err(BindingError::Malformed { line: line_number });
+            }
+
+            if kind == "deadzone" {
+                let value = values.trim();
+                let deadzone = value.parse::<f32>().ok();
+                match name {
+                    "press" => {
+                        map.deadzones.press = deadzone
+                            .filter(|press| *press > 0.0 && *press < 1.0)
+                            .ok_or_else(|| BindingError::InvalidPressThreshold {
+                                line: line_number,
+                                value: value.to_string(),
+                            })?;
+                    }
+                    "stick" | "trigger" => {
+                        let deadzone = deadzone
+                            .filter(|deadzone| (0.0..1.0).contains(deadzone))
+                            .ok_or_else(|| BindingError::InvalidDeadzone {
+                                line: line_number,
+                                value: value.to_string(),
+                            })?;
+                        if name == "stick" {
+                            map.deadzones.stick = deadzone;
+                        } else {
+                            map.deadzones.trigger = deadzone;
+                        }
+                    }
+                    _ => return Err(BindingError::Malformed { line: line_number }),
+                }
+                continue;
+            }
+
+            for value in values
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+            {
+                let unknown = || BindingError::UnknownButton {
+                    line: line_number,
+                    name: value.to_string(),
+                };
+                if kind == "axis" {
+                    map.bind_axis(name, AxisSource::from_name(value).ok_or_else(unknown)?);
+                } else {
+                    map.bind(name, Button::from_name(value).ok_or_else(unknown)?);
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// Reads and parses a binding file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BindingError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| BindingError::Read {
+            path: path.display().to_string(),
+            error,
+        })?;
+        Self::parse(&text)
+    }
+}
+
+impl fmt::Display for ActionMap {
+    /// Writes the bindings in the format [`ActionMap::parse`] reads, sorted by name.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_line<T: fmt::Display>(
+            f: &mut fmt::Formatter<'_>,
+            name: &str,
+            values: &[T],
+        ) -> fmt::Result {
+            write!(f, "{} =", name)?;
+            for (i, value) in values.iter().enumerate() {
+                let separator = if i == 0 { " " } else { ", " };
+                write!(f, "{}{}", separator, value)?;
+            }
+            writeln!(f)
+        }
+
+        let mut actions: Vec<_> = self.bindings.iter().collect();
+        actions.sort_by_key(|(action, _)| *action);
+        for (action, buttons) in actions {
+            write_line(f, action, buttons)?;
+        }
+
+        let mut axes: Vec<_> = self.axes.iter().collect();
+        axes.sort_by_key(|(axis, _)| *axis);
+        for (axis, sources) in axes {
+            write_line(f, &format!("axis {}", axis), sources)?;
+        }
+
+        let deadzones = self.deadzones;
+        writeln!(f, "deadzone stick = {}", deadzones.stick)?;
+        writeln!(f, "deadzone trigger = {}", deadzones.trigger)?;
+        writeln!(f, "deadzone press = {}", deadzones.press)
+    }
+}
+
+/// Keyboard, mouse and controller state, tracked across frames.
+///
+/// Call [`Input::begin_frame`] before handing over the events of a frame.
+/// A button is *held* from its press event to its release event, and was
+/// *pressed* or *released* in the frame that saw the event, so holding several
+/// keys at once works and nothing depends on OS key repeat.
+///
+/// Controller buttons and axes count no matter which controller they are on.
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    held: HashSet<Button>,
+    pressed: HashSet<Button>,
+    released: HashSet<Button>,
+    mouse_motion: Vec2,
+    wheel: Vec2,
+    gamepads: HashMap<i32, GamepadState>,
+    actions: ActionMap,
+}
+
+impl Input {
+    pub fn new(actions: ActionMap) -> Self {
+        Self {
+            actions,
+            ..Self::default()
+        }
+    }
+
+    pub fn actions(&self) -> &ActionMap {
+        &self.actions
+    }
+
+    pub fn actions_mut(&mut self) -> &mut ActionMap {
+        &mut self.actions
+    }
+
+    /// Forgets what was pressed and released and how far the mouse moved in
+    /// the last frame. Held buttons stay held.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.mouse_motion = Vec2::ZERO;
+        self.wheel = Vec2::ZERO;
+        // Picks up deadzones changed through `actions_mut`
+        self.update_axis_buttons();
+    }
+
+    pub fn handle_event(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Key { repeat: true, .. } => (),
+            InputEvent::Key {
+                scancode, pressed, ..
+            } => self.set_button(Button::Key(scancode), pressed),
+            InputEvent::MouseButton { button, pressed } => {
+                self.set_button(Button::Mouse(button), pressed)
+            }
+            InputEvent::MouseMotion { x, y } => self.mouse_motion += Vec2::new(x, y),
+            InputEvent::MouseWheel { x, y } => self.wheel += Vec2::new(x, y),
+            InputEvent::GamepadAdded { id } => {
+                self.gamepads.entry(id).or_default();
+            }
+            InputEvent::GamepadRemoved { id } => {
+                if let Some(gamepad) = self.gamepads.remove(&id) {
+                    for button in gamepad.buttons {
+                        self.update_gamepad_button(button);
+                    }
+                    self.update_axis_buttons();
+                }
+            }
+            InputEvent::GamepadButton {
+                id,
+                button,
+                pressed,
+            } => {
+                // Events of a controller that was never added are taken as a
+                // late `GamepadAdded`.
+                let gamepad = self.gamepads.entry(id).or_default();
+                if pressed {
+                    gamepad.buttons.insert(button);
+                } else {
+                    gamepad.buttons.remove(&button);
+                }
+                self.update_gamepad_button(button);
+            }
+            InputEvent::GamepadAxis { id, axis, value } => {
+                let gamepad = self.gamepads.entry(id).or_default();
+                gamepad.set_axis(axis, normalize_axis(value));
+                self.update_axis_buttons();
+            }
+        }
+    }
+
+    fn set_button(&mut self, button: Button, down: bool) {
+        if down {
+            if self.held.insert(button) {
+                self.pressed.insert(button);
+            }
+        } else if self.held.remove(&button) {
+            self.released.insert(button);
+        }
+    }
+
+    /// Holds a controller button while any controller holds it.
+    fn update_gamepad_button(&mut self, button: GamepadButton) {
+        let down = self
+            .gamepads
+            .values()
+            .any(|gamepad| gamepad.buttons.contains(&button));
+        self.set_button(Button::Gamepad(button), down);
+    }
+
+    /// Holds the axes pushed past the press threshold as buttons.
+    fn update_axis_buttons(&mut self) {
+        let press = self.actions.deadzones.press;
+        for (axis, _) in GamepadAxis::ALL {
+            let value = self.gamepad_axis(axis);
+            let positive = Button::GamepadAxis {
+                axis,
+                positive: true,
+            };
+            let negative = Button::GamepadAxis {
+                axis,
+                positive: false,
+            };
+            self.set_button(positive, value >= press);
+            self.set_button(negative, value <= -press);
+        }
+    }
+
+    /// Releases every held button, for when the window loses focus and the
+    /// release events would go elsewhere.
+    pub fn release_all(&mut self) {
+        self.released.extend(self.held.drain());
+        for gamepad in self.gamepads.values_mut() {
+            *gamepad = GamepadState::default();
+        }
+    }
+
+    pub fn is_held(&self, button: impl Into<Button>) -> bool {
+        self.held.contains(&button.into())
+    }
+
+    pub fn was_pressed(&self, button: impl Into<Button>) -> bool {
+        self.pressed.contains(&button.into())
+    }
+
+    pub fn was_released(&self, button: impl Into<Button>) -> bool {
+        self.released.contains(&button.into())
+    }
+
+    /// How far the mouse moved this frame in pixels, positive `y` is downwards.
+    pub fn mouse_motion(&self) -> Vec2 {
+        self.mouse_motion
+    }
+
+    /// How many wheel steps were scrolled this frame.
+    pub fn wheel(&self) -> Vec2 {
+        self.wheel
+    }
+
+    /// The ids of the connected controllers, in no particular order.
+    pub fn gamepads(&self) -> impl Iterator<Item = i32> + '_ {
+        self.gamepads.keys().copied()
+    }
+
+    /// A controller axis with the deadzones applied. With several controllers
+    /// connected the one pushed furthest wins.
+    pub fn gamepad_axis(&self, axis: GamepadAxis) -> f32 {
+        let deadzones = self.actions.deadzones;
+        self.gamepads
+            .values()
+            .map(|gamepad| gamepad.axis(axis, &deadzones))
+            .fold(0.0, |furthest: f32, value| {
+                if value.abs() > furthest.abs() {
+                    value
+                } else {
+                    furthest
+                }
+            })
+    }
+
+    /// Whether any button bound to the action is held.
+    pub fn action_held(&self, action: &str) -> bool {
+        self.actions
+            .buttons(action)
+            .iter()
+            .any(|button| self.held.contains(button))
+    }
+
+    /// Whether the action started this frame: one of its buttons was pressed
+    /// while none of the others were already held.
+    pub fn action_pressed(&self, action: &str) -> bool {
+        let buttons = self.actions.buttons(action);
+        buttons.iter().any(|button| self.pressed.contains(button))
+            && buttons
+                .iter()
+                .all(|button| self.pressed.contains(button) || !self.held.contains(button))
+    }
+
+    /// Whether the action stopped this frame: one of its buttons was released
+    /// and none are held anymore.
+    pub fn action_released(&self, action: &str) -> bool {
+        let buttons = self.actions.buttons(action);
+        buttons.iter().any(|button| self.released.contains(button))
+            && !buttons.iter().any(|button| self.held.contains(button))
+    }
+
+    /// The value of a named axis from -1 to 1, the sum of all its sources.
+    pub fn axis(&self, axis: &str) -> f32 {
+        let button_value = |button: &Button| if self.held.contains(button) { 1.0 } else { 0.0 };
+        self.actions
+            .axis_sources(axis)
+            .iter()
+            .map(|source| match source {
+                AxisSource::Buttons { negative, positive } => {
+                    button_value(positive) - button_value(negative)
+                }
+                AxisSource::Gamepad { axis, inverted } => {
+                    let value = self.gamepad_axis(*axis);
+                    if *inverted {
+                        -value
+                    } else {
+                        value
+                    }
+                }
+            })
+            .sum::<f32>()
+            .clamp(-1.0, 1.0)
+    }
+}
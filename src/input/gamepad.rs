@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+
+use glam::Vec2;
+
+/// A button of a game controller, in the order SDL numbers them.
+///
+/// Named after the Xbox layout, `A` is the bottom face button on any pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub(crate) const ALL: [(GamepadButton, &'static str); 15] = [
+        (GamepadButton::A, "PadA"),
+        (GamepadButton::B, "PadB"),
+        (GamepadButton::X, "PadX"),
+        (GamepadButton::Y, "PadY"),
+        (GamepadButton::Back, "PadBack"),
+        (GamepadButton::Guide, "PadGuide"),
+        (GamepadButton::Start, "PadStart"),
+        (GamepadButton::LeftStick, "PadLeftStick"),
+        (GamepadButton::RightStick, "PadRightStick"),
+        (GamepadButton::LeftShoulder, "PadLeftShoulder"),
+        (GamepadButton::RightShoulder, "PadRightShoulder"),
+        (GamepadButton::DPadUp, "PadDPadUp"),
+        (GamepadButton::DPadDown, "PadDPadDown"),
+        (GamepadButton::DPadLeft, "PadDPadLeft"),
+        (GamepadButton::DPadRight, "PadDPadRight"),
+    ];
+
+    /// The button for an SDL game controller button number.
+    pub fn from_sdl(button: u8) -> Option<Self> {
+        Self::ALL
+            .get(usize::from(button))
+            .map(|&(button, _)| button)
+    }
+
+    /// The name used in binding files.
+    pub fn name(self) -> &'static str {
+        Self::ALL[self as usize].1
+    }
+
+    /// Looks a button up by its name in a binding file, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, button_name)| button_name.eq_ignore_ascii_case(name))
+            .map(|&(button, _)| button)
+    }
+}
+
+/// An analog axis of a game controller, in the order SDL numbers them.
+///
+/// Sticks go from -1 to 1 with positive `y` pointing down, like SDL reports
+/// them. Triggers go from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    TriggerLeft,
+    TriggerRight,
+}
+
+impl GamepadAxis {
+    pub(crate) const ALL: [(GamepadAxis, &'static str); 6] = [
+        (GamepadAxis::LeftX, "PadLeftX"),
+        (GamepadAxis::LeftY, "PadLeftY"),
+        (GamepadAxis::RightX, "PadRightX"),
+        (GamepadAxis::RightY, "PadRightY"),
+        (GamepadAxis::TriggerLeft, "PadTriggerLeft"),
+        (GamepadAxis::TriggerRight, "PadTriggerRight"),
+    ];
+
+    /// The axis for an SDL game controller axis number.
+    pub fn from_sdl(axis: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(axis)).map(|&(axis, _)| axis)
+    }
+
+    /// The name used in binding files.
+    pub fn name(self) -> &'static str {
+        Self::ALL[self as usize].1
+    }
+
+    /// Looks an axis up by its name in a binding file, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, axis_name)| axis_name.eq_ignore_ascii_case(name))
+            .map(|&(axis, _)| axis)
+    }
+
+    pub fn is_trigger(self) -> bool {
+        matches!(self, GamepadAxis::TriggerLeft | GamepadAxis::TriggerRight)
+    }
+
+    /// The two axes of the stick this axis belongs to, `None` for triggers.
+    fn stick(self) -> Option<(GamepadAxis, GamepadAxis)> {
+        match self {
+            GamepadAxis::LeftX | GamepadAxis::LeftY => {
+                Some((GamepadAxis::LeftX, GamepadAxis::LeftY))
+            }
+            GamepadAxis::RightX | GamepadAxis::RightY => {
+                Some((GamepadAxis::RightX, GamepadAxis::RightY))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How far sticks and triggers have to move before they count.
+///
+/// Values inside a deadzone read as 0, the rest of the range is stretched
+/// back to the full 0 to 1, so there is no jump at the edge of the deadzone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deadzones {
+    /// Radius around the center of a stick, both axes of the stick together.
+    pub stick: f32,
+    /// Fraction of a trigger's travel.
+    pub trigger: f32,
+    /// How far past its deadzone an axis bound as a button has to be to count as held.
+    pub press: f32,
+}
+
+impl Default for Deadzones {
+    fn default() -> Self {
+        Self {
+            stick: 0.2,
+            trigger: 0.1,
+            press: 0.5,
+        }
+    }
+}
+
+impl Deadzones {
+    /// Applies the stick deadzone to a stick position.
+    pub fn apply_stick(&self, stick: Vec2) -> Vec2 {
+        let length = stick.length();
+        if length <= self.stick {
+            return Vec2::ZERO;
+        }
+        let scaled = ((length - self.stick) / (1.0 - self.stick)).min(1.0);
+        stick / length * scaled
+    }
+
+    /// Applies the trigger deadzone to a trigger position.
+    pub fn apply_trigger(&self, trigger: f32) -> f32 {
+        if trigger <= self.trigger {
+            return 0.0;
+        }
+        ((trigger - self.trigger) / (1.0 - self.trigger)).min(1.0)
+    }
+}
+
+/// Maps the `i16` SDL reports for an axis onto -1 to 1.
+pub fn normalize_axis(value: i16) -> f32 {
+    if value < 0 {
+        f32::from(value) / 32768.0
+    } else {
+        f32::from(value) / 32767.0
+    }
+}
+
+/// What one connected controller is doing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct GamepadState {
+    pub(crate) buttons: HashSet<GamepadButton>,
+    axes: [f32; 6],
+}
+
+impl GamepadState {
+    pub(crate) fn set_axis(&mut self, axis: GamepadAxis, value: f32) {
+        self.axes[axis as usize] = value;
+    }
+
+    /// The position of an axis with the deadzones applied.
+    pub(crate) fn axis(&self, axis: GamepadAxis, deadzones: &Deadzones) -> f32 {
+        match axis.stick() {
+            Some((x, y)) => {
+                let stick = Vec2::new(self.axes[x as usize], self.axes[y as usize]);
+                let stick = deadzones.apply_stick(stick);
+                if axis == x {
+                    stick.x
+                } else {
+                    stick.y
+                }
+            }
+            None => deadzones.apply_trigger(self.axes[axis as usize]),
+        }
+    }
+}
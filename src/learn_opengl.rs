@@ -1,13 +1,75 @@
+use std::{cell::RefCell, collections::HashMap, ffi::c_void, fmt, marker::PhantomData, mem};
+
 use gl33::{
     global_loader::{
         glAttachShader, glBindBuffer, glBindVertexArray, glBufferData, glClearColor,
-        glCompileShader, glCreateProgram, glCreateShader, glDeleteProgram, glDeleteShader,
-        glGenBuffers, glGenVertexArrays, glGetProgramInfoLog, glGetProgramiv, glGetShaderInfoLog,
-        glGetShaderiv, glLinkProgram, glShaderSource, glUseProgram,
+        glCompileShader, glCreateProgram, glCreateShader, glDeleteBuffers, glDeleteProgram,
+        glDeleteShader, glDeleteVertexArrays, glGenBuffers, glGenVertexArrays, glGetProgramInfoLog,
+        glGetProgramiv, glGetShaderInfoLog, glGetShaderiv, glLinkProgram, glShaderSource,
+        glUseProgram, load_global_gl,
     },
     GLenum, GL_ARRAY_BUFFER, GL_COMPILE_STATUS, GL_ELEMENT_ARRAY_BUFFER, GL_FRAGMENT_SHADER,
-    GL_INFO_LOG_LENGTH, GL_LINK_STATUS, GL_VERTEX_SHADER,
+    GL_GEOMETRY_SHADER, GL_INFO_LOG_LENGTH, GL_LINK_STATUS, GL_VERTEX_SHADER,
+};
+
+mod framebuffer;
+mod hot_reload;
+mod mesh;
+mod preprocessor;
+mod program_builder;
+mod reflection;
+mod shader_error;
+mod texture;
+mod uniform;
+mod vertex_layout;
+
+pub use framebuffer::{
+    read_pixels, DepthStencilFormat, Framebuffer, FramebufferBuilder, FramebufferError,
+    Renderbuffer,
+};
+pub use hot_reload::ReloadableProgram;
+pub use mesh::{Index, InstanceBuffer, Mesh, PrimitiveMode};
+pub use preprocessor::{
+    DirectoryFileSystem, PreprocessError, PreprocessedSource, Preprocessor, ShaderFileSystem,
+    SourceFile,
+};
+pub use program_builder::{ProgramBuilder, TransformFeedbackMode};
+pub use reflection::{
+    ActiveAttribute, ActiveUniform, BlockMember, GlslType, LayoutMismatch, ProgramInterface,
+    UniformBlock,
 };
+pub use shader_error::{parse_info_log, Diagnostic, Severity, ShaderError};
+pub use texture::{
+    unpack_alignment, PixelFormat, Texture2D, Texture2DBuilder, TextureFilter, TextureWrap,
+};
+pub use uniform::{Uniform, UniformElement, UniformError};
+pub use vertex_layout::{AttributeFormat, AttributeType, VertexAttribute, VertexLayout};
+
+/// Proof that the OpenGL functions have been loaded for a live context.
+///
+/// Every GL object wrapper borrows the context it was created with, so the
+/// borrow checker makes sure no handle outlives it. The context is neither
+/// `Send` nor `Sync`, since GL calls have to happen on the thread that owns it.
+pub struct GlContext {
+    _not_send: PhantomData<*const ()>,
+}
+
+impl GlContext {
+    /// Loads every OpenGL function through the given loader.
+    ///
+    /// # Safety
+    ///
+    /// The loader has to return function pointers of the GL context that is
+    /// current on this thread, and that context has to stay alive for as long
+    /// as the returned value does.
+    pub unsafe fn load_with(loader: impl Fn(*const u8) -> *const c_void) -> Self {
+        load_global_gl(&loader);
+
+        Self {
+            _not_send: PhantomData,
+        }
+    }
+}
 
 /// Clear the buffer with the following color.
 pub fn clear_color(r: f32, g: f32, b: f32, a: f32) {
@@ -15,28 +77,48 @@ pub fn clear_color(r: f32, g: f32, b: f32, a: f32) {
     unsafe { glClearColor(r, g, b, a) };
 }
 
-/// Basic wrapper for a vertex array object.
-pub struct VertexArray(pub u32);
+/// Owning wrapper for a vertex array object.
+///
+/// The vertex array is deleted when the wrapper is dropped.
+pub struct VertexArray<'gl> {
+    id: u32,
+    _gl: PhantomData<&'gl GlContext>,
+}
 
-impl VertexArray {
+impl<'gl> VertexArray<'gl> {
     /// Creates a new vertex array object.
-    pub fn new() -> Option<Self> {
+    pub fn new(_gl: &'gl GlContext) -> Option<Self> {
         let mut vao = 0u32;
         unsafe {
             glGenVertexArrays(1, &mut vao);
         }
         if vao != 0 {
-            Some(Self(vao))
+            Some(Self {
+                id: vao,
+                _gl: PhantomData,
+            })
         } else {
             None
         }
     }
 
+    /// The GL name of this vertex array.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Gives up ownership of the vertex array without deleting it and returns its GL name.
+    pub fn leak(self) -> u32 {
+        let id = self.id;
+        mem::forget(self);
+        id
+    }
+
     /// Bind this vertex array as the current vertex array object.
     /// With glBindVertexArray we would make the "vao" (vertex array object) as the active VAO.
     /// This is context wide effect and all functions now operate on this vao.
     pub fn bind(&self) {
-        glBindVertexArray(self.0);
+        glBindVertexArray(self.id);
     }
 
     /// Clear the current vertex array object binding.
@@ -45,6 +127,14 @@ impl VertexArray {
     }
 }
 
+impl Drop for VertexArray<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            glDeleteVertexArrays(1, &self.id);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BufferType {
     /// Array Buffers hold arrays of vertex data for drawing.
@@ -53,37 +143,57 @@ pub enum BufferType {
     ElementArray,
 }
 
-impl Into<GLenum> for BufferType {
-    fn into(self) -> GLenum {
-        match self {
+impl From<BufferType> for GLenum {
+    fn from(value: BufferType) -> Self {
+        match value {
             BufferType::Array => GL_ARRAY_BUFFER,
             BufferType::ElementArray => GL_ELEMENT_ARRAY_BUFFER,
         }
     }
 }
 
-/// Basic wrapper for a buffer object.
-pub struct Buffer(pub u32);
+/// Owning wrapper for a buffer object.
+///
+/// The buffer is deleted when the wrapper is dropped.
+pub struct Buffer<'gl> {
+    id: u32,
+    _gl: PhantomData<&'gl GlContext>,
+}
 
-impl Buffer {
+impl<'gl> Buffer<'gl> {
     /// Makes a new vertex buffer.
-    pub fn new() -> Option<Self> {
+    pub fn new(_gl: &'gl GlContext) -> Option<Self> {
         let mut vbo = 0;
         unsafe {
             glGenBuffers(1, &mut vbo);
         }
 
         if vbo != 0 {
-            Some(Self(vbo))
+            Some(Self {
+                id: vbo,
+                _gl: PhantomData,
+            })
         } else {
             None
         }
     }
 
+    /// The GL name of this buffer.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Gives up ownership of the buffer without deleting it and returns its GL name.
+    pub fn leak(self) -> u32 {
+        let id = self.id;
+        mem::forget(self);
+        id
+    }
+
     /// Bind this vertex buffer for the given type.
     pub fn bind(&self, buffer_type: BufferType) {
         unsafe {
-            glBindBuffer(buffer_type.into(), self.0);
+            glBindBuffer(buffer_type.into(), self.id);
         }
     }
 
@@ -95,6 +205,14 @@ impl Buffer {
     }
 }
 
+impl Drop for Buffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            glDeleteBuffers(1, &self.id);
+        }
+    }
+}
+
 /// Places a slice of data into a previously-bound buffer.
 pub fn buffer_data(buffer_type: BufferType, data: &[u8], usage: GLenum) {
     unsafe {
@@ -108,46 +226,80 @@ pub fn buffer_data(buffer_type: BufferType, data: &[u8], usage: GLenum) {
 }
 
 /// The types of shader object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ShaderType {
     /// Vertex shaders determine the position of geometry within the screen.
     Vertex,
+    /// Geometry shaders turn whole primitives into zero or more new primitives.
+    Geometry,
     /// Fragment shaders determine the color output of geometry.
     Fragment,
 }
 
-impl Into<GLenum> for ShaderType {
-    fn into(self) -> GLenum {
-        match self {
+impl From<ShaderType> for GLenum {
+    fn from(value: ShaderType) -> Self {
+        match value {
             ShaderType::Vertex => GL_VERTEX_SHADER,
+            ShaderType::Geometry => GL_GEOMETRY_SHADER,
             ShaderType::Fragment => GL_FRAGMENT_SHADER,
         }
     }
 }
 
-/// Basic wrapper of a shader object
-pub struct Shader(pub u32);
+impl fmt::Display for ShaderType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ShaderType::Vertex => "vertex",
+            ShaderType::Geometry => "geometry",
+            ShaderType::Fragment => "fragment",
+        })
+    }
+}
 
-impl Shader {
+/// Owning wrapper of a shader object.
+///
+/// The shader is marked for deletion when the wrapper is dropped.
+pub struct Shader<'gl> {
+    id: u32,
+    _gl: PhantomData<&'gl GlContext>,
+}
+
+impl<'gl> Shader<'gl> {
     /// Makes a new shader.
     ///
     /// Prefer the [`Shader::from_source`](Shader::from_source) method.
     ///
     /// Possibly skip the direct creation of the shader object and use
     /// [`ShaderProgram::from_vert_frag`](ShaderProgram::from_vert_frag) directly.
-    pub fn new(shader_type: ShaderType) -> Option<Self> {
+    pub fn new(_gl: &'gl GlContext, shader_type: ShaderType) -> Option<Self> {
         let shader = glCreateShader(shader_type.into());
         if shader != 0 {
-            return Some(Self(shader));
+            Some(Self {
+                id: shader,
+                _gl: PhantomData,
+            })
         } else {
             None
         }
     }
 
+    /// The GL name of this shader.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Gives up ownership of the shader without deleting it and returns its GL name.
+    pub fn leak(self) -> u32 {
+        let id = self.id;
+        mem::forget(self);
+        id
+    }
+
     /// Assigns (thus replaces previous) source string to the shader.
     pub fn set_source(&self, src: &str) {
         unsafe {
             glShaderSource(
-                self.0,
+                self.id,
                 1,
                 &(src.as_bytes().as_ptr().cast()),
                 &(src.len().try_into().unwrap()),
@@ -157,30 +309,27 @@ impl Shader {
 
     /// Compiles the shader based on the current source.
     pub fn compile(&self) {
-        glCompileShader(self.0);
+        glCompileShader(self.id);
     }
 
     /// Check if previously execute compile was successful.
     pub fn compile_success(&self) -> bool {
         let mut compiled = 0;
-        unsafe { glGetShaderiv(self.0, GL_COMPILE_STATUS, &mut compiled) }
+        unsafe { glGetShaderiv(self.id, GL_COMPILE_STATUS, &mut compiled) }
 
         compiled != 0
     }
 
-    /// Marks a shader for deletion.
-    ///
-    /// This only marks the shader for deletion. It deletes it only after it is unattached.
-    pub fn delete(self) {
-        glDeleteShader(self.0);
-    }
-
-    /// Takes a shader type and source string and produces either the compiled shader or an error message.
+    /// Takes a shader type and source string and produces either the compiled shader or an error
+    /// describing what the compiler did not like.
     ///
     /// Prefer [`ShaderProgram::from_vert_frag`](ShaderProgram::from_vert_frag).
-    pub fn from_source(shader_type: ShaderType, source: &str) -> Result<Self, String> {
-        let id =
-            Self::new(shader_type).ok_or_else(|| "Could not allocate new shader".to_string())?;
+    pub fn from_source(
+        gl: &'gl GlContext,
+        shader_type: ShaderType,
+        source: &str,
+    ) -> Result<Self, ShaderError> {
+        let id = Self::new(gl, shader_type).ok_or(ShaderError::ShaderAllocation(shader_type))?;
 
         id.set_source(source);
         id.compile();
@@ -188,18 +337,23 @@ impl Shader {
         if id.compile_success() {
             Ok(id)
         } else {
-            let out = id.get_info_log();
-            id.delete();
-            Err(out)
+            Err(ShaderError::compile(shader_type, source, id.get_info_log()))
         }
     }
 }
 
-impl InfoLog for Shader {
+impl Drop for Shader<'_> {
+    /// This only marks the shader for deletion. It deletes it only after it is unattached.
+    fn drop(&mut self) {
+        glDeleteShader(self.id);
+    }
+}
+
+impl InfoLog for Shader<'_> {
     fn get_info_length(&self) -> i32 {
         let mut needed_len = 0;
         unsafe {
-            glGetShaderiv(self.0, GL_INFO_LOG_LENGTH, &mut needed_len);
+            glGetShaderiv(self.id, GL_INFO_LOG_LENGTH, &mut needed_len);
         }
 
         needed_len
@@ -210,7 +364,7 @@ impl InfoLog for Shader {
 
         unsafe {
             glGetShaderInfoLog(
-                self.0,
+                self.id,
                 buffer.capacity().try_into().unwrap(),
                 &mut len_written,
                 buffer.as_mut_ptr().cast(),
@@ -221,92 +375,94 @@ impl InfoLog for Shader {
     }
 }
 
-/// Basic wrapper for shader program.
-pub struct ShaderProgram(pub u32);
+/// Owning wrapper for shader program.
+///
+/// The program is marked for deletion when the wrapper is dropped.
+pub struct ShaderProgram<'gl> {
+    id: u32,
+    uniform_locations: RefCell<HashMap<String, i32>>,
+    _gl: PhantomData<&'gl GlContext>,
+}
 
-impl ShaderProgram {
+impl<'gl> ShaderProgram<'gl> {
     /// Allocates a new program object.
     ///
     /// Prefer [`ShaderProgram::from_vert_frag`](ShaderProgram::from_vert_frag),
     /// it makes a complete program from the vertex and fragment sources all at
     /// once.
-    pub fn new() -> Option<Self> {
+    pub fn new(_gl: &'gl GlContext) -> Option<Self> {
         let prog = glCreateProgram();
         if prog != 0 {
-            Some(Self(prog))
+            Some(Self {
+                id: prog,
+                uniform_locations: RefCell::new(HashMap::new()),
+                _gl: PhantomData,
+            })
         } else {
             None
         }
     }
 
+    /// The GL name of this program.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Gives up ownership of the program without deleting it and returns its GL name.
+    pub fn leak(self) -> u32 {
+        let id = self.id;
+        mem::forget(self);
+        id
+    }
+
     /// Attaches a shader object to this program object.
     pub fn attach_shader(&self, shader: &Shader) {
-        glAttachShader(self.0, shader.0);
+        glAttachShader(self.id, shader.id);
     }
 
     /// Links the various attached, compiled shader objects into a usable program.
     pub fn link_program(&self) {
-        glLinkProgram(self.0);
+        glLinkProgram(self.id);
     }
 
     /// Checks if the last linking operation was successful.
     pub fn link_success(&self) -> bool {
         let mut success = 0;
-        unsafe { glGetProgramiv(self.0, GL_LINK_STATUS, &mut success) };
+        unsafe { glGetProgramiv(self.id, GL_LINK_STATUS, &mut success) };
 
         success != 0
     }
 
     /// Sets the program as the program to use when drawing.
     pub fn use_program(&self) {
-        glUseProgram(self.0);
-    }
-
-    /// Marks the program for deletion.
-    ///
-    /// Note: This _does not_ immediately delete the program. If the program is
-    /// currently in use it won't be deleted until it's not the active program.
-    /// When a program is finally deleted and attached shaders are unattached.
-    pub fn delete(self) {
-        glDeleteProgram(self.0);
+        glUseProgram(self.id);
     }
 
     /// Takes a vertex shader source string and a fragment shader source string
-    /// and either gets you a working program object or gets you an error message.
+    /// and either gets you a working program object or gets you an error.
     ///
     /// This is the preferred way to create a simple shader program in the common
-    /// case. It's just less error prone than doing all the steps yourself.
-    pub fn from_vert_frag(vert: &str, frag: &str) -> Result<Self, String> {
-        let p = Self::new().ok_or_else(|| "Couldn't allocate a program".to_string())?;
-
-        let v = Shader::from_source(ShaderType::Vertex, vert)
-            .map_err(|e| format!("Vertex Compile Error: {}", e))?;
-
-        let f = Shader::from_source(ShaderType::Fragment, frag)
-            .map_err(|e| format!("Fragment Compile Error: {}", e))?;
-
-        p.attach_shader(&v);
-        p.attach_shader(&f);
-        p.link_program();
-
-        v.delete();
-        f.delete();
+    /// case. It's just less error prone than doing all the steps yourself. For
+    /// more stages or pre-link settings use the [`ProgramBuilder`].
+    pub fn from_vert_frag(gl: &'gl GlContext, vert: &str, frag: &str) -> Result<Self, ShaderError> {
+        ProgramBuilder::new().vertex(vert).fragment(frag).build(gl)
+    }
+}
 
-        if p.link_success() {
-            Ok(p)
-        } else {
-            let out = format!("Program Link Error: {}", p.get_info_log());
-            p.delete();
-            Err(out)
-        }
+impl Drop for ShaderProgram<'_> {
+    /// Note: This _does not_ immediately delete the program. If the program is
+    /// currently in use it won't be deleted until it's not the active program.
+    /// When a program is finally deleted and attached shaders are unattached.
+    fn drop(&mut self) {
+        glDeleteProgram(self.id);
     }
 }
 
-impl InfoLog for ShaderProgram {
+impl InfoLog for ShaderProgram<'_> {
     fn get_info_length(&self) -> i32 {
         let mut needed_len = 0;
         unsafe {
-            glGetProgramiv(self.0, GL_INFO_LOG_LENGTH, &mut needed_len);
+            glGetProgramiv(self.id, GL_INFO_LOG_LENGTH, &mut needed_len);
         }
 
         needed_len
@@ -316,7 +472,7 @@ impl InfoLog for ShaderProgram {
         let mut len_written = 0;
         unsafe {
             glGetProgramInfoLog(
-                self.0,
+                self.id,
                 buffer.capacity().try_into().unwrap(),
                 &mut len_written,
                 buffer.as_mut_ptr().cast(),
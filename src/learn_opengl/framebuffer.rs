@@ -0,0 +1,497 @@
+use std::{error::Error, fmt, marker::PhantomData, mem};
+
+use gl33::{
+    global_loader::{
+        glBindFramebuffer, glBindRenderbuffer, glCheckFramebufferStatus, glDeleteFramebuffers,
+        glDeleteRenderbuffers, glDrawBuffers, glFramebufferRenderbuffer, glFramebufferTexture2D,
+        glGenFramebuffers, glGenRenderbuffers, glGetIntegerv, glPixelStorei, glReadBuffer,
+        glReadPixels, glRenderbufferStorage, glViewport,
+    },
+    GLenum, GL_COLOR_ATTACHMENT0, GL_DEPTH24_STENCIL8, GL_DEPTH_ATTACHMENT, GL_DEPTH_COMPONENT24,
+    GL_DEPTH_STENCIL_ATTACHMENT, GL_FRAMEBUFFER, GL_FRAMEBUFFER_COMPLETE,
+    GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT, GL_FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER,
+    GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT, GL_FRAMEBUFFER_INCOMPLETE_MULTISAMPLE,
+    GL_FRAMEBUFFER_INCOMPLETE_READ_BUFFER, GL_FRAMEBUFFER_UNDEFINED, GL_FRAMEBUFFER_UNSUPPORTED,
+    GL_MAX_COLOR_ATTACHMENTS, GL_MAX_DRAW_BUFFERS, GL_NONE, GL_PACK_ALIGNMENT, GL_READ_FRAMEBUFFER,
+    GL_RENDERBUFFER, GL_RGBA, GL_TEXTURE_2D, GL_UNSIGNED_BYTE,
+};
+use image::{imageops, RgbaImage};
+
+use super::{GlContext, PixelFormat, Texture2D, TextureFilter, TextureWrap};
+
+/// Why a framebuffer can't be rendered to.
+///
+/// Everything but [`FramebufferError::CreateFailed`] and
+/// [`FramebufferError::TooManyColorAttachments`] is a status
+/// `glCheckFramebufferStatus` reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramebufferError {
+    /// GL did not hand out a name for the framebuffer or one of its attachments.
+    CreateFailed,
+    /// More color attachments were asked for than the driver can draw to at once.
+    TooManyColorAttachments { requested: usize, max: usize },
+    /// The default framebuffer is bound, but there is no window.
+    Undefined,
+    /// An attachment has no storage, a size of zero or a format that can't be
+    /// rendered to.
+    IncompleteAttachment,
+    /// Nothing is attached at all.
+    MissingAttachment,
+    /// A draw buffer names an attachment point with nothing attached.
+    IncompleteDrawBuffer,
+    /// The read buffer names an attachment point with nothing attached.
+    IncompleteReadBuffer,
+    /// The combination of formats is not supported by the driver.
+    Unsupported,
+    /// The attachments don't all have the same number of samples.
+    IncompleteMultisample,
+    /// A status this wrapper does not know about.
+    Unknown(u32),
+}
+
+impl FramebufferError {
+    /// The error for a status returned by `glCheckFramebufferStatus`, `None`
+    /// when it says the framebuffer is complete.
+    pub fn from_status(status: GLenum) -> Option<Self> {
+        Some(match status {
+            GL_FRAMEBUFFER_COMPLETE => return None,
+            GL_FRAMEBUFFER_UNDEFINED => Self::Undefined,
+            GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT => Self::IncompleteAttachment,
+            GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => Self::MissingAttachment,
+            GL_FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => Self::IncompleteDrawBuffer,
+            GL_FRAMEBUFFER_INCOMPLETE_READ_BUFFER => Self::IncompleteReadBuffer,
+            GL_FRAMEBUFFER_UNSUPPORTED => Self::Unsupported,
+            GL_FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => Self::IncompleteMultisample,
+            GLenum(status) => Self::Unknown(status),
+        })
+    }
+}
+
+impl fmt::Display for FramebufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateFailed => f.write_str("could not create the framebuffer objects"),
+            Self::TooManyColorAttachments { requested, max } => write!(
+                f,
+                "{} color attachments were requested, but at most {} are supported",
+                requested, max
+            ),
+            Self::Undefined => f.write_str("the default framebuffer does not exist"),
+            Self::IncompleteAttachment => f.write_str("an attachment is incomplete"),
+            Self::MissingAttachment => f.write_str("the framebuffer has no attachments"),
+            Self::IncompleteDrawBuffer => {
+                f.write_str("a draw buffer refers to a missing attachment")
+            }
+            Self::IncompleteReadBuffer => {
+                f.write_str("the read buffer refers to a missing attachment")
+            }
+            Self::Unsupported => f.write_str("the attachment formats are not supported together"),
+            Self::IncompleteMultisample => {
+                f.write_str("the attachments have different sample counts")
+            }
+            Self::Unknown(status) => write!(f, "unknown framebuffer status {:#x}", status),
+        }
+    }
+}
+
+impl Error for FramebufferError {}
+
+/// The formats of the depth and stencil renderbuffer of a [`Framebuffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthStencilFormat {
+    /// 24 bit depth, no stencil.
+    Depth24,
+    /// 24 bit depth and an 8 bit stencil in the same buffer.
+    Depth24Stencil8,
+}
+
+impl DepthStencilFormat {
+    fn internal_format(self) -> GLenum {
+        match self {
+            Self::Depth24 => GL_DEPTH_COMPONENT24,
+            Self::Depth24Stencil8 => GL_DEPTH24_STENCIL8,
+        }
+    }
+
+    fn attachment(self) -> GLenum {
+        match self {
+            Self::Depth24 => GL_DEPTH_ATTACHMENT,
+            Self::Depth24Stencil8 => GL_DEPTH_STENCIL_ATTACHMENT,
+        }
+    }
+}
+
+/// Owning wrapper for a renderbuffer, storage that can be rendered to but
+/// not sampled.
+///
+/// The renderbuffer is deleted when the wrapper is dropped.
+pub struct Renderbuffer<'gl> {
+    id: u32,
+    width: u32,
+    height: u32,
+    _gl: PhantomData<&'gl GlContext>,
+}
+
+impl<'gl> Renderbuffer<'gl> {
+    /// Makes a renderbuffer with storage in the given internal format.
+    pub fn new(
+        _gl: &'gl GlContext,
+        width: u32,
+        height: u32,
+        internal_format: GLenum,
+    ) -> Option<Self> {
+        let mut id = 0;
+        unsafe {
+            glGenRenderbuffers(1, &mut id);
+        }
+        if id == 0 {
+            return None;
+        }
+
+        unsafe {
+            glBindRenderbuffer(GL_RENDERBUFFER, id);
+            glRenderbufferStorage(
+                GL_RENDERBUFFER,
+                internal_format,
+                width.try_into().unwrap(),
+                height.try_into().unwrap(),
+            );
+            glBindRenderbuffer(GL_RENDERBUFFER, 0);
+        }
+
+        Some(Self {
+            id,
+            width,
+            height,
+            _gl: PhantomData,
+        })
+    }
+
+    /// The GL name of this renderbuffer.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Gives up ownership of the renderbuffer without deleting it and returns its GL name.
+    pub fn leak(self) -> u32 {
+        let id = self.id;
+        mem::forget(self);
+        id
+    }
+}
+
+impl Drop for Renderbuffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            glDeleteRenderbuffers(1, &self.id);
+        }
+    }
+}
+
+/// Builder for the attachments of a [`Framebuffer`].
+#[derive(Debug, Clone)]
+pub struct FramebufferBuilder {
+    width: u32,
+    height: u32,
+    color: Vec<PixelFormat>,
+    depth_stencil: Option<DepthStencilFormat>,
+    filter: TextureFilter,
+}
+
+impl FramebufferBuilder {
+    /// Adds a color texture, written by fragment shader output `n` for the
+    /// `n`th call.
+    pub fn color(mut self, format: PixelFormat) -> Self {
+        self.color.push(format);
+        self
+    }
+
+    /// Adds a depth, and maybe stencil, renderbuffer.
+    pub fn depth_stencil(self, format: DepthStencilFormat) -> Self {
+        Self {
+            depth_stencil: Some(format),
+            ..self
+        }
+    }
+
+    /// Sets how the color textures are sampled once rendered, defaults to linear.
+    pub fn filter(self, filter: TextureFilter) -> Self {
+        Self { filter, ..self }
+    }
+
+    /// Makes the attachments and checks that the framebuffer is complete.
+    ///
+    /// Leaves the default framebuffer bound.
+    pub fn build<'gl>(&self, gl: &'gl GlContext) -> Result<Framebuffer<'gl>, FramebufferError> {
+        let max = max_color_attachments();
+        if self.color.len() > max {
+            return Err(FramebufferError::TooManyColorAttachments {
+                requested: self.color.len(),
+                max,
+            });
+        }
+
+        let mut id = 0;
+        unsafe {
+            glGenFramebuffers(1, &mut id);
+        }
+        if id == 0 {
+            return Err(FramebufferError::CreateFailed);
+        }
+        // Owned from here on, so the early returns delete it
+        let mut framebuffer = Framebuffer {
+            id,
+            width: self.width,
+            height: self.height,
+            color: Vec::with_capacity(self.color.len()),
+            depth_stencil: None,
+            _gl: PhantomData,
+        };
+        unsafe {
+            glBindFramebuffer(GL_FRAMEBUFFER, id);
+        }
+
+        // Render targets are drawn at their own size, mipmaps would go stale
+        let texture_builder = Texture2D::builder()
+            .wrap(TextureWrap::ClampToEdge)
+            .min_filter(self.filter)
+            .mag_filter(self.filter)
+            .mipmaps(false);
+        for (attachment, &format) in self.color.iter().enumerate() {
+            let texture = texture_builder
+                .build_empty(gl, self.width, self.height, format)
+                .ok_or(FramebufferError::CreateFailed)?;
+            unsafe {
+                glFramebufferTexture2D(
+                    GL_FRAMEBUFFER,
+                    color_attachment(attachment),
+                    GL_TEXTURE_2D,
+                    texture.id(),
+                    0,
+                );
+            }
+            framebuffer.color.push(texture);
+        }
+
+        if let Some(format) = self.depth_stencil {
+            let renderbuffer =
+                Renderbuffer::new(gl, self.width, self.height, format.internal_format())
+                    .ok_or(FramebufferError::CreateFailed)?;
+            unsafe {
+                glFramebufferRenderbuffer(
+                    GL_FRAMEBUFFER,
+                    format.attachment(),
+                    GL_RENDERBUFFER,
+                    renderbuffer.id(),
+                );
+            }
+            framebuffer.depth_stencil = Some(renderbuffer);
+        }
+
+        // Every color texture gets the fragment output of the same index, a
+        // depth-only framebuffer draws and reads no color at all
+        let draw_buffers: Vec<GLenum> = (0..self.color.len()).map(color_attachment).collect();
+        unsafe {
+            if draw_buffers.is_empty() {
+                glDrawBuffers(1, &GL_NONE);
+                glReadBuffer(GL_NONE);
+            } else {
+                glDrawBuffers(draw_buffers.len() as i32, draw_buffers.as_ptr());
+                glReadBuffer(draw_buffers[0]);
+            }
+        }
+
+        let status = framebuffer.check_status();
+        Framebuffer::clear_binding();
+        status.map(|()| framebuffer)
+    }
+}
+
+/// Owning wrapper for a framebuffer object, with color textures to render
+/// into and an optional depth and stencil renderbuffer.
+///
+/// The framebuffer and its attachments are deleted when the wrapper is dropped.
+pub struct Framebuffer<'gl> {
+    id: u32,
+    width: u32,
+    height: u32,
+    color: Vec<Texture2D<'gl>>,
+    depth_stencil: Option<Renderbuffer<'gl>>,
+    _gl: PhantomData<&'gl GlContext>,
+}
+
+impl<'gl> Framebuffer<'gl> {
+    /// Starts building a framebuffer of the given size, without attachments.
+    pub fn builder(width: u32, height: u32) -> FramebufferBuilder {
+        FramebufferBuilder {
+            width,
+            height,
+            color: Vec::new(),
+            depth_stencil: None,
+            filter: TextureFilter::Linear,
+        }
+    }
+
+    /// The usual render target: one RGBA8 color texture and a depth and
+    /// stencil buffer.
+    pub fn new(gl: &'gl GlContext, width: u32, height: u32) -> Result<Self, FramebufferError> {
+        Self::builder(width, height)
+            .color(PixelFormat::RGBA8)
+            .depth_stencil(DepthStencilFormat::Depth24Stencil8)
+            .build(gl)
+    }
+
+    /// The GL name of this framebuffer.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The color textures, in the order of the fragment outputs they receive.
+    pub fn color_attachments(&self) -> &[Texture2D<'gl>] {
+        &self.color
+    }
+
+    /// The color texture receiving fragment output `index`.
+    pub fn color_attachment(&self, index: usize) -> Option<&Texture2D<'gl>> {
+        self.color.get(index)
+    }
+
+    pub fn depth_stencil(&self) -> Option<&Renderbuffer<'gl>> {
+        self.depth_stencil.as_ref()
+    }
+
+    /// Asks GL whether the framebuffer can be rendered to, it has to be bound.
+    pub fn check_status(&self) -> Result<(), FramebufferError> {
+        let status = unsafe { glCheckFramebufferStatus(GL_FRAMEBUFFER) };
+        FramebufferError::from_status(status).map_or(Ok(()), Err)
+    }
+
+    /// Binds the framebuffer for drawing and reading, and sets the viewport to
+    /// cover it.
+    pub fn bind(&self) {
+        unsafe {
+            glBindFramebuffer(GL_FRAMEBUFFER, self.id);
+            glViewport(
+                0,
+                0,
+                self.width.try_into().unwrap(),
+                self.height.try_into().unwrap(),
+            );
+        }
+    }
+
+    /// Binds the default framebuffer again.
+    ///
+    /// The viewport is left alone, it has to be set back to the window size.
+    pub fn clear_binding() {
+        unsafe {
+            glBindFramebuffer(GL_FRAMEBUFFER, 0);
+        }
+    }
+
+    /// Reads back a color attachment as 8 bit RGBA, with the top row first.
+    ///
+    /// Waits for rendering to the framebuffer to finish. Leaves the default
+    /// framebuffer bound for reading.
+    ///
+    /// # Panics
+    ///
+    /// If there is no color attachment `index`.
+    pub fn read_pixels(&self, index: usize) -> RgbaImage {
+        assert!(
+            index < self.color.len(),
+            "no color attachment {}, the framebuffer has {}",
+            index,
+            self.color.len()
+        );
+
+        unsafe {
+            glBindFramebuffer(GL_READ_FRAMEBUFFER, self.id);
+            glReadBuffer(color_attachment(index));
+        }
+        let image = read_pixels(0, 0, self.width, self.height);
+        unsafe {
+            // Back to what the builder set up
+            glReadBuffer(color_attachment(0));
+            glBindFramebuffer(GL_READ_FRAMEBUFFER, 0);
+        }
+        image
+    }
+
+    /// Gives up ownership of the framebuffer and its attachments without
+    /// deleting them and returns the GL name of the framebuffer.
+    pub fn leak(self) -> u32 {
+        let mut this = mem::ManuallyDrop::new(self);
+        for texture in mem::take(&mut this.color) {
+            texture.leak();
+        }
+        if let Some(renderbuffer) = this.depth_stencil.take() {
+            renderbuffer.leak();
+        }
+        this.id
+    }
+}
+
+impl Drop for Framebuffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            glDeleteFramebuffers(1, &self.id);
+        }
+    }
+}
+
+/// Reads a rectangle of the bound read framebuffer's read buffer as 8 bit
+/// RGBA, flipped so the top row comes first like in an image file.
+///
+/// `x` and `y` are the bottom left corner in GL window coordinates.
+pub fn read_pixels(x: i32, y: i32, width: u32, height: u32) -> RgbaImage {
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    unsafe {
+        glPixelStorei(GL_PACK_ALIGNMENT, 1);
+        glReadPixels(
+            x,
+            y,
+            width.try_into().unwrap(),
+            height.try_into().unwrap(),
+            GL_RGBA,
+            GL_UNSIGNED_BYTE,
+            pixels.as_mut_ptr().cast(),
+        );
+        glPixelStorei(GL_PACK_ALIGNMENT, 4);
+    }
+
+    let mut image = RgbaImage::from_raw(width, height, pixels).unwrap();
+    // GL rows go bottom to top
+    imageops::flip_vertical_in_place(&mut image);
+    image
+}
+
+fn color_attachment(index: usize) -> GLenum {
+    GLenum(GL_COLOR_ATTACHMENT0.0 + index as u32)
+}
+
+/// How many color attachments can be drawn to at once.
+fn max_color_attachments() -> usize {
+    let (mut attachments, mut draw_buffers) = (0, 0);
+    unsafe {
+        glGetIntegerv(GL_MAX_COLOR_ATTACHMENTS, &mut attachments);
+        glGetIntegerv(GL_MAX_DRAW_BUFFERS, &mut draw_buffers);
+    }
+    attachments.min(draw_buffers).max(0) as usize
+}
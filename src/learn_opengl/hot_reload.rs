@@ -0,0 +1,184 @@
+use std::time::SystemTime;
+
+use super::{
+    GlContext, PreprocessError, Preprocessor, ProgramBuilder, ShaderError, ShaderFileSystem,
+    ShaderProgram, ShaderType,
+};
+
+/// Files a program was built from, with the modification time they had.
+type WatchedFiles = Vec<(String, Option<SystemTime>)>;
+
+/// A shader program built from files that is rebuilt when they change.
+///
+/// Call [`ReloadableProgram::poll`] once per frame. When any stage file or any
+/// file it includes got modified, the program is rebuilt. A successful rebuild
+/// replaces the program, which starts out with default uniform values and an
+/// empty location cache. A failed one prints the diagnostics and keeps the
+/// last good program.
+///
+/// ```ignore
+/// let mut program = ReloadableProgram::new(
+///     &gl,
+///     Preprocessor::new(DirectoryFileSystem::new("./assets/shaders")),
+///     &[(ShaderType::Vertex, "cube.vert"), (ShaderType::Fragment, "cube.frag")],
+/// )?;
+///
+/// loop {
+///     if program.poll() {
+///         // Set the uniforms that are only set once again.
+///     }
+///     program.use_program();
+/// }
+/// ```
+pub struct ReloadableProgram<'gl, F> {
+    gl: &'gl GlContext,
+    preprocessor: Preprocessor<F>,
+    stages: Vec<(ShaderType, String)>,
+    program: ShaderProgram<'gl>,
+    watched: WatchedFiles,
+}
+
+impl<'gl, F: ShaderFileSystem> ReloadableProgram<'gl, F> {
+    /// Builds the program from the given stage files.
+    pub fn new(
+        gl: &'gl GlContext,
+        preprocessor: Preprocessor<F>,
+        stages: &[(ShaderType, &str)],
+    ) -> Result<Self, ShaderError> {
+        let stages: Vec<_> = stages
+            .iter()
+            .map(|&(shader_type, path)| (shader_type, path.to_string()))
+            .collect();
+        let mut watched = Vec::new();
+        let program = Self::build(gl, &preprocessor, &stages, &mut watched)?;
+
+        Ok(Self {
+            gl,
+            preprocessor,
+            stages,
+            program,
+            watched,
+        })
+    }
+
+    /// Builds the program, adding every file it reaches to `watched`.
+    ///
+    /// The files named by a preprocessor error are watched as well, so that
+    /// fixing a broken or missing include triggers the next reload.
+    fn build(
+        gl: &'gl GlContext,
+        preprocessor: &Preprocessor<F>,
+        stages: &[(ShaderType, String)],
+        watched: &mut WatchedFiles,
+    ) -> Result<ShaderProgram<'gl>, ShaderError> {
+        let mut builder = ProgramBuilder::new();
+
+        for (shader_type, path) in stages {
+            let source = match preprocessor.process(path) {
+                Ok(source) => source,
+                Err(e) => {
+                    for path in error_paths(&e) {
+                        watch(watched, preprocessor, path);
+                    }
+                    return Err(e.into());
+                }
+            };
+            for file in &source.files {
+                watch(watched, preprocessor, &file.name);
+            }
+            builder = builder.preprocessed_stage(*shader_type, source);
+        }
+
+        builder.build(gl)
+    }
+
+    /// The last program that built successfully.
+    pub fn program(&self) -> &ShaderProgram<'gl> {
+        &self.program
+    }
+
+    /// Sets the last good program as the program to use when drawing.
+    pub fn use_program(&self) {
+        self.program.use_program();
+    }
+
+    /// Whether any of the files changed since the last build attempt.
+    pub fn changed(&self) -> bool {
+        self.watched
+            .iter()
+            .any(|(path, modified)| self.preprocessor.file_system().modified(path) != *modified)
+    }
+
+    /// Rebuilds the program right away, keeping the last good one on failure.
+    pub fn reload(&mut self) -> Result<(), ShaderError> {
+        // Whatever happens, don't retry the same broken files on every poll.
+        for (path, modified) in &mut self.watched {
+            *modified = self.preprocessor.file_system().modified(path);
+        }
+
+        let mut watched = Vec::new();
+        let result = Self::build(self.gl, &self.preprocessor, &self.stages, &mut watched);
+        if result.is_err() {
+            // The last good program still depends on the old files, and the
+            // build may have stopped before reaching all of them.
+            for (path, modified) in self.watched.drain(..) {
+                if watched.iter().all(|(watched, _)| watched != &path) {
+                    watched.push((path, modified));
+                }
+            }
+        }
+        self.watched = watched;
+        self.program = result?;
+        Ok(())
+    }
+
+    /// Rebuilds the program if any file changed, printing the diagnostics if that fails.
+    ///
+    /// Returns `true` when a new program replaced the old one, so uniforms have to be set again.
+    pub fn poll(&mut self) -> bool {
+        if !self.changed() {
+            return false;
+        }
+
+        match self.reload() {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Shader reload failed, keeping the last good program\n{}", e);
+                false
+            }
+        }
+    }
+}
+
+/// Adds the file to the watched ones with its current modification time.
+fn watch<F: ShaderFileSystem>(
+    watched: &mut WatchedFiles,
+    preprocessor: &Preprocessor<F>,
+    path: &str,
+) {
+    if watched.iter().all(|(watched, _)| watched != path) {
+        let modified = preprocessor.file_system().modified(path);
+        watched.push((path.to_string(), modified));
+    }
+}
+
+/// The files a preprocessor error is about, including the ones that are missing.
+fn error_paths(error: &PreprocessError) -> Vec<&str> {
+    match error {
+        PreprocessError::Read {
+            path,
+            included_from,
+            ..
+        } => included_from
+            .iter()
+            .map(|(file, _)| file.as_str())
+            .chain([path.as_str()])
+            .collect(),
+        PreprocessError::Cycle { path, chain } => chain
+            .iter()
+            .map(String::as_str)
+            .chain([path.as_str()])
+            .collect(),
+        PreprocessError::MalformedInclude { file, .. } => vec![file.as_str()],
+    }
+}
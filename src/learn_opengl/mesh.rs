@@ -0,0 +1,272 @@
+use std::{marker::PhantomData, ptr};
+
+use gl33::{
+    global_loader::{glDrawArrays, glDrawArraysInstanced, glDrawElements, glDrawElementsInstanced},
+    GLenum, GL_DYNAMIC_DRAW, GL_LINES, GL_LINE_LOOP, GL_LINE_STRIP, GL_POINTS, GL_STATIC_DRAW,
+    GL_TRIANGLES, GL_TRIANGLE_FAN, GL_TRIANGLE_STRIP, GL_UNSIGNED_BYTE, GL_UNSIGNED_INT,
+    GL_UNSIGNED_SHORT,
+};
+
+use super::{buffer_data, Buffer, BufferType, GlContext, VertexArray, VertexLayout};
+
+/// How the vertices of a draw call are put together into primitives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrimitiveMode {
+    Points,
+    Lines,
+    LineLoop,
+    LineStrip,
+    #[default]
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+impl From<PrimitiveMode> for GLenum {
+    fn from(mode: PrimitiveMode) -> Self {
+        match mode {
+            PrimitiveMode::Points => GL_POINTS,
+            PrimitiveMode::Lines => GL_LINES,
+            PrimitiveMode::LineLoop => GL_LINE_LOOP,
+            PrimitiveMode::LineStrip => GL_LINE_STRIP,
+            PrimitiveMode::Triangles => GL_TRIANGLES,
+            PrimitiveMode::TriangleStrip => GL_TRIANGLE_STRIP,
+            PrimitiveMode::TriangleFan => GL_TRIANGLE_FAN,
+        }
+    }
+}
+
+/// The integer types an index buffer can hold.
+pub trait Index: bytemuck::Pod {
+    /// The type passed to `glDrawElements`.
+    const TYPE: GLenum;
+}
+
+impl Index for u8 {
+    const TYPE: GLenum = GL_UNSIGNED_BYTE;
+}
+
+impl Index for u16 {
+    const TYPE: GLenum = GL_UNSIGNED_SHORT;
+}
+
+impl Index for u32 {
+    const TYPE: GLenum = GL_UNSIGNED_INT;
+}
+
+/// Geometry ready to be drawn: a vertex array with its vertex buffer and,
+/// optionally, an index buffer.
+///
+/// The attributes are set up from the [`VertexLayout`] of `V`, and the draw
+/// calls use the counts and index type recorded when the mesh was made.
+pub struct Mesh<'gl, V: VertexLayout> {
+    vao: VertexArray<'gl>,
+    vertex_buffer: Buffer<'gl>,
+    index_buffer: Option<Buffer<'gl>>,
+    vertex_count: usize,
+    index_count: usize,
+    index_type: Option<GLenum>,
+    mode: PrimitiveMode,
+    _vertex: PhantomData<V>,
+}
+
+impl<'gl, V: VertexLayout> Mesh<'gl, V> {
+    /// Uploads the vertices, drawn in order.
+    pub fn new(gl: &'gl GlContext, vertices: &[V], mode: PrimitiveMode) -> Option<Self> {
+        let vao = VertexArray::new(gl)?;
+        let vertex_buffer = Buffer::new(gl)?;
+
+        vao.apply_layout::<V>(&vertex_buffer);
+        buffer_data(
+            BufferType::Array,
+            bytemuck::cast_slice(vertices),
+            GL_STATIC_DRAW,
+        );
+
+        VertexArray::clear_binding();
+        Buffer::clear_binding(BufferType::Array);
+
+        Some(Self {
+            vao,
+            vertex_buffer,
+            index_buffer: None,
+            vertex_count: vertices.len(),
+            index_count: 0,
+            index_type: None,
+            mode,
+            _vertex: PhantomData,
+        })
+    }
+
+    /// Uploads the vertices and the indices of the vertices to draw.
+    pub fn with_indices<I: Index>(
+        gl: &'gl GlContext,
+        vertices: &[V],
+        indices: &[I],
+        mode: PrimitiveMode,
+    ) -> Option<Self> {
+        let mut mesh = Self::new(gl, vertices, mode)?;
+        let index_buffer = Buffer::new(gl)?;
+
+        // The element buffer binding is part of the vertex array state, so it
+        // must stay bound until the vertex array is unbound
+        mesh.vao.bind();
+        index_buffer.bind(BufferType::ElementArray);
+        buffer_data(
+            BufferType::ElementArray,
+            bytemuck::cast_slice(indices),
+            GL_STATIC_DRAW,
+        );
+        VertexArray::clear_binding();
+        Buffer::clear_binding(BufferType::ElementArray);
+
+        mesh.index_buffer = Some(index_buffer);
+        mesh.index_count = indices.len();
+        mesh.index_type = Some(I::TYPE);
+        Some(mesh)
+    }
+
+    pub fn vertex_array(&self) -> &VertexArray<'gl> {
+        &self.vao
+    }
+
+    pub fn vertex_buffer(&self) -> &Buffer<'gl> {
+        &self.vertex_buffer
+    }
+
+    pub fn index_buffer(&self) -> Option<&Buffer<'gl>> {
+        self.index_buffer.as_ref()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// The number of indices, 0 for a mesh without an index buffer.
+    pub fn index_count(&self) -> usize {
+        self.index_count
+    }
+
+    /// The type of the indices, `None` for a mesh without an index buffer.
+    pub fn index_type(&self) -> Option<GLenum> {
+        self.index_type
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.index_buffer.is_some()
+    }
+
+    pub fn mode(&self) -> PrimitiveMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: PrimitiveMode) {
+        self.mode = mode;
+    }
+
+    /// Draws the whole mesh with the program in use.
+    ///
+    /// Leaves the vertex array bound.
+    pub fn draw(&self) {
+        self.vao.bind();
+        unsafe {
+            match self.index_type {
+                Some(index_type) => glDrawElements(
+                    self.mode.into(),
+                    self.index_count as i32,
+                    index_type,
+                    ptr::null(),
+                ),
+                None => glDrawArrays(self.mode.into(), 0, self.vertex_count as i32),
+            }
+        }
+    }
+
+    /// Draws the mesh `instances` times with the program in use, the shader
+    /// tells them apart by `gl_InstanceID` or by per-instance attributes.
+    ///
+    /// Leaves the vertex array bound.
+    pub fn draw_instanced(&self, instances: u32) {
+        self.vao.bind();
+        unsafe {
+            match self.index_type {
+                Some(index_type) => glDrawElementsInstanced(
+                    self.mode.into(),
+                    self.index_count as i32,
+                    index_type,
+                    ptr::null(),
+                    instances as i32,
+                ),
+                None => glDrawArraysInstanced(
+                    self.mode.into(),
+                    0,
+                    self.vertex_count as i32,
+                    instances as i32,
+                ),
+            }
+        }
+    }
+
+    /// Draws the mesh once for every instance in `instances`, in a single
+    /// draw call.
+    ///
+    /// The attributes of `I` are pointed at the instance buffer in this mesh's
+    /// vertex array first, their locations must not overlap the ones of `V`.
+    /// They are disabled again afterwards, so a plain [`draw`](Mesh::draw)
+    /// does not step through the instance buffer. Leaves the vertex array bound.
+    pub fn draw_instances<I: VertexLayout>(&self, instances: &InstanceBuffer<'gl, I>) {
+        self.vao.apply_layout::<I>(&instances.buffer);
+        Buffer::clear_binding(BufferType::Array);
+        self.draw_instanced(instances.len() as u32);
+        self.vao.disable_layout::<I>();
+    }
+}
+
+/// Per-instance attributes for [`Mesh::draw_instances`], such as a model
+/// matrix and a color for every copy of the mesh.
+///
+/// The attributes of `I` should have a divisor of 1, otherwise they step
+/// along with the vertices instead of the instances.
+pub struct InstanceBuffer<'gl, I: VertexLayout> {
+    buffer: Buffer<'gl>,
+    len: usize,
+    _instance: PhantomData<I>,
+}
+
+impl<'gl, I: VertexLayout> InstanceBuffer<'gl, I> {
+    /// Uploads the instances, drawn in order.
+    pub fn new(gl: &'gl GlContext, instances: &[I]) -> Option<Self> {
+        let mut buffer = Self {
+            buffer: Buffer::new(gl)?,
+            len: 0,
+            _instance: PhantomData,
+        };
+        buffer.update(instances);
+        Some(buffer)
+    }
+
+    /// Replaces all of the instances, the buffer grows or shrinks to fit.
+    pub fn update(&mut self, instances: &[I]) {
+        self.buffer.bind(BufferType::Array);
+        buffer_data(
+            BufferType::Array,
+            bytemuck::cast_slice(instances),
+            GL_DYNAMIC_DRAW,
+        );
+        Buffer::clear_binding(BufferType::Array);
+        self.len = instances.len();
+    }
+
+    pub fn buffer(&self) -> &Buffer<'gl> {
+        &self.buffer
+    }
+
+    /// The number of instances drawn.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
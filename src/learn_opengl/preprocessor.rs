@@ -0,0 +1,334 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt, fs, io,
+    path::PathBuf,
+    time::SystemTime,
+};
+
+/// Where `#include`d shader files are read from.
+pub trait ShaderFileSystem {
+    /// Reads the file at the given `/` separated path.
+    fn read(&self, path: &str) -> io::Result<String>;
+
+    /// When the file was last modified, `None` if that is not known.
+    ///
+    /// Used to decide when shaders are reloaded.
+    fn modified(&self, _path: &str) -> Option<SystemTime> {
+        None
+    }
+}
+
+/// An in-memory file system, mapping paths to sources.
+impl ShaderFileSystem for HashMap<String, String> {
+    fn read(&self, path: &str) -> io::Result<String> {
+        self.get(path).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("no virtual file {}", path))
+        })
+    }
+}
+
+/// Reads shader files from a directory on disk.
+#[derive(Debug, Clone)]
+pub struct DirectoryFileSystem {
+    root: PathBuf,
+}
+
+impl DirectoryFileSystem {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl ShaderFileSystem for DirectoryFileSystem {
+    fn read(&self, path: &str) -> io::Result<String> {
+        fs::read_to_string(self.root.join(path))
+    }
+
+    fn modified(&self, path: &str) -> Option<SystemTime> {
+        fs::metadata(self.root.join(path))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+/// One of the files that make up a preprocessed shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// The path the file was read from.
+    pub name: String,
+    pub text: String,
+}
+
+/// The result of preprocessing a shader.
+///
+/// Every file is given its GLSL source string number by its position in
+/// `files`, the root file is number 0. The `#line` directives in `code` use
+/// those numbers, so the driver reports diagnostics against the original files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreprocessedSource {
+    pub code: String,
+    pub files: Vec<SourceFile>,
+}
+
+/// Everything that can go wrong while preprocessing.
+#[derive(Debug)]
+pub enum PreprocessError {
+    /// A file could not be read.
+    Read {
+        path: String,
+        /// The file and line of the `#include`, `None` for the root file.
+        included_from: Option<(String, usize)>,
+        error: io::Error,
+    },
+    /// A file includes itself, directly or through other files.
+    Cycle { path: String, chain: Vec<String> },
+    /// An `#include` line that is not of the form `#include "path"`.
+    MalformedInclude { file: String, line: usize },
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreprocessError::Read {
+                path,
+                included_from: Some((file, line)),
+                error,
+            } => write!(
+                f,
+                "{}:{}: could not include {}: {}",
+                file, line, path, error
+            ),
+            PreprocessError::Read {
+                path,
+                included_from: None,
+                error,
+            } => write!(f, "could not read {}: {}", path, error),
+            PreprocessError::Cycle { path, chain } => {
+                write!(
+                    f,
+                    "{} includes itself: {} -> {}",
+                    path,
+                    chain.join(" -> "),
+                    path
+                )
+            }
+            PreprocessError::MalformedInclude { file, line } => {
+                write!(f, "{}:{}: expected #include \"path\"", file, line)
+            }
+        }
+    }
+}
+
+impl Error for PreprocessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PreprocessError::Read { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves `#include "file.glsl"`, injects `#define`s and inserts `#line`
+/// directives, so several files can be compiled as one shader.
+///
+/// Includes are resolved relative to the including file. A file that starts
+/// with `#pragma once` is only included the first time.
+///
+/// ```ignore
+/// let source = Preprocessor::new(DirectoryFileSystem::new("./assets/shaders"))
+///     .define("MAX_LIGHTS", "4")
+///     .flag("USE_NORMAL_MAP")
+///     .process("lit.frag")?;
+/// ```
+pub struct Preprocessor<F> {
+    file_system: F,
+    defines: Vec<(String, String)>,
+}
+
+impl<F: ShaderFileSystem> Preprocessor<F> {
+    pub fn new(file_system: F) -> Self {
+        Self {
+            file_system,
+            defines: Vec::new(),
+        }
+    }
+
+    /// The file system the files are read from.
+    pub fn file_system(&self) -> &F {
+        &self.file_system
+    }
+
+    /// Adds a `#define name value` right after the `#version` line.
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.push((name.into(), value.into()));
+        self
+    }
+
+    /// Adds a `#define name` without a value, for `#ifdef` variants.
+    pub fn flag(self, name: impl Into<String>) -> Self {
+        self.define(name, "")
+    }
+
+    /// Preprocesses the file at the given path.
+    pub fn process(&self, path: &str) -> Result<PreprocessedSource, PreprocessError> {
+        let text = self
+            .file_system
+            .read(path)
+            .map_err(|error| PreprocessError::Read {
+                path: path.to_string(),
+                included_from: None,
+                error,
+            })?;
+        self.process_source(path, &text)
+    }
+
+    /// Preprocesses a source that is already in memory, like a `const` string.
+    ///
+    /// The name is used for diagnostics and to resolve relative includes.
+    pub fn process_source(
+        &self,
+        name: &str,
+        text: &str,
+    ) -> Result<PreprocessedSource, PreprocessError> {
+        let mut state = State {
+            code: String::new(),
+            files: Vec::new(),
+            chain: Vec::new(),
+            included_once: HashSet::new(),
+        };
+        self.expand(&mut state, name, text.to_string(), true)?;
+
+        Ok(PreprocessedSource {
+            code: state.code,
+            files: state.files,
+        })
+    }
+
+    fn defines(&self, code: &mut String) {
+        for (name, value) in &self.defines {
+            if value.is_empty() {
+                code.push_str(&format!("#define {}\n", name));
+            } else {
+                code.push_str(&format!("#define {} {}\n", name, value));
+            }
+        }
+    }
+
+    fn expand(
+        &self,
+        state: &mut State,
+        name: &str,
+        text: String,
+        root: bool,
+    ) -> Result<(), PreprocessError> {
+        let index = state.files.len();
+        state.chain.push(name.to_string());
+        state.files.push(SourceFile {
+            name: name.to_string(),
+            text: text.clone(),
+        });
+
+        let has_version = root
+            && text
+                .lines()
+                .any(|line| directive(line, "version").is_some());
+        if root && !has_version {
+            self.defines(&mut state.code);
+            state.code.push_str(&format!("#line 1 {}\n", index));
+        }
+
+        for (line_number, line) in text.lines().enumerate().map(|(n, line)| (n + 1, line)) {
+            if root && directive(line, "version").is_some() {
+                state.code.push_str(line);
+                state.code.push('\n');
+                self.defines(&mut state.code);
+                state
+                    .code
+                    .push_str(&format!("#line {} {}\n", line_number + 1, index));
+            } else if directive(line, "version").is_some() {
+                // Only the root file gets to pick the version.
+                state.code.push('\n');
+            } else if directive(line, "pragma").is_some_and(|pragma| pragma.trim() == "once") {
+                state.included_once.insert(name.to_string());
+                state.code.push('\n');
+            } else if let Some(argument) = directive(line, "include") {
+                let include = argument
+                    .trim()
+                    .strip_prefix('"')
+                    .and_then(|rest| rest.strip_suffix('"'))
+                    .ok_or_else(|| PreprocessError::MalformedInclude {
+                        file: name.to_string(),
+                        line: line_number,
+                    })?;
+                let path = resolve(name, include);
+
+                if state.chain.contains(&path) {
+                    return Err(PreprocessError::Cycle {
+                        path,
+                        chain: state.chain.clone(),
+                    });
+                }
+                if state.included_once.contains(&path) {
+                    state.code.push('\n');
+                    continue;
+                }
+
+                let included =
+                    self.file_system
+                        .read(&path)
+                        .map_err(|error| PreprocessError::Read {
+                            path: path.clone(),
+                            included_from: Some((name.to_string(), line_number)),
+                            error,
+                        })?;
+                state
+                    .code
+                    .push_str(&format!("#line 1 {}\n", state.files.len()));
+                self.expand(state, &path, included, false)?;
+                state
+                    .code
+                    .push_str(&format!("#line {} {}\n", line_number + 1, index));
+            } else {
+                state.code.push_str(line);
+                state.code.push('\n');
+            }
+        }
+
+        state.chain.pop();
+        Ok(())
+    }
+}
+
+struct State {
+    code: String,
+    files: Vec<SourceFile>,
+    chain: Vec<String>,
+    included_once: HashSet<String>,
+}
+
+/// The argument of a `#name ...` directive line, `# name` is allowed too.
+fn directive<'l>(line: &'l str, name: &str) -> Option<&'l str> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let argument = rest.strip_prefix(name)?;
+    (argument.is_empty() || argument.starts_with(char::is_whitespace)).then_some(argument)
+}
+
+/// Resolves `include` relative to the directory of `from`, folding `.` and `..`.
+fn resolve(from: &str, include: &str) -> String {
+    let mut parts: Vec<&str> = from.split('/').collect();
+    parts.pop();
+
+    for part in include.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    parts.retain(|part| !part.is_empty());
+    parts.join("/")
+}
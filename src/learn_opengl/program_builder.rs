@@ -0,0 +1,174 @@
+use std::ffi::CString;
+
+use gl33::{
+    global_loader::{glBindAttribLocation, glBindFragDataLocation, glTransformFeedbackVaryings},
+    GLenum, GL_INTERLEAVED_ATTRIBS, GL_SEPARATE_ATTRIBS,
+};
+
+use super::{
+    GlContext, InfoLog, PreprocessedSource, Shader, ShaderError, ShaderProgram, ShaderType,
+    SourceFile,
+};
+
+/// How transform feedback varyings are written to their buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformFeedbackMode {
+    /// All varyings go into a single buffer, one vertex after the other.
+    Interleaved,
+    /// Every varying goes into its own buffer binding.
+    Separate,
+}
+
+impl From<TransformFeedbackMode> for GLenum {
+    fn from(mode: TransformFeedbackMode) -> Self {
+        match mode {
+            TransformFeedbackMode::Interleaved => GL_INTERLEAVED_ATTRIBS,
+            TransformFeedbackMode::Separate => GL_SEPARATE_ATTRIBS,
+        }
+    }
+}
+
+/// Collects the stages and pre-link settings of a program and builds it in one go.
+///
+/// ```ignore
+/// let program = ProgramBuilder::new()
+///     .vertex(VERT_SHADER)
+///     .geometry(GEOM_SHADER)
+///     .fragment(FRAG_SHADER)
+///     .bind_frag_data_location(0, "final_color")
+///     .build(&gl)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProgramBuilder {
+    stages: Vec<(ShaderType, String, Vec<SourceFile>)>,
+    attrib_locations: Vec<(u32, String)>,
+    frag_data_locations: Vec<(u32, String)>,
+    feedback_varyings: Vec<String>,
+    feedback_mode: Option<TransformFeedbackMode>,
+}
+
+impl ProgramBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a stage with its source. Stages are compiled in the order they are added.
+    pub fn stage(mut self, shader_type: ShaderType, source: impl Into<String>) -> Self {
+        self.stages.push((shader_type, source.into(), Vec::new()));
+        self
+    }
+
+    /// Adds a stage that went through the [`Preprocessor`](super::Preprocessor).
+    ///
+    /// Compile errors of the stage point into the original files.
+    pub fn preprocessed_stage(
+        mut self,
+        shader_type: ShaderType,
+        source: PreprocessedSource,
+    ) -> Self {
+        self.stages.push((shader_type, source.code, source.files));
+        self
+    }
+
+    /// Adds a vertex stage.
+    pub fn vertex(self, source: impl Into<String>) -> Self {
+        self.stage(ShaderType::Vertex, source)
+    }
+
+    /// Adds a geometry stage.
+    pub fn geometry(self, source: impl Into<String>) -> Self {
+        self.stage(ShaderType::Geometry, source)
+    }
+
+    /// Adds a fragment stage.
+    pub fn fragment(self, source: impl Into<String>) -> Self {
+        self.stage(ShaderType::Fragment, source)
+    }
+
+    /// Binds a vertex input to a location before linking, for shaders without
+    /// `layout (location = ...)` qualifiers.
+    pub fn bind_attrib_location(mut self, index: u32, name: impl Into<String>) -> Self {
+        self.attrib_locations.push((index, name.into()));
+        self
+    }
+
+    /// Binds a fragment output to a draw buffer before linking.
+    pub fn bind_frag_data_location(mut self, color_number: u32, name: impl Into<String>) -> Self {
+        self.frag_data_locations.push((color_number, name.into()));
+        self
+    }
+
+    /// Captures the given outputs of the last vertex processing stage with transform feedback.
+    pub fn transform_feedback_varyings<S: Into<String>>(
+        mut self,
+        varyings: impl IntoIterator<Item = S>,
+        mode: TransformFeedbackMode,
+    ) -> Self {
+        self.feedback_varyings = varyings.into_iter().map(Into::into).collect();
+        self.feedback_mode = Some(mode);
+        self
+    }
+
+    /// Compiles every stage, applies the pre-link settings and links the program.
+    ///
+    /// A compile error reports the stage that failed.
+    pub fn build<'gl>(&self, gl: &'gl GlContext) -> Result<ShaderProgram<'gl>, ShaderError> {
+        let program = ShaderProgram::new(gl).ok_or(ShaderError::ProgramAllocation)?;
+
+        let shaders = self
+            .stages
+            .iter()
+            .map(|(shader_type, source, files)| {
+                Shader::from_source(gl, *shader_type, source)
+                    .map_err(|e| e.with_files(files.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for shader in &shaders {
+            program.attach_shader(shader);
+        }
+
+        for (index, name) in &self.attrib_locations {
+            let name = c_string(name)?;
+            unsafe { glBindAttribLocation(program.id, *index, name.as_ptr().cast()) };
+        }
+
+        for (color_number, name) in &self.frag_data_locations {
+            let name = c_string(name)?;
+            unsafe { glBindFragDataLocation(program.id, *color_number, name.as_ptr().cast()) };
+        }
+
+        if let Some(mode) = self.feedback_mode {
+            let names = self
+                .feedback_varyings
+                .iter()
+                .map(|name| c_string(name))
+                .collect::<Result<Vec<_>, _>>()?;
+            let pointers: Vec<*const u8> = names.iter().map(|name| name.as_ptr().cast()).collect();
+            unsafe {
+                glTransformFeedbackVaryings(
+                    program.id,
+                    pointers.len().try_into().unwrap(),
+                    pointers.as_ptr().cast(),
+                    mode.into(),
+                );
+            }
+        }
+
+        program.link_program();
+
+        // The shaders are only marked for deletion here, they get deleted once the
+        // program is and they are detached.
+        drop(shaders);
+
+        if program.link_success() {
+            Ok(program)
+        } else {
+            Err(ShaderError::link(program.get_info_log()))
+        }
+    }
+}
+
+fn c_string(name: &str) -> Result<CString, ShaderError> {
+    CString::new(name).map_err(|_| ShaderError::InvalidName(name.to_string()))
+}
@@ -0,0 +1,563 @@
+use std::{ffi::CString, fmt};
+
+use gl33::{
+    global_loader::{
+        glGetActiveAttrib, glGetActiveUniform, glGetActiveUniformBlockName,
+        glGetActiveUniformBlockiv, glGetActiveUniformsiv, glGetAttribLocation, glGetProgramiv,
+        glGetUniformLocation,
+    },
+    GL_FLOAT_MAT2x3, GL_FLOAT_MAT2x4, GL_FLOAT_MAT3x2, GL_FLOAT_MAT3x4, GL_FLOAT_MAT4x2,
+    GL_FLOAT_MAT4x3, GLenum, GL_ACTIVE_ATTRIBUTES, GL_ACTIVE_ATTRIBUTE_MAX_LENGTH,
+    GL_ACTIVE_UNIFORMS, GL_ACTIVE_UNIFORM_BLOCKS, GL_ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH,
+    GL_ACTIVE_UNIFORM_MAX_LENGTH, GL_BOOL, GL_BOOL_VEC2, GL_BOOL_VEC3, GL_BOOL_VEC4, GL_FLOAT,
+    GL_FLOAT_MAT2, GL_FLOAT_MAT3, GL_FLOAT_MAT4, GL_FLOAT_VEC2, GL_FLOAT_VEC3, GL_FLOAT_VEC4,
+    GL_INT, GL_INT_SAMPLER_2D, GL_INT_VEC2, GL_INT_VEC3, GL_INT_VEC4, GL_SAMPLER_1D, GL_SAMPLER_2D,
+    GL_SAMPLER_2D_ARRAY, GL_SAMPLER_2D_SHADOW, GL_SAMPLER_3D, GL_SAMPLER_CUBE,
+    GL_UNIFORM_ARRAY_STRIDE, GL_UNIFORM_BLOCK_BINDING, GL_UNIFORM_BLOCK_DATA_SIZE,
+    GL_UNIFORM_BLOCK_INDEX, GL_UNIFORM_MATRIX_STRIDE, GL_UNIFORM_OFFSET, GL_UNSIGNED_INT,
+    GL_UNSIGNED_INT_SAMPLER_2D, GL_UNSIGNED_INT_VEC2, GL_UNSIGNED_INT_VEC3, GL_UNSIGNED_INT_VEC4,
+};
+
+use super::{ShaderProgram, VertexAttribute, VertexLayout};
+
+/// The GLSL type of an active attribute or uniform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlslType {
+    Float,
+    Vec2,
+    Vec3,
+    Vec4,
+    Int,
+    IVec2,
+    IVec3,
+    IVec4,
+    UInt,
+    UVec2,
+    UVec3,
+    UVec4,
+    Bool,
+    BVec2,
+    BVec3,
+    BVec4,
+    Mat2,
+    Mat3,
+    Mat4,
+    Mat2x3,
+    Mat2x4,
+    Mat3x2,
+    Mat3x4,
+    Mat4x2,
+    Mat4x3,
+    Sampler1D,
+    Sampler2D,
+    Sampler3D,
+    SamplerCube,
+    Sampler2DShadow,
+    Sampler2DArray,
+    ISampler2D,
+    USampler2D,
+    /// A type this wrapper does not name, with the raw GL enum.
+    Other(GLenum),
+}
+
+impl GlslType {
+    /// Number of components in a single attribute slot, a matrix column for matrices.
+    pub fn components(self) -> Option<i32> {
+        use GlslType::*;
+        match self {
+            Float | Int | UInt | Bool => Some(1),
+            Vec2 | IVec2 | UVec2 | BVec2 | Mat2 | Mat3x2 | Mat4x2 => Some(2),
+            Vec3 | IVec3 | UVec3 | BVec3 | Mat3 | Mat2x3 | Mat4x3 => Some(3),
+            Vec4 | IVec4 | UVec4 | BVec4 | Mat4 | Mat2x4 | Mat3x4 => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Number of attribute locations the type takes up, the column count for matrices.
+    pub fn slots(self) -> u32 {
+        use GlslType::*;
+        match self {
+            Mat2 | Mat2x3 | Mat2x4 => 2,
+            Mat3 | Mat3x2 | Mat3x4 => 3,
+            Mat4 | Mat4x2 | Mat4x3 => 4,
+            _ => 1,
+        }
+    }
+}
+
+/// GL type enum and GLSL spelling of every named type.
+const GLSL_TYPES: &[(GLenum, GlslType, &str)] = &[
+    (GL_FLOAT, GlslType::Float, "float"),
+    (GL_FLOAT_VEC2, GlslType::Vec2, "vec2"),
+    (GL_FLOAT_VEC3, GlslType::Vec3, "vec3"),
+    (GL_FLOAT_VEC4, GlslType::Vec4, "vec4"),
+    (GL_INT, GlslType::Int, "int"),
+    (GL_INT_VEC2, GlslType::IVec2, "ivec2"),
+    (GL_INT_VEC3, GlslType::IVec3, "ivec3"),
+    (GL_INT_VEC4, GlslType::IVec4, "ivec4"),
+    (GL_UNSIGNED_INT, GlslType::UInt, "uint"),
+    (GL_UNSIGNED_INT_VEC2, GlslType::UVec2, "uvec2"),
+    (GL_UNSIGNED_INT_VEC3, GlslType::UVec3, "uvec3"),
+    (GL_UNSIGNED_INT_VEC4, GlslType::UVec4, "uvec4"),
+    (GL_BOOL, GlslType::Bool, "bool"),
+    (GL_BOOL_VEC2, GlslType::BVec2, "bvec2"),
+    (GL_BOOL_VEC3, GlslType::BVec3, "bvec3"),
+    (GL_BOOL_VEC4, GlslType::BVec4, "bvec4"),
+    (GL_FLOAT_MAT2, GlslType::Mat2, "mat2"),
+    (GL_FLOAT_MAT3, GlslType::Mat3, "mat3"),
+    (GL_FLOAT_MAT4, GlslType::Mat4, "mat4"),
+    (GL_FLOAT_MAT2x3, GlslType::Mat2x3, "mat2x3"),
+    (GL_FLOAT_MAT2x4, GlslType::Mat2x4, "mat2x4"),
+    (GL_FLOAT_MAT3x2, GlslType::Mat3x2, "mat3x2"),
+    (GL_FLOAT_MAT3x4, GlslType::Mat3x4, "mat3x4"),
+    (GL_FLOAT_MAT4x2, GlslType::Mat4x2, "mat4x2"),
+    (GL_FLOAT_MAT4x3, GlslType::Mat4x3, "mat4x3"),
+    (GL_SAMPLER_1D, GlslType::Sampler1D, "sampler1D"),
+    (GL_SAMPLER_2D, GlslType::Sampler2D, "sampler2D"),
+    (GL_SAMPLER_3D, GlslType::Sampler3D, "sampler3D"),
+    (GL_SAMPLER_CUBE, GlslType::SamplerCube, "samplerCube"),
+    (
+        GL_SAMPLER_2D_SHADOW,
+        GlslType::Sampler2DShadow,
+        "sampler2DShadow",
+    ),
+    (
+        GL_SAMPLER_2D_ARRAY,
+        GlslType::Sampler2DArray,
+        "sampler2DArray",
+    ),
+    (GL_INT_SAMPLER_2D, GlslType::ISampler2D, "isampler2D"),
+    (
+        GL_UNSIGNED_INT_SAMPLER_2D,
+        GlslType::USampler2D,
+        "usampler2D",
+    ),
+];
+
+impl From<GLenum> for GlslType {
+    fn from(gl_type: GLenum) -> Self {
+        GLSL_TYPES
+            .iter()
+            .find(|(known, _, _)| *known == gl_type)
+            .map_or(GlslType::Other(gl_type), |&(_, glsl_type, _)| glsl_type)
+    }
+}
+
+impl fmt::Display for GlslType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match GLSL_TYPES
+            .iter()
+            .find(|(_, glsl_type, _)| glsl_type == self)
+        {
+            Some((_, _, name)) => f.write_str(name),
+            None => match self {
+                GlslType::Other(gl_type) => write!(f, "<type {:#x}>", gl_type.0),
+                _ => unreachable!("every named type is in the table"),
+            },
+        }
+    }
+}
+
+/// An input of the vertex stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveAttribute {
+    pub name: String,
+    pub glsl_type: GlslType,
+    /// Number of array elements, 1 for non arrays.
+    pub size: i32,
+    /// The location, -1 for built-ins like `gl_VertexID`.
+    pub location: i32,
+}
+
+/// Where a uniform that lives in a uniform block sits within the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockMember {
+    pub block_index: u32,
+    /// Byte offset from the start of the block.
+    pub offset: i32,
+    /// Bytes between array elements, 0 for non arrays.
+    pub array_stride: i32,
+    /// Bytes between matrix columns, 0 for non matrices.
+    pub matrix_stride: i32,
+}
+
+/// A uniform of any stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveUniform {
+    /// The name, arrays are reported as `name[0]`.
+    pub name: String,
+    pub glsl_type: GlslType,
+    /// Number of array elements, 1 for non arrays.
+    pub size: i32,
+    /// The location, -1 for uniforms inside of a block.
+    pub location: i32,
+    /// The block layout, if the uniform is part of a uniform block.
+    pub block: Option<BlockMember>,
+}
+
+/// A uniform block and the uniforms inside of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformBlock {
+    pub name: String,
+    pub index: u32,
+    /// The uniform buffer binding point the block reads from.
+    pub binding: u32,
+    /// Minimum size of the buffer backing the block in bytes.
+    pub data_size: i32,
+    /// Indices into [`ProgramInterface::uniforms`] of the members, sorted by offset.
+    pub members: Vec<usize>,
+}
+
+/// Everything a linked program expects from the outside.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramInterface {
+    pub attributes: Vec<ActiveAttribute>,
+    pub uniforms: Vec<ActiveUniform>,
+    pub blocks: Vec<UniformBlock>,
+}
+
+/// Ways a vertex layout can fail to feed a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutMismatch {
+    /// The program reads an attribute that the layout does not provide.
+    Missing { name: String, location: i32 },
+    /// The layout provides a different number of components than the program reads.
+    Components {
+        name: String,
+        location: i32,
+        expected: i32,
+        found: i32,
+    },
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutMismatch::Missing { name, location } => write!(
+                f,
+                "attribute `{}` at location {} is not provided by the vertex layout",
+                name, location
+            ),
+            LayoutMismatch::Components {
+                name,
+                location,
+                expected,
+                found,
+            } => write!(
+                f,
+                "attribute `{}` at location {} reads {} components, the vertex layout provides {}",
+                name, location, expected, found
+            ),
+        }
+    }
+}
+
+impl ProgramInterface {
+    /// Finds an attribute by name.
+    pub fn attribute(&self, name: &str) -> Option<&ActiveAttribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+    }
+
+    /// Finds a uniform by name, `name` and `name[0]` both match an array.
+    pub fn uniform(&self, name: &str) -> Option<&ActiveUniform> {
+        self.uniforms
+            .iter()
+            .find(|uniform| uniform.name == name || uniform.name.strip_suffix("[0]") == Some(name))
+    }
+
+    /// Finds a uniform block by name.
+    pub fn block(&self, name: &str) -> Option<&UniformBlock> {
+        self.blocks.iter().find(|block| block.name == name)
+    }
+
+    /// The names out of `names` that are not active uniforms of the program.
+    pub fn missing_uniforms<'n>(&self, names: &[&'n str]) -> Vec<&'n str> {
+        names
+            .iter()
+            .copied()
+            .filter(|name| self.uniform(name).is_none())
+            .collect()
+    }
+
+    /// Checks that every attribute the program reads is provided by the vertex layout `V`.
+    pub fn check_layout<V: VertexLayout>(&self) -> Result<(), Vec<LayoutMismatch>> {
+        self.check_layouts(&[V::ATTRIBUTES])
+    }
+
+    /// Checks that every attribute the program reads is provided by one of the
+    /// layouts, like a vertex and an instance layout bound together.
+    ///
+    /// Matrices and arrays take up several locations, each of them has to be
+    /// provided.
+    pub fn check_layouts(&self, layouts: &[&[VertexAttribute]]) -> Result<(), Vec<LayoutMismatch>> {
+        let mut mismatches = Vec::new();
+
+        // Built-ins have no location and are not fed from buffers.
+        for attribute in self.attributes.iter().filter(|a| a.location >= 0) {
+            let first = attribute.location as u32;
+            let slots = attribute.glsl_type.slots() * attribute.size.max(1) as u32;
+
+            for location in first..first + slots {
+                let provided = layouts
+                    .iter()
+                    .flat_map(|layout| layout.iter())
+                    .find(|provided| {
+                        (provided.location..provided.location + provided.slots).contains(&location)
+                    });
+
+                match (provided, attribute.glsl_type.components()) {
+                    (None, _) => mismatches.push(LayoutMismatch::Missing {
+                        name: attribute.name.clone(),
+                        location: location as i32,
+                    }),
+                    (Some(provided), Some(expected)) if provided.components != expected => {
+                        mismatches.push(LayoutMismatch::Components {
+                            name: attribute.name.clone(),
+                            location: location as i32,
+                            expected,
+                            found: provided.components,
+                        })
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+}
+
+impl fmt::Display for ProgramInterface {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn array(size: i32) -> String {
+            if size > 1 {
+                format!(" [{}]", size)
+            } else {
+                String::new()
+            }
+        }
+
+        writeln!(f, "attributes:")?;
+        for attribute in &self.attributes {
+            writeln!(
+                f,
+                "  location {:>2}: {} {}{}",
+                attribute.location,
+                attribute.glsl_type,
+                attribute.name,
+                array(attribute.size)
+            )?;
+        }
+
+        writeln!(f, "uniforms:")?;
+        for uniform in self
+            .uniforms
+            .iter()
+            .filter(|uniform| uniform.block.is_none())
+        {
+            writeln!(
+                f,
+                "  location {:>2}: {} {}{}",
+                uniform.location,
+                uniform.glsl_type,
+                uniform.name,
+                array(uniform.size)
+            )?;
+        }
+
+        writeln!(f, "uniform blocks:")?;
+        for block in &self.blocks {
+            writeln!(
+                f,
+                "  {} (binding {}, {} bytes):",
+                block.name, block.binding, block.data_size
+            )?;
+            for uniform in block.members.iter().map(|&member| &self.uniforms[member]) {
+                let offset = uniform.block.map_or(0, |block| block.offset);
+                writeln!(
+                    f,
+                    "    offset {:>4}: {} {}{}",
+                    offset,
+                    uniform.glsl_type,
+                    uniform.name,
+                    array(uniform.size)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a name of at most `max_len` bytes (including the nul) written by `read`.
+fn read_name(max_len: i32, read: impl FnOnce(i32, &mut i32, *mut u8)) -> String {
+    let mut buffer = vec![0u8; max_len.max(1) as usize];
+    let mut len_written = 0;
+    read(buffer.len() as i32, &mut len_written, buffer.as_mut_ptr());
+    buffer.truncate(len_written.max(0) as usize);
+
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+impl ShaderProgram<'_> {
+    fn get_parameter(&self, parameter: GLenum) -> i32 {
+        let mut value = 0;
+        unsafe { glGetProgramiv(self.id, parameter, &mut value) };
+        value
+    }
+
+    /// Asks the linked program for its active attributes, uniforms and uniform blocks.
+    pub fn interface(&self) -> ProgramInterface {
+        let uniforms = self.active_uniforms();
+
+        ProgramInterface {
+            attributes: self.active_attributes(),
+            blocks: self.blocks_of(&uniforms),
+            uniforms,
+        }
+    }
+
+    /// The active inputs of the vertex stage, sorted by location.
+    pub fn active_attributes(&self) -> Vec<ActiveAttribute> {
+        let count = self.get_parameter(GL_ACTIVE_ATTRIBUTES);
+        let max_len = self.get_parameter(GL_ACTIVE_ATTRIBUTE_MAX_LENGTH);
+
+        let mut attributes: Vec<_> = (0..count.max(0) as u32)
+            .map(|index| {
+                let mut size = 0;
+                let mut gl_type = GLenum(0);
+                let name = read_name(max_len, |buffer_len, len_written, buffer| unsafe {
+                    glGetActiveAttrib(
+                        self.id,
+                        index,
+                        buffer_len,
+                        len_written,
+                        &mut size,
+                        &mut gl_type,
+                        buffer.cast(),
+                    )
+                });
+                let c_name = CString::new(name.as_str()).unwrap();
+                let location = unsafe { glGetAttribLocation(self.id, c_name.as_ptr().cast()) };
+
+                ActiveAttribute {
+                    name,
+                    glsl_type: gl_type.into(),
+                    size,
+                    location,
+                }
+            })
+            .collect();
+
+        attributes.sort_by_key(|attribute| attribute.location);
+        attributes
+    }
+
+    /// The active uniforms of all stages, including the ones inside of uniform blocks.
+    pub fn active_uniforms(&self) -> Vec<ActiveUniform> {
+        let count = self.get_parameter(GL_ACTIVE_UNIFORMS);
+        let max_len = self.get_parameter(GL_ACTIVE_UNIFORM_MAX_LENGTH);
+
+        (0..count.max(0) as u32)
+            .map(|index| {
+                let mut size = 0;
+                let mut gl_type = GLenum(0);
+                let name = read_name(max_len, |buffer_len, len_written, buffer| unsafe {
+                    glGetActiveUniform(
+                        self.id,
+                        index,
+                        buffer_len,
+                        len_written,
+                        &mut size,
+                        &mut gl_type,
+                        buffer.cast(),
+                    )
+                });
+
+                let uniform_parameter = |parameter| {
+                    let mut value = 0;
+                    unsafe { glGetActiveUniformsiv(self.id, 1, &index, parameter, &mut value) };
+                    value
+                };
+                let block_index = uniform_parameter(GL_UNIFORM_BLOCK_INDEX);
+                let block = (block_index >= 0).then(|| BlockMember {
+                    block_index: block_index as u32,
+                    offset: uniform_parameter(GL_UNIFORM_OFFSET),
+                    array_stride: uniform_parameter(GL_UNIFORM_ARRAY_STRIDE),
+                    matrix_stride: uniform_parameter(GL_UNIFORM_MATRIX_STRIDE),
+                });
+
+                let location = if block.is_some() {
+                    -1
+                } else {
+                    let c_name = CString::new(name.as_str()).unwrap();
+                    unsafe { glGetUniformLocation(self.id, c_name.as_ptr().cast()) }
+                };
+
+                ActiveUniform {
+                    name,
+                    glsl_type: gl_type.into(),
+                    size,
+                    location,
+                    block,
+                }
+            })
+            .collect()
+    }
+
+    /// The active uniform blocks, with members indexing into
+    /// [`ShaderProgram::active_uniforms`](ShaderProgram::active_uniforms).
+    pub fn uniform_blocks(&self) -> Vec<UniformBlock> {
+        self.blocks_of(&self.active_uniforms())
+    }
+
+    fn blocks_of(&self, uniforms: &[ActiveUniform]) -> Vec<UniformBlock> {
+        let count = self.get_parameter(GL_ACTIVE_UNIFORM_BLOCKS);
+        let max_len = self.get_parameter(GL_ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH);
+
+        (0..count.max(0) as u32)
+            .map(|index| {
+                let block_parameter = |parameter| {
+                    let mut value = 0;
+                    unsafe { glGetActiveUniformBlockiv(self.id, index, parameter, &mut value) };
+                    value
+                };
+                let name = read_name(max_len, |buffer_len, len_written, buffer| unsafe {
+                    glGetActiveUniformBlockName(
+                        self.id,
+                        index,
+                        buffer_len,
+                        len_written,
+                        buffer.cast(),
+                    )
+                });
+
+                let mut members: Vec<usize> = uniforms
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, uniform)| {
+                        uniform
+                            .block
+                            .is_some_and(|block| block.block_index == index)
+                    })
+                    .map(|(member, _)| member)
+                    .collect();
+                members.sort_by_key(|&member| uniforms[member].block.map(|block| block.offset));
+
+                UniformBlock {
+                    name,
+                    index,
+                    binding: block_parameter(GL_UNIFORM_BLOCK_BINDING) as u32,
+                    data_size: block_parameter(GL_UNIFORM_BLOCK_DATA_SIZE),
+                    members,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,347 @@
+use std::{error::Error, fmt};
+
+use super::{PreprocessError, ShaderType, SourceFile};
+
+/// Everything that can go wrong while building a shader or a shader program.
+#[derive(Debug)]
+pub enum ShaderError {
+    /// A stage could not be put together from its files.
+    Preprocess(PreprocessError),
+    /// `glCreateShader` did not hand out a shader object.
+    ShaderAllocation(ShaderType),
+    /// `glCreateProgram` did not hand out a program object.
+    ProgramAllocation,
+    /// A name handed to GL before linking contains a nul byte.
+    InvalidName(String),
+    /// A shader stage did not compile.
+    Compile {
+        /// The stage that failed.
+        stage: ShaderType,
+        /// The source that was handed to the compiler.
+        source: String,
+        /// The raw info log of the driver.
+        log: String,
+        /// The info log parsed into separate entries.
+        diagnostics: Vec<Diagnostic>,
+        /// The original files of a preprocessed source, indexed by source string number.
+        files: Vec<SourceFile>,
+    },
+    /// The compiled stages could not be linked together.
+    Link {
+        /// The raw info log of the driver.
+        log: String,
+        /// The info log parsed into separate entries.
+        diagnostics: Vec<Diagnostic>,
+    },
+}
+
+impl ShaderError {
+    /// Builds a compile error, parsing the info log into diagnostics.
+    pub fn compile(stage: ShaderType, source: &str, log: String) -> Self {
+        Self::Compile {
+            stage,
+            source: source.to_string(),
+            diagnostics: parse_info_log(&log),
+            log,
+            files: Vec::new(),
+        }
+    }
+
+    /// Attaches the original files of a preprocessed source to a compile error,
+    /// so diagnostics point into them instead of into the combined source.
+    pub fn with_files(self, source_files: Vec<SourceFile>) -> Self {
+        match self {
+            Self::Compile {
+                stage,
+                source,
+                log,
+                mut diagnostics,
+                ..
+            } => {
+                for diagnostic in &mut diagnostics {
+                    diagnostic.file = diagnostic
+                        .source_string
+                        .and_then(|index| source_files.get(index as usize))
+                        .map(|file| file.name.clone());
+                }
+                Self::Compile {
+                    stage,
+                    source,
+                    log,
+                    diagnostics,
+                    files: source_files,
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Builds a link error, parsing the info log into diagnostics.
+    pub fn link(log: String) -> Self {
+        Self::Link {
+            diagnostics: parse_info_log(&log),
+            log,
+        }
+    }
+
+    /// The stage that failed to compile, if this is a compile error.
+    pub fn stage(&self) -> Option<ShaderType> {
+        match self {
+            Self::ShaderAllocation(stage) | Self::Compile { stage, .. } => Some(*stage),
+            Self::Preprocess(_)
+            | Self::ProgramAllocation
+            | Self::InvalidName(_)
+            | Self::Link { .. } => None,
+        }
+    }
+
+    /// The parsed diagnostics of the driver, empty for allocation failures.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            Self::Compile { diagnostics, .. } | Self::Link { diagnostics, .. } => diagnostics,
+            Self::Preprocess(_)
+            | Self::ShaderAllocation(_)
+            | Self::ProgramAllocation
+            | Self::InvalidName(_) => &[],
+        }
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preprocess(e) => write!(f, "{}", e),
+            Self::ShaderAllocation(stage) => write!(f, "could not allocate a {} shader", stage),
+            Self::ProgramAllocation => write!(f, "could not allocate a shader program"),
+            Self::InvalidName(name) => write!(f, "`{}` is not a valid GLSL name", name),
+            Self::Compile {
+                stage,
+                source,
+                log,
+                diagnostics,
+                files,
+            } => {
+                writeln!(f, "{} shader failed to compile", stage)?;
+                if diagnostics.is_empty() {
+                    return write!(f, "{}", log.trim_end());
+                }
+                let stage_name = format!("{} shader", stage);
+                for diagnostic in diagnostics {
+                    match diagnostic
+                        .source_string
+                        .and_then(|index| files.get(index as usize))
+                    {
+                        Some(file) => diagnostic.render(f, &file.name, &file.text)?,
+                        None => diagnostic.render(f, &stage_name, source)?,
+                    }
+                }
+                Ok(())
+            }
+            Self::Link { log, diagnostics } => {
+                writeln!(f, "shader program failed to link")?;
+                if diagnostics.is_empty() {
+                    return write!(f, "{}", log.trim_end());
+                }
+                for diagnostic in diagnostics {
+                    diagnostic.render(f, "program", "")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for ShaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Preprocess(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<PreprocessError> for ShaderError {
+    fn from(e: PreprocessError) -> Self {
+        Self::Preprocess(e)
+    }
+}
+
+/// How bad a single info log entry is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        })
+    }
+}
+
+/// A single entry of a shader or program info log.
+///
+/// Drivers are free to format their logs however they like. The common
+/// formats of Mesa (`0:12(5): error: ...`), NVIDIA (`0(12) : error C0000: ...`)
+/// and AMD/Intel/Apple (`ERROR: 0:12: ...`) are understood, anything else ends
+/// up as a diagnostic without a position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The source string number, 0 unless the shader was built from several strings
+    /// or has `#line` directives.
+    pub source_string: Option<u32>,
+    /// The original file, filled in by [`ShaderError::with_files`] for preprocessed sources.
+    pub file: Option<String>,
+    /// The 1-based line number.
+    pub line: Option<u32>,
+    /// The 1-based column, only reported by some drivers.
+    pub column: Option<u32>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Writes the diagnostic with an excerpt of the offending source line, rustc style.
+    fn render(&self, f: &mut fmt::Formatter<'_>, origin: &str, source: &str) -> fmt::Result {
+        writeln!(f, "{}: {}", self.severity, self.message)?;
+
+        let Some(line) = self.line else {
+            return Ok(());
+        };
+        let Some(text) = source.lines().nth((line as usize).saturating_sub(1)) else {
+            return writeln!(f, " --> {}:{}", origin, line);
+        };
+
+        let gutter = " ".repeat(line.to_string().len());
+        let (caret_start, caret_len) = match self.column {
+            Some(column) => ((column as usize).saturating_sub(1), 1),
+            None => {
+                let trimmed = text.trim_start();
+                (text.len() - trimmed.len(), trimmed.trim_end().len().max(1))
+            }
+        };
+
+        match self.column {
+            Some(column) => writeln!(f, "{}--> {}:{}:{}", gutter, origin, line, column)?,
+            None => writeln!(f, "{}--> {}:{}", gutter, origin, line)?,
+        }
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line, text)?;
+        writeln!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(caret_start),
+            "^".repeat(caret_len)
+        )
+    }
+}
+
+/// Splits a driver info log into diagnostics, one per non-empty line.
+pub fn parse_info_log(log: &str) -> Vec<Diagnostic> {
+    log.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(parse_line)
+        .collect()
+}
+
+fn parse_line(line: &str) -> Diagnostic {
+    parse_prefixed(line)
+        .or_else(|| parse_mesa(line))
+        .or_else(|| parse_nvidia(line))
+        .unwrap_or_else(|| {
+            // Link logs usually have no position, but may still start with a severity.
+            let (severity, message) = line
+                .split_once(':')
+                .and_then(|(severity, message)| Some((severity_of(severity)?, message.trim())))
+                .unwrap_or((Severity::Error, line));
+
+            Diagnostic {
+                source_string: None,
+                file: None,
+                line: None,
+                column: None,
+                severity,
+                message: message.to_string(),
+            }
+        })
+}
+
+/// `ERROR: 0:12: message`
+fn parse_prefixed(line: &str) -> Option<Diagnostic> {
+    let (severity, rest) = line.split_once(':')?;
+    let severity = severity_of(severity)?;
+    let mut parts = rest.trim_start().splitn(3, ':');
+    let source_string = parts.next()?.trim().parse().ok()?;
+    let line_number = parts.next()?.trim().parse().ok()?;
+    let message = parts.next()?.trim();
+
+    Some(Diagnostic {
+        source_string: Some(source_string),
+        file: None,
+        line: Some(line_number),
+        column: None,
+        severity,
+        message: message.to_string(),
+    })
+}
+
+/// `0:12(5): error: message`
+fn parse_mesa(line: &str) -> Option<Diagnostic> {
+    let (source_string, rest) = line.split_once(':')?;
+    let source_string = source_string.trim().parse().ok()?;
+    let (line_number, rest) = rest.split_once('(')?;
+    let line_number = line_number.trim().parse().ok()?;
+    let (column, rest) = rest.split_once(')')?;
+    let column = column.trim().parse().ok()?;
+    let (severity, message) = rest.trim_start_matches(':').split_once(':')?;
+
+    Some(Diagnostic {
+        source_string: Some(source_string),
+        file: None,
+        line: Some(line_number),
+        column: Some(column),
+        severity: severity_of(severity)?,
+        message: message.trim().to_string(),
+    })
+}
+
+/// `0(12) : error C0000: message`
+fn parse_nvidia(line: &str) -> Option<Diagnostic> {
+    let (source_string, rest) = line.split_once('(')?;
+    let source_string = source_string.trim().parse().ok()?;
+    let (line_number, rest) = rest.split_once(')')?;
+    let line_number = line_number.trim().parse().ok()?;
+    let rest = rest.trim_start().strip_prefix(':')?;
+    let (kind, message) = rest.split_once(':')?;
+    // The kind is followed by a vendor code, like "error C1008".
+    let severity = kind.split_whitespace().next()?;
+
+    Some(Diagnostic {
+        source_string: Some(source_string),
+        file: None,
+        line: Some(line_number),
+        column: None,
+        severity: severity_of(severity)?,
+        message: message.trim().to_string(),
+    })
+}
+
+fn severity_of(word: &str) -> Option<Severity> {
+    let word = word.trim();
+    if word.eq_ignore_ascii_case("error") || word.eq_ignore_ascii_case("fatal error") {
+        Some(Severity::Error)
+    } else if word.eq_ignore_ascii_case("warning") {
+        Some(Severity::Warning)
+    } else if word.eq_ignore_ascii_case("note") || word.eq_ignore_ascii_case("info") {
+        Some(Severity::Note)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,395 @@
+use std::{borrow::Cow, marker::PhantomData, mem, ptr};
+
+use gl33::{
+    global_loader::{
+        glActiveTexture, glBindTexture, glDeleteTextures, glGenTextures, glGenerateMipmap,
+        glPixelStorei, glTexImage2D, glTexParameteri, glTexParameteriv,
+    },
+    GLenum, GL_CLAMP_TO_BORDER, GL_CLAMP_TO_EDGE, GL_FLOAT, GL_GREEN, GL_LINEAR,
+    GL_LINEAR_MIPMAP_LINEAR, GL_MIRRORED_REPEAT, GL_NEAREST, GL_NEAREST_MIPMAP_NEAREST, GL_ONE,
+    GL_R16, GL_R8, GL_RED, GL_REPEAT, GL_RG, GL_RG16, GL_RG8, GL_RGB, GL_RGB16, GL_RGB32F, GL_RGB8,
+    GL_RGBA, GL_RGBA16, GL_RGBA16F, GL_RGBA32F, GL_RGBA8, GL_TEXTURE0, GL_TEXTURE_2D,
+    GL_TEXTURE_MAG_FILTER, GL_TEXTURE_MIN_FILTER, GL_TEXTURE_SWIZZLE_RGBA, GL_TEXTURE_WRAP_S,
+    GL_TEXTURE_WRAP_T, GL_UNPACK_ALIGNMENT, GL_UNSIGNED_BYTE, GL_UNSIGNED_SHORT,
+};
+use image::DynamicImage;
+
+use super::GlContext;
+
+/// How texture coordinates outside of `[0, 1]` are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureWrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+impl From<TextureWrap> for GLenum {
+    fn from(wrap: TextureWrap) -> Self {
+        match wrap {
+            TextureWrap::Repeat => GL_REPEAT,
+            TextureWrap::MirroredRepeat => GL_MIRRORED_REPEAT,
+            TextureWrap::ClampToEdge => GL_CLAMP_TO_EDGE,
+            TextureWrap::ClampToBorder => GL_CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// How texels are sampled when the texture is magnified or minified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+
+/// The formats a pixel buffer is uploaded with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelFormat {
+    /// The format the texture is stored in on the GPU.
+    pub internal_format: GLenum,
+    /// The channels of the uploaded data.
+    pub format: GLenum,
+    /// The type of a single channel of the uploaded data.
+    pub data_type: GLenum,
+    /// Size of a single pixel of the uploaded data in bytes.
+    pub bytes_per_pixel: usize,
+    /// Swizzle that makes gray images sample as gray instead of red.
+    pub swizzle: Option<[GLenum; 4]>,
+}
+
+impl PixelFormat {
+    /// 8 bits per channel, the usual color render target.
+    pub const RGBA8: Self = Self::new(GL_RGBA8, GL_RGBA, GL_UNSIGNED_BYTE, 4);
+    /// Half floats per channel, for render targets that go past 1.0.
+    pub const RGBA16F: Self = Self::new(GL_RGBA16F, GL_RGBA, GL_FLOAT, 16);
+    pub const RGBA32F: Self = Self::new(GL_RGBA32F, GL_RGBA, GL_FLOAT, 16);
+
+    const fn new(
+        internal_format: GLenum,
+        format: GLenum,
+        data_type: GLenum,
+        bytes_per_pixel: usize,
+    ) -> Self {
+        Self {
+            internal_format,
+            format,
+            data_type,
+            bytes_per_pixel,
+            swizzle: None,
+        }
+    }
+
+    const fn gray(self) -> Self {
+        Self {
+            swizzle: Some([GL_RED, GL_RED, GL_RED, GL_ONE]),
+            ..self
+        }
+    }
+
+    const fn gray_alpha(self) -> Self {
+        Self {
+            swizzle: Some([GL_RED, GL_RED, GL_RED, GL_GREEN]),
+            ..self
+        }
+    }
+
+    /// Picks the formats matching the color type of the image.
+    ///
+    /// Color types without a direct GL equivalent are converted to RGBA8, so
+    /// the returned bytes may be a converted copy of the image.
+    pub fn of_image(image: &DynamicImage) -> (Self, Cow<'_, [u8]>) {
+        let format = match image {
+            DynamicImage::ImageLuma8(_) => Self::new(GL_R8, GL_RED, GL_UNSIGNED_BYTE, 1).gray(),
+            DynamicImage::ImageLumaA8(_) => {
+                Self::new(GL_RG8, GL_RG, GL_UNSIGNED_BYTE, 2).gray_alpha()
+            }
+            DynamicImage::ImageRgb8(_) => Self::new(GL_RGB8, GL_RGB, GL_UNSIGNED_BYTE, 3),
+            DynamicImage::ImageRgba8(_) => Self::new(GL_RGBA8, GL_RGBA, GL_UNSIGNED_BYTE, 4),
+            DynamicImage::ImageLuma16(_) => Self::new(GL_R16, GL_RED, GL_UNSIGNED_SHORT, 2).gray(),
+            DynamicImage::ImageLumaA16(_) => {
+                Self::new(GL_RG16, GL_RG, GL_UNSIGNED_SHORT, 4).gray_alpha()
+            }
+            DynamicImage::ImageRgb16(_) => Self::new(GL_RGB16, GL_RGB, GL_UNSIGNED_SHORT, 6),
+            DynamicImage::ImageRgba16(_) => Self::new(GL_RGBA16, GL_RGBA, GL_UNSIGNED_SHORT, 8),
+            DynamicImage::ImageRgb32F(_) => Self::new(GL_RGB32F, GL_RGB, GL_FLOAT, 12),
+            DynamicImage::ImageRgba32F(_) => Self::new(GL_RGBA32F, GL_RGBA, GL_FLOAT, 16),
+            _ => {
+                let converted = image.to_rgba8().into_raw();
+                return (
+                    Self::new(GL_RGBA8, GL_RGBA, GL_UNSIGNED_BYTE, 4),
+                    Cow::Owned(converted),
+                );
+            }
+        };
+
+        (format, Cow::Borrowed(image.as_bytes()))
+    }
+}
+
+/// The largest unpack alignment GL accepts that evenly divides a row of pixels.
+///
+/// GL assumes rows start on 4 byte boundaries, which does not hold for tightly
+/// packed RGB or gray images with odd widths.
+pub fn unpack_alignment(width: u32, bytes_per_pixel: usize) -> i32 {
+    let row_len = width as usize * bytes_per_pixel;
+    [8, 4, 2]
+        .into_iter()
+        .find(|alignment| row_len.is_multiple_of(*alignment))
+        .unwrap_or(1) as i32
+}
+
+/// Builder for the sampling options of a [`Texture2D`].
+#[derive(Debug, Clone, Copy)]
+pub struct Texture2DBuilder {
+    wrap_s: TextureWrap,
+    wrap_t: TextureWrap,
+    min_filter: TextureFilter,
+    mag_filter: TextureFilter,
+    mipmaps: bool,
+}
+
+impl Default for Texture2DBuilder {
+    fn default() -> Self {
+        Self {
+            wrap_s: TextureWrap::Repeat,
+            wrap_t: TextureWrap::Repeat,
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            mipmaps: true,
+        }
+    }
+}
+
+impl Texture2DBuilder {
+    /// Sets the wrapping of both texture coordinates.
+    pub fn wrap(self, wrap: TextureWrap) -> Self {
+        Self {
+            wrap_s: wrap,
+            wrap_t: wrap,
+            ..self
+        }
+    }
+
+    /// Sets the wrapping of the horizontal texture coordinate.
+    pub fn wrap_s(self, wrap_s: TextureWrap) -> Self {
+        Self { wrap_s, ..self }
+    }
+
+    /// Sets the wrapping of the vertical texture coordinate.
+    pub fn wrap_t(self, wrap_t: TextureWrap) -> Self {
+        Self { wrap_t, ..self }
+    }
+
+    /// Sets the filter used when the texture is drawn smaller than it is.
+    pub fn min_filter(self, min_filter: TextureFilter) -> Self {
+        Self { min_filter, ..self }
+    }
+
+    /// Sets the filter used when the texture is drawn bigger than it is.
+    pub fn mag_filter(self, mag_filter: TextureFilter) -> Self {
+        Self { mag_filter, ..self }
+    }
+
+    /// Sets whether mipmaps are generated and used for minification.
+    pub fn mipmaps(self, mipmaps: bool) -> Self {
+        Self { mipmaps, ..self }
+    }
+
+    /// Uploads the image with formats picked from its color type.
+    pub fn build<'gl>(&self, gl: &'gl GlContext, image: &DynamicImage) -> Option<Texture2D<'gl>> {
+        let (format, data) = PixelFormat::of_image(image);
+        self.build_raw(gl, image.width(), image.height(), format, &data)
+    }
+
+    /// Uploads tightly packed pixel data in the given format.
+    pub fn build_raw<'gl>(
+        &self,
+        gl: &'gl GlContext,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        data: &[u8],
+    ) -> Option<Texture2D<'gl>> {
+        assert_eq!(
+            data.len(),
+            width as usize * height as usize * format.bytes_per_pixel
+        );
+        self.build_with(gl, width, height, format, Some(data))
+    }
+
+    /// Allocates storage in the given format without uploading anything, the
+    /// contents are undefined until they are rendered to.
+    pub fn build_empty<'gl>(
+        &self,
+        gl: &'gl GlContext,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+    ) -> Option<Texture2D<'gl>> {
+        self.build_with(gl, width, height, format, None)
+    }
+
+    fn build_with<'gl>(
+        &self,
+        gl: &'gl GlContext,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        data: Option<&[u8]>,
+    ) -> Option<Texture2D<'gl>> {
+        let mut texture = Texture2D::new(gl)?;
+        texture.width = width;
+        texture.height = height;
+        texture.bind();
+
+        let min_filter = match (self.min_filter, self.mipmaps) {
+            (TextureFilter::Nearest, false) => GL_NEAREST,
+            (TextureFilter::Linear, false) => GL_LINEAR,
+            (TextureFilter::Nearest, true) => GL_NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::Linear, true) => GL_LINEAR_MIPMAP_LINEAR,
+        };
+        let mag_filter = match self.mag_filter {
+            TextureFilter::Nearest => GL_NEAREST,
+            TextureFilter::Linear => GL_LINEAR,
+        };
+        let wrap_s: GLenum = self.wrap_s.into();
+        let wrap_t: GLenum = self.wrap_t.into();
+
+        unsafe {
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, wrap_s.0 as i32);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, wrap_t.0 as i32);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, min_filter.0 as i32);
+            glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, mag_filter.0 as i32);
+
+            if let Some(swizzle) = format.swizzle {
+                let swizzle = swizzle.map(|channel| channel.0 as i32);
+                glTexParameteriv(GL_TEXTURE_2D, GL_TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+            }
+
+            glPixelStorei(
+                GL_UNPACK_ALIGNMENT,
+                unpack_alignment(width, format.bytes_per_pixel),
+            );
+            glTexImage2D(
+                GL_TEXTURE_2D,
+                0,
+                format.internal_format.0 as i32,
+                width.try_into().unwrap(),
+                height.try_into().unwrap(),
+                0,
+                format.format,
+                format.data_type,
+                data.map_or(ptr::null(), |data| data.as_ptr().cast()),
+            );
+            // Back to the GL default, so later uploads are not surprised.
+            glPixelStorei(GL_UNPACK_ALIGNMENT, 4);
+
+            if self.mipmaps {
+                glGenerateMipmap(GL_TEXTURE_2D);
+            }
+        }
+
+        Texture2D::clear_binding();
+
+        Some(texture)
+    }
+}
+
+/// Owning wrapper for a 2D texture object.
+///
+/// The texture is deleted when the wrapper is dropped.
+pub struct Texture2D<'gl> {
+    id: u32,
+    width: u32,
+    height: u32,
+    _gl: PhantomData<&'gl GlContext>,
+}
+
+impl<'gl> Texture2D<'gl> {
+    /// Makes a new texture object without any storage.
+    ///
+    /// Prefer [`Texture2D::from_image`](Texture2D::from_image) or the
+    /// [`Texture2D::builder`](Texture2D::builder).
+    pub fn new(_gl: &'gl GlContext) -> Option<Self> {
+        let mut texture = 0u32;
+        unsafe {
+            glGenTextures(1, &mut texture);
+        }
+
+        if texture != 0 {
+            Some(Self {
+                id: texture,
+                width: 0,
+                height: 0,
+                _gl: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Starts building a texture with the given sampling options.
+    ///
+    /// Defaults to repeating, linear filtering and generated mipmaps.
+    pub fn builder() -> Texture2DBuilder {
+        Texture2DBuilder::default()
+    }
+
+    /// Uploads the image with the default sampling options.
+    pub fn from_image(gl: &'gl GlContext, image: &DynamicImage) -> Option<Self> {
+        Self::builder().build(gl, image)
+    }
+
+    /// The GL name of this texture.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Width of the base level in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the base level in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Gives up ownership of the texture without deleting it and returns its GL name.
+    pub fn leak(self) -> u32 {
+        let id = self.id;
+        mem::forget(self);
+        id
+    }
+
+    /// Bind this texture to the 2D target of the active texture unit.
+    pub fn bind(&self) {
+        unsafe {
+            glBindTexture(GL_TEXTURE_2D, self.id);
+        }
+    }
+
+    /// Makes the given texture unit active and binds this texture to it.
+    pub fn bind_to_unit(&self, unit: u32) {
+        unsafe {
+            glActiveTexture(GLenum(GL_TEXTURE0.0 + unit));
+        }
+        self.bind();
+    }
+
+    /// Clear the 2D texture binding of the active texture unit.
+    pub fn clear_binding() {
+        unsafe {
+            glBindTexture(GL_TEXTURE_2D, 0);
+        }
+    }
+}
+
+impl Drop for Texture2D<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            glDeleteTextures(1, &self.id);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::{error::Error, ffi::CString, fmt};
+
+use gl33::global_loader::{
+    glGetUniformLocation, glUniform1f, glUniform1fv, glUniform1i, glUniform1iv, glUniform2fv,
+    glUniform3fv, glUniform4fv, glUniformMatrix3fv, glUniformMatrix4fv,
+};
+use glam::{Mat3, Mat4, Vec2, Vec3, Vec4};
+
+use super::ShaderProgram;
+
+/// Errors of looking up or setting a uniform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UniformError {
+    /// The name is not an active uniform of the program. Either it was never
+    /// declared or the compiler optimized it away because it is unused.
+    NotFound(String),
+    /// The name contains a nul byte and can't be handed to GL.
+    InvalidName(String),
+}
+
+impl fmt::Display for UniformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniformError::NotFound(name) => write!(f, "`{}` is not an active uniform", name),
+            UniformError::InvalidName(name) => write!(f, "`{}` is not a valid uniform name", name),
+        }
+    }
+}
+
+impl Error for UniformError {}
+
+/// Values that can be uploaded to a uniform location of the program in use.
+pub trait Uniform {
+    fn set(&self, location: i32);
+}
+
+/// Uniform types that can also be uploaded as arrays, `uniform vec3 lights[4]` and alike.
+pub trait UniformElement: Sized {
+    fn set_slice(values: &[Self], location: i32);
+}
+
+fn count<T>(values: &[T]) -> i32 {
+    values.len().try_into().unwrap()
+}
+
+impl UniformElement for f32 {
+    fn set_slice(values: &[Self], location: i32) {
+        unsafe { glUniform1fv(location, count(values), values.as_ptr()) };
+    }
+}
+
+impl UniformElement for i32 {
+    fn set_slice(values: &[Self], location: i32) {
+        unsafe { glUniform1iv(location, count(values), values.as_ptr()) };
+    }
+}
+
+impl UniformElement for bool {
+    fn set_slice(values: &[Self], location: i32) {
+        let values: Vec<i32> = values.iter().map(|&value| value as i32).collect();
+        i32::set_slice(&values, location);
+    }
+}
+
+impl UniformElement for Vec2 {
+    fn set_slice(values: &[Self], location: i32) {
+        let floats: &[f32] = bytemuck::cast_slice(values);
+        unsafe { glUniform2fv(location, count(values), floats.as_ptr()) };
+    }
+}
+
+impl UniformElement for Vec3 {
+    fn set_slice(values: &[Self], location: i32) {
+        let floats: &[f32] = bytemuck::cast_slice(values);
+        unsafe { glUniform3fv(location, count(values), floats.as_ptr()) };
+    }
+}
+
+impl UniformElement for Vec4 {
+    fn set_slice(values: &[Self], location: i32) {
+        let floats: &[f32] = bytemuck::cast_slice(values);
+        unsafe { glUniform4fv(location, count(values), floats.as_ptr()) };
+    }
+}
+
+impl UniformElement for Mat3 {
+    fn set_slice(values: &[Self], location: i32) {
+        // glam matrices are column major, just like GL expects them, so no transpose.
+        let floats: &[f32] = bytemuck::cast_slice(values);
+        unsafe { glUniformMatrix3fv(location, count(values), 0, floats.as_ptr()) };
+    }
+}
+
+impl UniformElement for Mat4 {
+    fn set_slice(values: &[Self], location: i32) {
+        let floats: &[f32] = bytemuck::cast_slice(values);
+        unsafe { glUniformMatrix4fv(location, count(values), 0, floats.as_ptr()) };
+    }
+}
+
+impl Uniform for f32 {
+    fn set(&self, location: i32) {
+        unsafe { glUniform1f(location, *self) };
+    }
+}
+
+impl Uniform for i32 {
+    fn set(&self, location: i32) {
+        unsafe { glUniform1i(location, *self) };
+    }
+}
+
+impl Uniform for bool {
+    fn set(&self, location: i32) {
+        unsafe { glUniform1i(location, *self as i32) };
+    }
+}
+
+macro_rules! impl_uniform {
+    ($($ty:ty),*) => {
+        $(
+            impl Uniform for $ty {
+                fn set(&self, location: i32) {
+                    <$ty>::set_slice(std::slice::from_ref(self), location);
+                }
+            }
+        )*
+    };
+}
+
+impl_uniform!(Vec2, Vec3, Vec4, Mat3, Mat4);
+
+impl<T: UniformElement, const N: usize> Uniform for [T; N] {
+    fn set(&self, location: i32) {
+        T::set_slice(self, location);
+    }
+}
+
+impl<T: UniformElement> Uniform for &[T] {
+    fn set(&self, location: i32) {
+        T::set_slice(self, location);
+    }
+}
+
+impl<T: UniformElement> Uniform for Vec<T> {
+    fn set(&self, location: i32) {
+        T::set_slice(self, location);
+    }
+}
+
+impl ShaderProgram<'_> {
+    /// Looks up the location of a uniform, asking GL only the first time per name.
+    pub fn uniform_location(&self, name: &str) -> Result<i32, UniformError> {
+        if let Some(&location) = self.uniform_locations.borrow().get(name) {
+            return Self::found(name, location);
+        }
+
+        let c_name = CString::new(name).map_err(|_| UniformError::InvalidName(name.to_string()))?;
+        let location = unsafe { glGetUniformLocation(self.id, c_name.as_ptr().cast()) };
+        self.uniform_locations
+            .borrow_mut()
+            .insert(name.to_string(), location);
+
+        Self::found(name, location)
+    }
+
+    fn found(name: &str, location: i32) -> Result<i32, UniformError> {
+        if location >= 0 {
+            Ok(location)
+        } else {
+            Err(UniformError::NotFound(name.to_string()))
+        }
+    }
+
+    /// Sets a uniform of this program, which has to be the program in use.
+    pub fn try_set_uniform(&self, name: &str, value: impl Uniform) -> Result<(), UniformError> {
+        let location = self.uniform_location(name)?;
+        value.set(location);
+        Ok(())
+    }
+
+    /// Sets a uniform of this program, which has to be the program in use.
+    ///
+    /// A uniform that is not active is skipped with a warning, printed the
+    /// first time the name is looked up. Use
+    /// [`ShaderProgram::try_set_uniform`](ShaderProgram::try_set_uniform) to
+    /// handle that case yourself.
+    pub fn set_uniform(&self, name: &str, value: impl Uniform) {
+        let first_lookup = !self.uniform_locations.borrow().contains_key(name);
+
+        if let Err(e) = self.try_set_uniform(name, value) {
+            if first_lookup {
+                eprintln!("Warning: program {}: {}", self.id, e);
+            }
+        }
+    }
+
+    /// Forgets every cached uniform location, needed after the program is relinked.
+    pub fn clear_uniform_cache(&self) {
+        self.uniform_locations.borrow_mut().clear();
+    }
+}
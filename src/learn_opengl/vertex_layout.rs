@@ -0,0 +1,301 @@
+use std::mem;
+
+use gl33::{
+    global_loader::{
+        glDisableVertexAttribArray, glEnableVertexAttribArray, glVertexAttribDivisor,
+        glVertexAttribIPointer, glVertexAttribPointer,
+    },
+    GLenum, GL_BYTE, GL_FLOAT, GL_INT, GL_SHORT, GL_UNSIGNED_BYTE, GL_UNSIGNED_INT,
+    GL_UNSIGNED_SHORT,
+};
+
+use super::{Buffer, BufferType, VertexArray};
+
+/// The element type of a vertex attribute as it is stored in the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    F32,
+    I32,
+    U32,
+    I16,
+    U16,
+    I8,
+    U8,
+}
+
+impl AttributeType {
+    /// Size of a single component in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            AttributeType::F32 | AttributeType::I32 | AttributeType::U32 => 4,
+            AttributeType::I16 | AttributeType::U16 => 2,
+            AttributeType::I8 | AttributeType::U8 => 1,
+        }
+    }
+}
+
+impl From<AttributeType> for GLenum {
+    fn from(attribute_type: AttributeType) -> Self {
+        match attribute_type {
+            AttributeType::F32 => GL_FLOAT,
+            AttributeType::I32 => GL_INT,
+            AttributeType::U32 => GL_UNSIGNED_INT,
+            AttributeType::I16 => GL_SHORT,
+            AttributeType::U16 => GL_UNSIGNED_SHORT,
+            AttributeType::I8 => GL_BYTE,
+            AttributeType::U8 => GL_UNSIGNED_BYTE,
+        }
+    }
+}
+
+/// Rust types that can be the field of a vertex.
+///
+/// Matrices take up one attribute location per column, so they report more than one slot.
+pub trait AttributeFormat {
+    /// Number of components per attribute slot, 1 to 4.
+    const COMPONENTS: i32;
+    const TYPE: AttributeType;
+    /// Number of consecutive attribute locations the field takes up.
+    const SLOTS: u32 = 1;
+}
+
+macro_rules! impl_attribute_format {
+    ($($ty:ty => $components:literal, $attribute_type:ident;)*) => {
+        $(
+            impl AttributeFormat for $ty {
+                const COMPONENTS: i32 = $components;
+                const TYPE: AttributeType = AttributeType::$attribute_type;
+            }
+        )*
+    };
+}
+
+macro_rules! impl_attribute_format_scalar {
+    ($($scalar:ty => $attribute_type:ident),*) => {
+        impl_attribute_format! {
+            $(
+                $scalar => 1, $attribute_type;
+                [$scalar; 1] => 1, $attribute_type;
+                [$scalar; 2] => 2, $attribute_type;
+                [$scalar; 3] => 3, $attribute_type;
+                [$scalar; 4] => 4, $attribute_type;
+            )*
+        }
+    };
+}
+
+impl_attribute_format_scalar!(f32 => F32, i32 => I32, u32 => U32, i16 => I16, u16 => U16, i8 => I8, u8 => U8);
+
+impl_attribute_format! {
+    glam::Vec2 => 2, F32;
+    glam::Vec3 => 3, F32;
+    glam::Vec4 => 4, F32;
+    glam::IVec2 => 2, I32;
+    glam::IVec3 => 3, I32;
+    glam::IVec4 => 4, I32;
+    glam::UVec2 => 2, U32;
+    glam::UVec3 => 3, U32;
+    glam::UVec4 => 4, U32;
+}
+
+impl AttributeFormat for glam::Mat3 {
+    const COMPONENTS: i32 = 3;
+    const TYPE: AttributeType = AttributeType::F32;
+    const SLOTS: u32 = 3;
+}
+
+impl AttributeFormat for glam::Mat4 {
+    const COMPONENTS: i32 = 4;
+    const TYPE: AttributeType = AttributeType::F32;
+    const SLOTS: u32 = 4;
+}
+
+/// Description of a single field of a vertex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexAttribute {
+    /// The `layout (location = ...)` of the attribute in the shader.
+    pub location: u32,
+    /// Number of components per slot, 1 to 4.
+    pub components: i32,
+    pub attribute_type: AttributeType,
+    /// Whether integer data is mapped to `[0, 1]` (or `[-1, 1]`) floats.
+    pub normalized: bool,
+    /// Whether integer data stays integer in the shader (`ivec`/`uvec` inputs).
+    pub integer: bool,
+    /// Byte offset of the field within the vertex.
+    pub offset: usize,
+    /// Number of instances that share a value, 0 advances every vertex.
+    pub divisor: u32,
+    /// Number of consecutive locations the field takes up, more than 1 for matrices.
+    pub slots: u32,
+}
+
+impl VertexAttribute {
+    /// Describes a field of type `T` at the given location and byte offset.
+    pub const fn of<T: AttributeFormat>(location: u32, offset: usize) -> Self {
+        Self {
+            location,
+            components: T::COMPONENTS,
+            attribute_type: T::TYPE,
+            normalized: false,
+            integer: false,
+            offset,
+            divisor: 0,
+            slots: T::SLOTS,
+        }
+    }
+
+    /// Maps integer data to floats in `[0, 1]` or `[-1, 1]`.
+    pub const fn normalized(self) -> Self {
+        Self {
+            normalized: true,
+            ..self
+        }
+    }
+
+    /// Keeps integer data as integers in the shader.
+    pub const fn integer(self) -> Self {
+        Self {
+            integer: true,
+            ..self
+        }
+    }
+
+    /// Advances the attribute once every `divisor` instances instead of every vertex.
+    pub const fn divisor(self, divisor: u32) -> Self {
+        Self { divisor, ..self }
+    }
+
+    /// Size of one slot of the attribute in bytes.
+    pub const fn slot_size(&self) -> usize {
+        self.components as usize * self.attribute_type.size()
+    }
+}
+
+/// Vertex types that know how their fields map to shader attributes.
+///
+/// Implement it with the [`vertex_layout!`](crate::vertex_layout) macro, which
+/// computes the offsets and formats from the struct definition itself.
+pub trait VertexLayout: bytemuck::Pod {
+    /// One entry per field that is fed to the shader.
+    const ATTRIBUTES: &'static [VertexAttribute];
+
+    /// Size of a whole vertex in bytes.
+    fn stride() -> i32 {
+        mem::size_of::<Self>().try_into().unwrap()
+    }
+}
+
+impl VertexArray<'_> {
+    /// Binds this vertex array and the buffer, then points every attribute of
+    /// `V` at the buffer and enables it.
+    ///
+    /// The vertex array stays bound afterwards.
+    pub fn apply_layout<V: VertexLayout>(&self, buffer: &Buffer) {
+        self.bind();
+        buffer.bind(BufferType::Array);
+
+        let stride = V::stride();
+        for attribute in V::ATTRIBUTES {
+            for slot in 0..attribute.slots {
+                let location = attribute.location + slot;
+                let offset = attribute.offset + slot as usize * attribute.slot_size();
+
+                unsafe {
+                    if attribute.integer {
+                        glVertexAttribIPointer(
+                            location,
+                            attribute.components,
+                            attribute.attribute_type.into(),
+                            stride,
+                            offset as *const _,
+                        );
+                    } else {
+                        glVertexAttribPointer(
+                            location,
+                            attribute.components,
+                            attribute.attribute_type.into(),
+                            attribute.normalized as u8,
+                            stride,
+                            offset as *const _,
+                        );
+                    }
+                    glEnableVertexAttribArray(location);
+                    glVertexAttribDivisor(location, attribute.divisor);
+                }
+            }
+        }
+    }
+
+    /// Disables the attributes of `V` again, the shader then reads the current
+    /// value of each attribute, as set by `glVertexAttrib*`, instead of the buffer.
+    ///
+    /// The vertex array stays bound afterwards.
+    pub fn disable_layout<V: VertexLayout>(&self) {
+        self.bind();
+
+        for attribute in V::ATTRIBUTES {
+            for location in attribute.location..attribute.location + attribute.slots {
+                unsafe {
+                    glDisableVertexAttribArray(location);
+                    glVertexAttribDivisor(location, 0);
+                }
+            }
+        }
+    }
+}
+
+/// Defines a `#[repr(C)]` vertex struct and implements
+/// [`VertexLayout`](crate::learn_opengl::VertexLayout) for it.
+///
+/// Every field needs an `#[attrib(location = N)]` attribute, optionally followed
+/// by `normalized`, `integer` or `divisor = N`. The struct still needs to derive
+/// `bytemuck::Pod` and `bytemuck::Zeroable` itself.
+///
+/// ```ignore
+/// vertex_layout! {
+///     #[derive(Debug, Clone, Copy, Pod, Zeroable)]
+///     pub struct Vertex {
+///         #[attrib(location = 0)]
+///         pub position: Vec3,
+///         #[attrib(location = 1)]
+///         pub tex_coord: Vec2,
+///         #[attrib(location = 2, normalized)]
+///         pub color: [u8; 4],
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! vertex_layout {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                #[attrib(location = $location:literal $(, $option:ident $(= $value:literal)?)*)]
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $crate::learn_opengl::VertexLayout for $name {
+            const ATTRIBUTES: &'static [$crate::learn_opengl::VertexAttribute] = &[
+                $(
+                    $crate::learn_opengl::VertexAttribute::of::<$ty>(
+                        $location,
+                        ::std::mem::offset_of!($name, $field),
+                    )
+                    $(.$option($($value)?))*
+                ),*
+            ];
+        }
+    };
+}
@@ -1 +1,10 @@
+pub mod camera;
+pub mod capture;
+pub mod clock;
+pub mod geometry;
+pub mod golden;
+pub mod input;
 pub mod learn_opengl;
+pub mod lighting;
+pub mod platform;
+pub mod scene;
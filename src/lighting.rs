@@ -0,0 +1,374 @@
+use std::{error::Error, fmt};
+
+use glam::Vec3;
+
+use crate::learn_opengl::{Preprocessor, ShaderFileSystem, ShaderProgram, Texture2D, Uniform};
+
+/// How many lights of each type the shaders have room for.
+///
+/// The counts become the `MAX_DIRECTIONAL_LIGHTS`, `MAX_POINT_LIGHTS` and
+/// `MAX_SPOT_LIGHTS` defines of `lighting.glsl`, so programs have to be built
+/// with the same limits the lights are uploaded with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LightLimits {
+    pub directional: usize,
+    pub point: usize,
+    pub spot: usize,
+}
+
+impl Default for LightLimits {
+    fn default() -> Self {
+        Self {
+            directional: 1,
+            point: 4,
+            spot: 2,
+        }
+    }
+}
+
+impl LightLimits {
+    /// Adds the defines for the limits to a preprocessor.
+    ///
+    /// GLSL has no empty arrays, so a limit of 0 still makes room for one light
+    /// in the shader. No lights of that type are uploaded either way.
+    pub fn define<F: ShaderFileSystem>(&self, preprocessor: Preprocessor<F>) -> Preprocessor<F> {
+        preprocessor
+            .define(
+                "MAX_DIRECTIONAL_LIGHTS",
+                self.directional.max(1).to_string(),
+            )
+            .define("MAX_POINT_LIGHTS", self.point.max(1).to_string())
+            .define("MAX_SPOT_LIGHTS", self.spot.max(1).to_string())
+    }
+}
+
+/// The colors a light contributes to the ambient, diffuse and specular terms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightColor {
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+}
+
+impl LightColor {
+    /// A light of the given color with a dim ambient term.
+    pub fn new(color: Vec3) -> Self {
+        Self {
+            ambient: color * 0.1,
+            diffuse: color,
+            specular: color,
+        }
+    }
+}
+
+/// How light fades with the distance `d`: `1 / (constant + linear * d +
+/// quadratic * d²)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for Attenuation {
+    /// Reaches about 50 units.
+    fn default() -> Self {
+        Self {
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+impl Attenuation {
+    /// The fraction of the light left at `distance`.
+    pub fn at(&self, distance: f32) -> f32 {
+        1.0 / (self.constant + self.linear * distance + self.quadratic * distance * distance)
+    }
+}
+
+/// Light from infinitely far away, like the sun.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionalLight {
+    /// The direction the light travels in.
+    pub direction: Vec3,
+    pub color: LightColor,
+}
+
+/// Light shining in all directions from a point, fading with distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: LightColor,
+    pub attenuation: Attenuation,
+}
+
+/// Light shining in a cone, like a flashlight. It is at full strength inside
+/// the inner cone and fades out towards the outer one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotLight {
+    pub position: Vec3,
+    /// The direction the cone points in.
+    pub direction: Vec3,
+    /// Angle between the direction and the edge of the inner cone, in radians.
+    pub inner_cutoff: f32,
+    /// Angle between the direction and the edge of the outer cone, in radians.
+    pub outer_cutoff: f32,
+    pub color: LightColor,
+    pub attenuation: Attenuation,
+}
+
+/// Which specular term the lighting include computes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShadingModel {
+    /// Reflects the light direction and compares it with the view direction.
+    Phong,
+    /// Compares the normal with the halfway vector between the light and view
+    /// directions, which keeps highlights from cutting off at grazing angles.
+    #[default]
+    BlinnPhong,
+}
+
+/// A surface as the lighting include sees it. The maps, when there are any,
+/// are multiplied with the colors.
+#[derive(Clone, Copy)]
+pub struct Material<'t, 'gl> {
+    pub diffuse_map: Option<&'t Texture2D<'gl>>,
+    pub specular_map: Option<&'t Texture2D<'gl>>,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    /// The specular exponent, higher is shinier with a smaller highlight.
+    pub shininess: f32,
+}
+
+impl<'t, 'gl> Material<'t, 'gl> {
+    /// A white material without maps.
+    pub fn new(shininess: f32) -> Self {
+        Self {
+            diffuse_map: None,
+            specular_map: None,
+            diffuse: Vec3::ONE,
+            specular: Vec3::ONE,
+            shininess,
+        }
+    }
+
+    /// Binds the maps to texture units `first_unit` and `first_unit + 1` and
+    /// sets the `material` uniform of the program in use.
+    pub fn upload(&self, program: &ShaderProgram<'_>, first_unit: u32) {
+        let maps = [
+            ("diffuse", self.diffuse_map, first_unit),
+            ("specular", self.specular_map, first_unit + 1),
+        ];
+        for (name, map, unit) in maps {
+            if let Some(texture) = map {
+                texture.bind_to_unit(unit);
+            }
+            set(program, &format!("material.{}_map", name), unit as i32);
+            set(
+                program,
+                &format!("material.has_{}_map", name),
+                map.is_some(),
+            );
+        }
+        set(program, "material.diffuse", self.diffuse);
+        set(program, "material.specular", self.specular);
+        set(program, "material.shininess", self.shininess);
+    }
+}
+
+/// More lights of one type than the shaders have room for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyLights {
+    pub kind: &'static str,
+    pub count: usize,
+    pub max: usize,
+}
+
+impl fmt::Display for TooManyLights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} lights, but there is only room for {}",
+            self.count, self.kind, self.max
+        )
+    }
+}
+
+impl Error for TooManyLights {}
+
+/// Every light in the scene, uploaded to the uniform arrays of `lighting.glsl`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lights {
+    pub directional: Vec<DirectionalLight>,
+    pub point: Vec<PointLight>,
+    pub spot: Vec<SpotLight>,
+    pub shading: ShadingModel,
+}
+
+impl Lights {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the light uniforms of the program in use, which has to be built
+    /// with `limits`. Nothing is uploaded when there are too many lights.
+    ///
+    /// Uniforms the program does not use are skipped.
+    pub fn upload(
+        &self,
+        program: &ShaderProgram<'_>,
+        limits: &LightLimits,
+    ) -> Result<(), TooManyLights> {
+        for (name, value) in self.uniforms(limits)? {
+            set(program, &name, value);
+        }
+        Ok(())
+    }
+
+    /// The uniforms [`upload`](Lights::upload) sets, by name.
+    pub fn uniforms(
+        &self,
+        limits: &LightLimits,
+    ) -> Result<Vec<(String, LightUniform)>, TooManyLights> {
+        let counts = [
+            ("directional", self.directional.len(), limits.directional),
+            ("point", self.point.len(), limits.point),
+            ("spot", self.spot.len(), limits.spot),
+        ];
+        for (kind, count, max) in counts {
+            if count > max {
+                return Err(TooManyLights { kind, count, max });
+            }
+        }
+
+        let mut uniforms = Uniforms::default();
+        uniforms.push(
+            "blinn_phong",
+            LightUniform::Bool(self.shading == ShadingModel::BlinnPhong),
+        );
+
+        uniforms.push(
+            "directional_light_count",
+            LightUniform::Int(self.directional.len() as i32),
+        );
+        for (i, light) in self.directional.iter().enumerate() {
+            let name = format!("directional_lights[{}]", i);
+            uniforms.push(
+                format!("{}.direction", name),
+                LightUniform::Vec3(light.direction.normalize()),
+            );
+            uniforms.color(&name, &light.color);
+        }
+
+        uniforms.push(
+            "point_light_count",
+            LightUniform::Int(self.point.len() as i32),
+        );
+        for (i, light) in self.point.iter().enumerate() {
+            let name = format!("point_lights[{}]", i);
+            uniforms.push(
+                format!("{}.position", name),
+                LightUniform::Vec3(light.position),
+            );
+            uniforms.color(&name, &light.color);
+            uniforms.attenuation(&name, &light.attenuation);
+        }
+
+        uniforms.push(
+            "spot_light_count",
+            LightUniform::Int(self.spot.len() as i32),
+        );
+        for (i, light) in self.spot.iter().enumerate() {
+            let name = format!("spot_lights[{}]", i);
+            uniforms.push(
+                format!("{}.position", name),
+                LightUniform::Vec3(light.position),
+            );
+            uniforms.push(
+                format!("{}.direction", name),
+                LightUniform::Vec3(light.direction.normalize()),
+            );
+            // The shader compares cosines, which saves it an acos per fragment
+            uniforms.push(
+                format!("{}.inner_cutoff", name),
+                LightUniform::Float(light.inner_cutoff.cos()),
+            );
+            uniforms.push(
+                format!("{}.outer_cutoff", name),
+                LightUniform::Float(light.outer_cutoff.cos()),
+            );
+            uniforms.color(&name, &light.color);
+            uniforms.attenuation(&name, &light.attenuation);
+        }
+
+        Ok(uniforms.0)
+    }
+}
+
+/// The value of a light uniform.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightUniform {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Vec3(Vec3),
+}
+
+impl Uniform for LightUniform {
+    fn set(&self, location: i32) {
+        match self {
+            LightUniform::Bool(value) => value.set(location),
+            LightUniform::Int(value) => value.set(location),
+            LightUniform::Float(value) => value.set(location),
+            LightUniform::Vec3(value) => value.set(location),
+        }
+    }
+}
+
+/// The list [`Lights::uniforms`] is built up in.
+#[derive(Default)]
+struct Uniforms(Vec<(String, LightUniform)>);
+
+impl Uniforms {
+    fn push(&mut self, name: impl Into<String>, value: LightUniform) {
+        self.0.push((name.into(), value));
+    }
+
+    fn color(&mut self, light: &str, color: &LightColor) {
+        self.push(
+            format!("{}.ambient", light),
+            LightUniform::Vec3(color.ambient),
+        );
+        self.push(
+            format!("{}.diffuse", light),
+            LightUniform::Vec3(color.diffuse),
+        );
+        self.push(
+            format!("{}.specular", light),
+            LightUniform::Vec3(color.specular),
+        );
+    }
+
+    fn attenuation(&mut self, light: &str, attenuation: &Attenuation) {
+        self.push(
+            format!("{}.constant", light),
+            LightUniform::Float(attenuation.constant),
+        );
+        self.push(
+            format!("{}.linear", light),
+            LightUniform::Float(attenuation.linear),
+        );
+        self.push(
+            format!("{}.quadratic", light),
+            LightUniform::Float(attenuation.quadratic),
+        );
+    }
+}
+
+/// Sets a uniform if the program uses it. Unused array elements and fields
+/// are optimized away, so missing ones are expected.
+fn set(program: &ShaderProgram<'_>, name: &str, value: impl Uniform) {
+    let _ = program.try_set_uniform(name, value);
+}
@@ -1,258 +1,212 @@
 // Make it windows and not console app. Doesnt open the terminal
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use beryllium::{events::{SDLK_a, SDLK_d, SDLK_s, SDLK_w, SDLK_6, SDLK_UP}, *};
+use beryllium::*;
+use fermium::joystick::SDL_JoystickGetDeviceInstanceID;
 use gl33::{
     global_loader::{
-        glActiveTexture, glAttachShader, glBindBuffer, glBindTexture, glBindVertexArray, glBufferData, glClear, glClearColor, glCompileShader, glCreateProgram, glCreateShader, glDeleteBuffers, glDeleteProgram, glDeleteShader, glDeleteVertexArrays, glDisableVertexAttribArray, glDrawArrays, glDrawElements, glEnable, glEnableVertexAttribArray, glGenBuffers, glGenTextures, glGenVertexArrays, glGenerateMipmap, glGetIntegerv, glGetProgramInfoLog, glGetProgramiv, glGetShaderInfoLog, glGetShaderiv, glGetUniformLocation, glLinkProgram, glShaderSource, glTexImage2D, glTexParameteri, glUniform1i, glUniform4f, glUniformMatrix4fv, glUseProgram, glVertexAttribPointer, load_global_gl
+        glClear, glClearColor, glEnable, glGetIntegerv, glVertexAttrib4fv
     },
     *,
 };
-use glam::vec4;
 
 use std::{
-    f32::consts::PI, ffi::CString, mem, time::SystemTime
+    f32::consts::PI,
+    time::{Duration, Instant},
 };
 
 use image::ImageReader;
+use opengl_chrno::{
+    camera::{Camera, CameraMovement, FlyCamera, OrbitCamera},
+    capture::Capture,
+    clock::FrameClock,
+    geometry::{primitives, Instance, Vertex},
+    input::{ActionMap, GamepadAxis, GamepadButton, Input, InputEvent, MouseButton, Scancode},
+    lighting::{
+        Attenuation, DirectionalLight, LightColor, LightLimits, Lights, Material, PointLight,
+        ShadingModel, SpotLight,
+    },
+    learn_opengl::{
+        DirectoryFileSystem, InstanceBuffer, Preprocessor, ReloadableProgram,
+        ShaderType, Texture2D, TextureFilter, TextureWrap,
+    },
+    platform::{Platform, Surface},
+    scene::{SceneGraph, Transform},
+};
 
-#[rustfmt::skip]
-fn get_vertices() -> [f32; 180] {
-    [ // I coppied the data from learnopengl, 1.5 -> 1.0 and 0.5 to 0.0 but i am too lazy
-    -0.5, -0.5, -0.5,  0.0, 0.0,
-     0.5, -0.5, -0.5,  1.0, 0.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-    -0.5,  0.5, -0.5,  0.0, 1.0,
-    -0.5, -0.5, -0.5,  0.0, 0.0,
-
-    -0.5, -0.5,  0.5,  0.5, 0.5,
-     0.5, -0.5,  0.5,  1.5, 0.5,
-     0.5,  0.5,  0.5,  1.5, 1.5,
-     0.5,  0.5,  0.5,  1.5, 1.5,
-    -0.5,  0.5,  0.5,  0.5, 1.5,
-    -0.5, -0.5,  0.5,  0.5, 0.5,
-
-    -0.5,  0.5,  0.5,  1.5, 0.5,
-    -0.5,  0.5, -0.5,  1.5, 1.5,
-    -0.5, -0.5, -0.5,  0.5, 1.5,
-    -0.5, -0.5, -0.5,  0.5, 1.5,
-    -0.5, -0.5,  0.5,  0.5, 0.5,
-    -0.5,  0.5,  0.5,  1.5, 0.5,
-
-     0.5,  0.5,  0.5,  1.5, 0.5,
-     0.5,  0.5, -0.5,  1.5, 1.5,
-     0.5, -0.5, -0.5,  0.5, 1.5,
-     0.5, -0.5, -0.5,  0.5, 1.5,
-     0.5, -0.5,  0.5,  0.5, 0.5,
-     0.5,  0.5,  0.5,  1.5, 0.5,
-
-    -0.5, -0.5, -0.5,  0.5, 1.5,
-     0.5, -0.5, -0.5,  1.5, 1.5,
-     0.5, -0.5,  0.5,  1.5, 0.5,
-     0.5, -0.5,  0.5,  1.5, 0.5,
-    -0.5, -0.5,  0.5,  0.5, 0.5,
-    -0.5, -0.5, -0.5,  0.5, 1.5,
-
-    -0.5,  0.5, -0.5,  0.5, 1.5,
-     0.5,  0.5, -0.5,  1.5, 1.5,
-     0.5,  0.5,  0.5,  1.5, 0.5,
-     0.5,  0.5,  0.5,  1.5, 0.5,
-    -0.5,  0.5,  0.5,  0.5, 0.5,
-    -0.5,  0.5, -0.5,  0.5, 1.5
-    ]
+/// Translates the SDL events the input system cares about.
+fn input_event(event: &events::Event) -> Option<InputEvent> {
+    match *event {
+        events::Event::Key { pressed, repeat, scancode, .. } => Some(InputEvent::Key {
+            scancode: Scancode(scancode.0),
+            pressed,
+            repeat: repeat != 0,
+        }),
+        events::Event::MouseButton { button, pressed, .. } => Some(InputEvent::MouseButton {
+            button: MouseButton::from_sdl(button)?,
+            pressed,
+        }),
+        events::Event::MouseMotion { x_delta, y_delta, .. } => Some(InputEvent::MouseMotion {
+            x: x_delta as f32,
+            y: y_delta as f32,
+        }),
+        events::Event::MouseWheel { x, y, .. } => Some(InputEvent::MouseWheel {
+            x: x as f32,
+            y: y as f32,
+        }),
+        // Added controllers have to be opened first, see the event loop
+        events::Event::ControllerRemoved { ctrl_id } => {
+            Some(InputEvent::GamepadRemoved { id: ctrl_id })
+        }
+        // The enums are numbered like SDL's, `Invalid` is -1 and maps to nothing
+        events::Event::ControllerButton { ctrl_id, button, pressed } => {
+            Some(InputEvent::GamepadButton {
+                id: ctrl_id,
+                button: GamepadButton::from_sdl(u8::try_from(button as i32).ok()?)?,
+                pressed,
+            })
+        }
+        events::Event::ControllerAxis { ctrl_id, axis, value } => {
+            Some(InputEvent::GamepadAxis {
+                id: ctrl_id,
+                axis: GamepadAxis::from_sdl(u8::try_from(axis as i32).ok()?)?,
+                value,
+            })
+        }
+        _ => None,
+    }
 }
 
-fn main() {
-    // Specify you will be using open GL before creating the window
-    let sdl = Sdl::init(init::InitFlags::EVERYTHING);
-
-    sdl.set_gl_context_major_version(3).unwrap();
-    sdl.set_gl_context_minor_version(3).unwrap();
-    // Core is a subset of all the features the OpenGL provides
-    sdl.set_gl_profile(video::GlProfile::Core).unwrap();
-    sdl.set_relative_mouse_mode(true).unwrap();
-    #[cfg(target_os = "macos")]
-    {
-        // For Mac OS -> FC basically makes all deperecated but available functions unavailable
-        // Necessary for Mac OS to use Core feature set
-        sdl.set_gl_context_flags(video::GlContextFlags::FORWARD_COMPATIBLE)
-            .unwrap();
-    }
+/// Named axes that move the camera, with the directions for negative and positive values.
+const MOVEMENT_AXES: [(&str, CameraMovement, CameraMovement); 3] = [
+    ("move_forward", CameraMovement::Backward, CameraMovement::Forward),
+    ("move_right", CameraMovement::Left, CameraMovement::Right),
+    ("move_up", CameraMovement::Down, CameraMovement::Up),
+];
+
+/// What the floor of cubes below the scene is drawn with, cycled by `cycle_crowd`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Crowd {
+    Off,
+    Instanced,
+    PerCube,
+}
 
-    let win_args = video::CreateWinArgs {
-        title: "LEARN OPENGL",
-        width: 800,
-        height: 600,
-        allow_high_dpi: true,
-        borderless: false,
-        resizable: false,
-    };
+impl Crowd {
+    fn next(self) -> Self {
+        match self {
+            Crowd::Off => Crowd::Instanced,
+            Crowd::Instanced => Crowd::PerCube,
+            Crowd::PerCube => Crowd::Off,
+        }
+    }
+}
 
-    // Beryllium sticks the window and GL context together as a single thing
-    // I assume it also does the glViewport, which sets the data for the NDC -> screen-space coord.
-    let win = sdl
-        .create_gl_window(win_args)
-        .expect("Could not make a window and context.");
-    
-    // Load up every OpenGL function
+/// Feeds one instance to the instanced program without an instance buffer,
+/// through the current values of its attributes at locations 4 to 8.
+fn set_instance_attributes(instance: &Instance) {
+    let columns = instance.model.to_cols_array_2d();
     unsafe {
-        load_global_gl(&|f_name| win.get_proc_address(f_name));
+        for (location, column) in (4..).zip(&columns) {
+            glVertexAttrib4fv(location, column);
+        }
+        glVertexAttrib4fv(8, instance.color.as_ref());
     }
+}
 
-    unsafe { glClearColor(0.2, 0.3, 0.3, 1.0) };
+/// How the cubes of the scene are shaded, cycled by `cycle_lighting`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lighting {
+    /// The two textures mixed, without any lights.
+    Off,
+    Shaded(ShadingModel),
+}
 
-    // VERTEX ARRAY OBJECT
+impl Lighting {
+    fn next(self) -> Self {
+        match self {
+            Lighting::Off => Lighting::Shaded(ShadingModel::Phong),
+            Lighting::Shaded(ShadingModel::Phong) => Lighting::Shaded(ShadingModel::BlinnPhong),
+            Lighting::Shaded(ShadingModel::BlinnPhong) => Lighting::Off,
+        }
+    }
+}
 
-    let mut vao = 0u32;
-    unsafe {
-        glGenVertexArrays(1, &mut vao);
+/// Recordings advance exactly this many frames per second of game time.
+const RECORDING_FRAME_RATE: f32 = 30.0;
+
+/// The crowd is a square of this many cubes a side.
+const CROWD_SIDE: usize = 100;
+
+/// How many pixels of mouse motion a fully pushed look stick is worth per second.
+const STICK_LOOK_SPEED: f32 = 600.0;
+
+fn main() {
+    // --headless renders offscreen, which also happens when there is no display.
+    // --frames N quits after N frames, so a headless run ends on its own
+    let mut surface = Surface::detect();
+    let mut frame_limit = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => surface = Surface::Headless,
+            "--frames" => {
+                frame_limit = args.next().and_then(|frames| frames.parse::<u64>().ok());
+                if frame_limit.is_none() {
+                    eprintln!("--frames needs a number of frames");
+                }
+            }
+            _ => eprintln!("Unknown argument {}", arg),
+        }
     }
-    assert!(vao != 0);
-    glBindVertexArray(vao);
 
-    let vertices = get_vertices();
+    // SDL sticks the window and GL context together as a single thing, and
+    // every OpenGL function is loaded for it
+    let platform = Platform::new("LEARN OPENGL", 800, 600, surface)
+        .unwrap_or_else(|e| panic!("{}", e));
+    platform.grab_mouse(true);
+    let gl = platform.gl();
+
+    unsafe { glClearColor(0.2, 0.3, 0.3, 1.0) };
 
     // Load texture image
-    let wooden_crate_texture = ImageReader::open("./assets/wall.jpg")
+    let wooden_crate_image = ImageReader::open("./assets/wall.jpg")
         .expect("Could not open image")
         .decode()
         .expect("Could not decode the image");
-    let (wooden_width, wooden_height) =
-        (wooden_crate_texture.width(), wooden_crate_texture.height());
-    let wooden_crate_texture = wooden_crate_texture.as_bytes();
 
-    let face_texture = ImageReader::open("./assets/awesomeface.png")
+    let face_image = ImageReader::open("./assets/awesomeface.png")
         .expect("Could not open image")
         .decode()
         .expect("Could not decode the image");
-    let (face_width, face_height) = (face_texture.width(), face_texture.height());
-    let face_texture = face_texture.as_bytes();
 
     // TEXTURE GENERATION
-    let mut texture_wooden_crate = 0u32;
-    unsafe { glGenTextures(1, &mut texture_wooden_crate) };
-    assert!(texture_wooden_crate != 0);
-    unsafe { glBindTexture(GL_TEXTURE_2D, texture_wooden_crate) };
-
-    unsafe { glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_REPEAT.0 as i32) };
-    unsafe { glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_REPEAT.0 as i32) };
-    unsafe {
-        glTexParameteri(
-            GL_TEXTURE_2D,
-            GL_TEXTURE_MIN_FILTER,
-            GL_LINEAR_MIPMAP_LINEAR.0 as i32,
-        )
-    }
-    unsafe { glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR.0 as i32) }
-
-    unsafe {
-        glTexImage2D(
-            GL_TEXTURE_2D,
-            0,
-            GL_RGB.0 as i32, // format in the opengl/gpu
-            wooden_width as i32,
-            wooden_height as i32,
-            0,
-            GL_RGB, // original format, so our byte slice
-            GL_UNSIGNED_BYTE,
-            wooden_crate_texture.as_ptr().cast(),
-        );
-    }
-    unsafe {
-        glGenerateMipmap(GL_TEXTURE_2D);
-    }
-    unsafe {
-        glBindTexture(GL_TEXTURE_2D, 0);
-    }
-
-    // Face texture
-    let mut texture_face = 0u32;
-    unsafe {
-        glGenTextures(1, &mut texture_face);
-    }
-    assert!(texture_face != 0);
-    unsafe {
-        glBindTexture(GL_TEXTURE_2D, texture_face);
-    }
-
-    unsafe {
-        glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_REPEAT.0 as i32);
-        glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_REPEAT.0 as i32);
-        glTexParameteri(
-            GL_TEXTURE_2D,
-            GL_TEXTURE_MIN_FILTER,
-            GL_LINEAR_MIPMAP_LINEAR.0 as i32,
-        );
-        glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR.0 as i32);
-    }
-
-    unsafe {
-        glTexImage2D(
-            GL_TEXTURE_2D,
-            0,
-            GL_RGB.0 as i32,
-            face_width as i32,
-            face_height as i32,
-            0,
-            GL_RGBA,
-            GL_UNSIGNED_BYTE,
-            face_texture.as_ptr().cast(),
-        );
-        glGenerateMipmap(GL_TEXTURE_2D);
-
-        glBindTexture(GL_TEXTURE_2D, 0);
-    }
-
-    // VERTEX BUFFER OBJECT
-
-    let mut vbo = 0u32;
-    unsafe {
-        glGenBuffers(1, &mut vbo);
-    }
-    assert!(vbo != 0);
-    unsafe { glBindBuffer(GL_ARRAY_BUFFER, vbo) };
-    unsafe {
-        glBufferData(
-            GL_ARRAY_BUFFER,
-            (vertices.len() * mem::size_of::<f32>()).try_into().unwrap(),
-            vertices.as_ptr().cast(),
-            GL_STATIC_DRAW,
-        )
-    };
-
-    unsafe {
-        glVertexAttribPointer(
-            0,        // Has to match the shader program later on
-            3,        // Number of components in the attribute
-            GL_FLOAT, // Element type of the data in the attribute
-            0,        // normalized
-            ((3 + 2) * mem::size_of::<f32>()).try_into().unwrap(), // Size in bytes of all the attributes, currently 3 * 4 bytes
-            0 as *const _, // Start of the vertext attribute within the buffer
-        );
-        glEnableVertexAttribArray(0);
-
-        glVertexAttribPointer(
-            1,
-            2,
-            GL_FLOAT,
-            0,
-            ((3 + 2) * mem::size_of::<f32>()).try_into().unwrap(),
-            (3 * mem::size_of::<f32>()) as *const _,
-        );
-        glEnableVertexAttribArray(1);
-    }
+    // The formats are picked from the color type, so the RGBA face does not get squashed into RGB
+    let texture_wooden_crate = Texture2D::builder()
+        .wrap(TextureWrap::Repeat)
+        .min_filter(TextureFilter::Linear)
+        .mag_filter(TextureFilter::Linear)
+        .mipmaps(true)
+        .build(gl, &wooden_crate_image)
+        .expect("Could not make the wooden crate texture");
+
+    let texture_face =
+        Texture2D::from_image(gl, &face_image).expect("Could not make the face texture");
+
+    let container_image = ImageReader::open("./assets/container.jpg")
+        .expect("Could not open image")
+        .decode()
+        .expect("Could not decode the image");
+    let texture_container = Texture2D::builder()
+        .mipmaps(true)
+        .build(gl, &container_image)
+        .expect("Could not make the container texture");
 
-    glBindVertexArray(0);
-    unsafe {
-        glDisableVertexAttribArray(0);
-    }
-    unsafe {
-        glDisableVertexAttribArray(1);
-    }
-    unsafe {
-        glBindBuffer(GL_ARRAY_BUFFER, 0);
-    }
+    // MESH
+    // The mesh owns its VAO, VBO and EBO, every field of the vertex becomes an attribute
+    let cube = primitives::cube(glam::Vec3::ONE, 1)
+        .upload(gl)
+        .expect("Could not make the cube mesh");
 
     // SHADERS
 
@@ -265,80 +219,53 @@ fn main() {
         max_attribute_number
     );
 
-    const VERT_SHADER: &str = r#"#version 330 core
-        layout (location = 0) in vec3 pos;
-        layout (location = 1) in vec2 textureCoord;
-
-        uniform mat4 model;
-        uniform mat4 view;
-        uniform mat4 projection;
-
-        out vec2 texCoord;
-
-        void main() {
-            //gl_Position = vec4(pos.x, pos.y, pos.z, 1.0);
-            gl_Position = projection * view * model * vec4(pos, 1.0);
-            texCoord = textureCoord;
+    // The shaders are read from disk and rebuilt whenever one of their files changes
+    let mut program = ReloadableProgram::new(
+        gl,
+        Preprocessor::new(DirectoryFileSystem::new("./assets/shaders")),
+        &[
+            (ShaderType::Vertex, "cube.vert"),
+            (ShaderType::Fragment, "cube.frag"),
+        ],
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+
+    // The same cube, but the model matrix and a tint come from the instance buffer
+    let mut instanced_program = ReloadableProgram::new(
+        gl,
+        Preprocessor::new(DirectoryFileSystem::new("./assets/shaders")),
+        &[
+            (ShaderType::Vertex, "cube_instanced.vert"),
+            (ShaderType::Fragment, "cube_instanced.frag"),
+        ],
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+
+    // The cubes again, lit by the lights below. The array sizes of the shared
+    // lighting include are defined to match the limits the lights are checked against
+    let light_limits = LightLimits::default();
+    let mut lit_program = ReloadableProgram::new(
+        gl,
+        light_limits.define(Preprocessor::new(DirectoryFileSystem::new("./assets/shaders"))),
+        &[
+            (ShaderType::Vertex, "lit.vert"),
+            (ShaderType::Fragment, "lit.frag"),
+        ],
+    )
+    .unwrap_or_else(|e| panic!("{}", e));
+
+    // Check that the vertex struct feeds everything the program reads
+    let interface = program.program().interface();
+    if let Err(mismatches) = interface.check_layout::<Vertex>() {
+        eprintln!("The program does not match the vertex layout:\n{}", interface);
+        for mismatch in mismatches {
+            eprintln!("Warning: {}", mismatch);
         }
-    "#;
-
-    const FRAG_SHADER: &str = r#"#version 330 core
-        out vec4 final_color;
-
-        //uniform vec4 ourColor;
-        // built in datatype for texture objects
-        uniform sampler2D texture1;
-        uniform sampler2D texture2;
-
-        in vec2 texCoord;
-
-        void main() {
-            final_color = mix(texture(texture1, texCoord), texture(texture2, vec2(texCoord.x, 1.0 - texCoord.y)), 0.2);
-            //final_color = texture(texture1, texCoord) * vertexColor;
-            //final_color = ourColor;
-        }
-    "#;
-
-    let vertex_shader = glCreateShader(GL_VERTEX_SHADER);
-    assert!(vertex_shader != 0);
-    unsafe {
-        glShaderSource(
-            vertex_shader,
-            1,
-            &(VERT_SHADER.as_bytes().as_ptr().cast()),
-            &(VERT_SHADER.len().try_into().unwrap()),
-        );
-    };
-    glCompileShader(vertex_shader);
-    log_error(vertex_shader, true);
-
-    let fragment_shader = glCreateShader(GL_FRAGMENT_SHADER);
-    assert!(fragment_shader != 0);
-    unsafe {
-        glShaderSource(
-            fragment_shader,
-            1,
-            &(FRAG_SHADER.as_bytes().as_ptr().cast()),
-            &(FRAG_SHADER.len().try_into().unwrap()),
-        );
     }
-    glCompileShader(fragment_shader);
-    log_error(fragment_shader, true);
-
-    // PROGRAM
-    let program = glCreateProgram();
-    assert!(program != 0);
-    glAttachShader(program, vertex_shader);
-    glAttachShader(program, fragment_shader);
-    glLinkProgram(program);
-    log_error(program, false);
-
-    glDeleteShader(vertex_shader);
-    glDeleteShader(fragment_shader);
 
     // Enable vsync - swap_window blocks until the image has been presented to the user
     // So we show images at most as fast the display's refresh rate
-    let _ = win.set_swap_interval(video::GlSwapInterval::Vsync);
+    let _ = platform.window().set_swap_interval(video::GlSwapInterval::Vsync);
 
     // Wireframe mode
     /*unsafe {
@@ -346,7 +273,7 @@ fn main() {
     }*/
 
     // CUBE POSITIONS
-    let cube_positions = vec![
+    let cube_positions = [
         glam::vec3(0.0, 0.0, 0.0),
         glam::vec3(2.0, 5.0, -15.0),
         glam::vec3(1.0, 3.0, -2.0),
@@ -357,103 +284,237 @@ fn main() {
         glam::vec3(-1.5, 1.0, -4.0),
     ];
 
+    // Every cube is a node, a small one rides along on the first and turns with it
+    let mut scene = SceneGraph::new();
+    let cubes: Vec<_> = cube_positions
+        .iter()
+        .map(|&position| scene.add(Transform::from_translation(position), ()))
+        .collect();
+    scene.add_child(
+        cubes[0],
+        Transform::new(
+            glam::vec3(0.0, 1.5, 0.0),
+            glam::Quat::IDENTITY,
+            glam::Vec3::splat(0.3),
+        ),
+        (),
+    );
 
-    let now = SystemTime::now();
-
-    glUseProgram(program);
-    let texture1 = CString::new("texture1").unwrap();
-    let texture2 = CString::new("texture2").unwrap();
-
-    let location_texture1 = unsafe { glGetUniformLocation(program, texture1.as_ptr().cast()) };
-    let location_texture2 = unsafe { glGetUniformLocation(program, texture2.as_ptr().cast()) };
-    assert!(location_texture1 >= 0);
-    assert!(location_texture2 >= 0);
-    unsafe {
-        glUniform1i(location_texture1, 0);
-        glUniform1i(location_texture2, 1);
+    // A floor of cubes below the scene, tinted by where they are
+    let crowd_instances: Vec<_> = (0..CROWD_SIDE * CROWD_SIDE)
+        .map(|i| {
+            let (x, z) = ((i % CROWD_SIDE) as f32, (i / CROWD_SIDE) as f32);
+            let side = CROWD_SIDE as f32;
+            let position = glam::vec3(2.0 * x - side, -8.0, 2.0 * z - side);
+            let model = glam::Mat4::from_rotation_translation(
+                glam::Quat::from_rotation_y(x * 0.3 + z * 0.7),
+                position,
+            );
+            Instance::with_color(model, glam::vec4(x / side, 0.5, z / side, 1.0))
+        })
+        .collect();
+    let crowd_buffer =
+        InstanceBuffer::new(gl, &crowd_instances).expect("Could not make the instance buffer");
+    let mut crowd = Crowd::Off;
+    // CPU time spent issuing the crowd's draw calls, reported once a second
+    let mut crowd_time = Duration::ZERO;
+    let mut crowd_frames = 0u32;
+    let mut crowd_report = Instant::now();
+
+    for program in [&program, &instanced_program] {
+        program.use_program();
+        program.program().set_uniform("texture1", 0);
+        program.program().set_uniform("texture2", 1);
     }
 
-    let model = CString::new("model").unwrap();
-    let location_model = unsafe { glGetUniformLocation(program, model.as_ptr().cast()) };
-    assert!(location_model >= 0);
+    // A dim sun, a warm light circling the first cube and a flashlight held by the camera
+    let mut lights = Lights::new();
+    lights.directional.push(DirectionalLight {
+        direction: glam::vec3(-0.2, -1.0, -0.3),
+        color: LightColor::new(glam::Vec3::splat(0.3)),
+    });
+    lights.point.push(PointLight {
+        position: glam::Vec3::ZERO,
+        color: LightColor::new(glam::vec3(1.0, 0.8, 0.5)),
+        attenuation: Attenuation::default(),
+    });
+    lights.spot.push(SpotLight {
+        position: glam::Vec3::ZERO,
+        direction: glam::Vec3::NEG_Z,
+        inner_cutoff: 12.5f32.to_radians(),
+        outer_cutoff: 17.5f32.to_radians(),
+        color: LightColor {
+            ambient: glam::Vec3::ZERO,
+            ..LightColor::new(glam::Vec3::ONE)
+        },
+        attenuation: Attenuation::default(),
+    });
+    let container_material = Material {
+        diffuse_map: Some(&texture_container),
+        specular: glam::Vec3::splat(0.5),
+        ..Material::new(32.0)
+    };
+    let mut lighting = Lighting::Shaded(ShadingModel::BlinnPhong);
 
-    let view = CString::new("view").unwrap();
-    let location_view = unsafe { glGetUniformLocation(program, view.as_ptr().cast())};
-    assert!(location_view >= 0);
+    unsafe { glEnable(GL_DEPTH_TEST) };
 
-    let projection = CString::new("projection").unwrap();
-    let location_projection = unsafe {glGetUniformLocation(program, projection.as_ptr().cast())};
-    assert!(location_projection >= 0);
+    // Game time pauses and scales, the camera keeps moving in real time
+    let mut clock = FrameClock::new();
+    // The cubes turn in fixed steps, drawn in between the last two
+    let mut previous_angle = 0.0f32;
+    let mut angle = 0.0f32;
 
-    unsafe { glEnable(GL_DEPTH_TEST) };
+    // Key bindings live in a file, so they can be changed without recompiling
+    let bindings = ActionMap::load("./assets/bindings.cfg").unwrap_or_else(|e| panic!("{}", e));
+    let mut input = Input::new(bindings);
+    // Controllers close when they are dropped, so the opened ones are kept by id
+    let mut controllers = std::collections::HashMap::new();
 
-    let mut delta_time = 0.0;
-    let mut last_frame = 0.0;
+    // toggle_camera switches between flying around and orbiting the first cube
+    let mut fly_camera = FlyCamera::new(glam::Vec3::new(0.0, 0.0, 0.0));
+    let mut orbit_camera = OrbitCamera::new(cube_positions[0], 5.0);
+    let mut orbiting = false;
 
-    let camera_speed = 10.0;
-    let mut camera_front = glam::Vec3::new(0.0, 0.0, -1.0);
-    let mut camera_pos = glam::Vec3::new(0.0, 0.0, 0.0);
-    let global_up = glam::Vec3::new(0.0, 1.0, 0.0);
+    let mut frames_drawn = 0u64;
 
-    let mut yaw = 0.0;
-    let mut pitch = 0.0;
-    let mut fov = 45.0;
+    // Screenshots and recordings are encoded on their own thread
+    let mut capture = Capture::new("./captures");
 
     // Processing events - we have to, OS otherwise thinks the application has stalled
     'main_loop: loop {
 
         // DELTA TIME
-        let current_frame = now.elapsed().unwrap().as_secs_f32();
-        delta_time = current_frame - last_frame;
-        last_frame = current_frame;
+        clock.tick();
+        let delta_time = clock.real_delta();
 
         // Handle events this frame
-        while let Some(event) = sdl.poll_events() {
-            match event {
-                (events::Event::Quit, _) => break 'main_loop,
-                (events::Event::Key { win_id: _, pressed: true, repeat: _, scancode: _, keycode: SDLK_w, modifiers: _ }, _) => {
-                    camera_pos += camera_front * camera_speed * delta_time;
-                },
-                (events::Event::Key { win_id: _, pressed: true, repeat: _, scancode: _, keycode: SDLK_s, modifiers: _ }, _) => {
-                    camera_pos -= camera_front * camera_speed * delta_time;
-                },
-                (events::Event::Key { win_id: _, pressed: true, repeat: _, scancode: _, keycode: SDLK_a, modifiers: _ }, _) => {
-                    camera_pos -= camera_front.cross(global_up).normalize() * camera_speed * delta_time;
-                },
-                (events::Event::Key { win_id: _, pressed: true, repeat: _, scancode: _, keycode: SDLK_d, modifiers: _ }, _) => {
-                    camera_pos += camera_front.cross(global_up).normalize() * camera_speed * delta_time;
-                },
-                (events::Event::MouseMotion { win_id: _, mouse_id: _, button_state: _, x_win, y_win, x_delta, y_delta }, _) => {
-                    yaw += x_delta as f32 * 0.1;
-                    pitch -= y_delta as f32 * 0.1;
-
-                    if pitch >= 89.0 {
-                        pitch = 89.0;
-                    }
-                    if pitch <= -89.0 {
-                        pitch = -89.0;
+        input.begin_frame();
+        while let Some((event, _)) = platform.sdl().poll_events() {
+            if let events::Event::Quit = event {
+                break 'main_loop;
+            }
+            if let events::Event::ControllerAdded { index } = event {
+                match platform.sdl().open_game_controller(index) {
+                    Ok(controller) => {
+                        // Later events name the controller by its joystick instance id,
+                        // which beryllium does not expose
+                        let id = unsafe { SDL_JoystickGetDeviceInstanceID(index) }.0;
+                        controllers.insert(id, controller);
+                        input.handle_event(InputEvent::GamepadAdded { id });
                     }
+                    Err(e) => eprintln!("Could not open controller {}: {:?}", index, e),
+                }
+            }
+            if let events::Event::ControllerRemoved { ctrl_id } = event {
+                controllers.remove(&ctrl_id);
+            }
+            if let Some(event) = input_event(&event) {
+                input.handle_event(event);
+            }
+        }
+        if input.action_pressed("quit") {
+            break 'main_loop;
+        }
 
-                    let mut look_direction = glam::Vec3::ZERO;
-                    look_direction.x = yaw.to_radians().cos() * pitch.to_radians().cos();
-                    look_direction.y = pitch.to_radians().sin();
-                    look_direction.z = yaw.to_radians().sin() * pitch.to_radians().cos();
-                    camera_front = look_direction.normalize();
-                },
-                (events::Event::MouseWheel { win_id: _, mouse_id: _, x: _, y }, _) => {
-                    fov -= y as f32;
-                    if fov < 1.0 {
-                        fov = 1.0;
-                    }
-                    if fov > 45.0 {
-                        fov = 45.0
-                    }
-                },
-                _ => (),
+        if input.action_pressed("pause") {
+            clock.toggle_pause();
+        }
+        if input.action_pressed("step_frame") {
+            clock.step();
+        }
+        if input.action_pressed("time_slower") {
+            clock.set_time_scale(clock.time_scale() * 0.5);
+        }
+        if input.action_pressed("time_faster") {
+            clock.set_time_scale(clock.time_scale() * 2.0);
+        }
+        if input.action_pressed("screenshot") {
+            capture.request_screenshot();
+        }
+        if input.action_pressed("toggle_recording") {
+            if let Some(frames) = capture.stop_recording() {
+                println!("Recorded {} frames", frames);
+            } else {
+                let dir = capture.start_recording(RECORDING_FRAME_RATE);
+                println!("Recording into {}", dir.display());
             }
+            // The recording plays back at its own rate, however long frames take to draw
+            clock.set_frame_time(capture.recording_frame_rate().map(|rate| 1.0 / rate));
+        }
+        if input.action_pressed("cycle_crowd") {
+            crowd = crowd.next();
+            crowd_time = Duration::ZERO;
+            crowd_frames = 0;
+            crowd_report = Instant::now();
+            println!("Crowd: {:?}", crowd);
+        }
+        if input.action_pressed("cycle_lighting") {
+            lighting = lighting.next();
+            println!("Lighting: {:?}", lighting);
+        }
+        clock.update(|step| {
+            previous_angle = angle;
+            angle += -PI / 3.0 * step;
+        });
+
+        // Held keys and sticks move the camera every frame, not just when the OS repeats them
+        if input.action_pressed("toggle_camera") {
+            orbiting = !orbiting;
+        }
+        let camera: &mut dyn Camera = if orbiting { &mut orbit_camera } else { &mut fly_camera };
+        for (axis, negative, positive) in MOVEMENT_AXES {
+            let value = input.axis(axis);
+            if value > 0.0 {
+                camera.process_movement(positive, delta_time * value);
+            } else if value < 0.0 {
+                camera.process_movement(negative, -delta_time * value);
+            }
+        }
+        let look = glam::Vec2::new(input.axis("look_x"), input.axis("look_y"));
+        let mouse_motion = input.mouse_motion() + look * STICK_LOOK_SPEED * delta_time;
+        if mouse_motion != glam::Vec2::ZERO {
+            camera.process_mouse(mouse_motion.x, mouse_motion.y);
+        }
+        if input.wheel().y != 0.0 {
+            camera.zoom(input.wheel().y);
         }
         // Now events are clear
 
+        // A reloaded program starts out with default uniforms
+        for program in [&mut program, &mut instanced_program] {
+            if program.poll() {
+                program.use_program();
+                program.program().set_uniform("texture1", 0);
+                program.program().set_uniform("texture2", 1);
+            }
+        }
+        // The lit program gets all of its uniforms every frame anyway
+        lit_program.poll();
+        let shader = match lighting {
+            Lighting::Off => {
+                program.use_program();
+                program.program()
+            }
+            Lighting::Shaded(shading) => {
+                lit_program.use_program();
+                let shader = lit_program.program();
+                let orbit = clock.time() * 0.8;
+                lights.point[0].position =
+                    cube_positions[0] + glam::vec3(orbit.cos() * 2.5, 1.0, orbit.sin() * 2.5);
+                lights.spot[0].position = camera.position();
+                // Both cameras look down -z in view space
+                lights.spot[0].direction =
+                    camera.view_matrix().inverse().transform_vector3(glam::Vec3::NEG_Z);
+                lights.shading = shading;
+                if let Err(e) = lights.upload(shader, &light_limits) {
+                    eprintln!("Warning: {}", e);
+                }
+                container_material.upload(shader, 0);
+                shader.set_uniform("view_position", camera.position());
+                shader
+            }
+        };
+
         // Here is the spot to change the world state and draw
 
         unsafe {
@@ -463,110 +524,98 @@ fn main() {
 
             // The default texture unit for a texture is 0 which is the default active texture unit
             // so we didn't need to assign a location in the previous section
-            glActiveTexture(GL_TEXTURE0);
-            glBindTexture(GL_TEXTURE_2D, texture_wooden_crate);
-            glActiveTexture(GL_TEXTURE1);
-            glBindTexture(GL_TEXTURE_2D, texture_face);
+            if lighting == Lighting::Off {
+                texture_wooden_crate.bind_to_unit(0);
+                texture_face.bind_to_unit(1);
+            }
 
             // Compute matrix
 
-            let time_value = now.elapsed().unwrap().as_secs_f32();
-
+            let cube_angle = previous_angle + (angle - previous_angle) * clock.alpha();
+            for &cube in &cubes {
+                scene
+                    .transform_mut(cube)
+                    .set_rotation(glam::Quat::from_rotation_x(cube_angle));
+            }
+            scene.update();
 
-            //glUniformMatrix4fv(location_model, 1, 0, model_matrix.to_cols_array().as_ptr());
+            //shader.set_uniform("model", model_matrix);
 
             // CAMERA SETUP
 
-            let camera_target = camera_pos + camera_front;
-            let camera_direction = (camera_pos - camera_target).normalize();
-            let camera_right = global_up.cross(camera_direction).normalize();
-
-            let camera_up = camera_direction.cross(camera_right);
-
-            let view_matrix_3 = glam::mat3(camera_right, camera_up, camera_direction).transpose();
-            let mut transpose_matrix = glam::Mat4::IDENTITY;
-            transpose_matrix.w_axis = glam::Vec4::from_array([-camera_pos[0], -camera_pos[1], -camera_pos[2], 1.0]);
-            let view_matrix = glam::Mat4::from_mat3(view_matrix_3) * transpose_matrix;
-
-            //let view_matrix = glam::Mat4::look_at_rh(camera_pos, camera_target, global_up);
-
-            glUniformMatrix4fv(location_view, 1, 0, view_matrix.to_cols_array().as_ptr());
-
-            let mut projection_matrix = glam::Mat4::IDENTITY;
-            projection_matrix = projection_matrix * glam::Mat4::perspective_rh_gl(fov.to_radians(), 800.0/600.0, 0.1, 100.0);
-            glUniformMatrix4fv(location_projection, 1, 0, projection_matrix.to_cols_array().as_ptr());
+            shader.set_uniform("view", camera.view_matrix());
+            shader.set_uniform("projection", camera.projection_matrix(platform.aspect_ratio()));
 
-            glBindVertexArray(vao);
+            for (_, node) in &scene {
+                shader.set_uniform("model", node.transform.world_matrix());
+                if lighting != Lighting::Off {
+                    shader.set_uniform("normal_matrix", node.transform.normal_matrix());
+                }
 
-            for i in 0..cube_positions.len() {
-                let model_matrix = glam::Mat4::from_translation(cube_positions[i]) * glam::Mat4::from_rotation_x(-PI/3.0 * time_value);
-                glUniformMatrix4fv(location_model, 1, 0, model_matrix.to_cols_array().as_ptr());
-
-                glDrawArrays(GL_TRIANGLES, 0, 36);    
+                cube.draw();
             }
 
-            //glDrawArrays(GL_TRIANGLES, 0, 36);
-
-            win.swap_window();
-        }
-    }
-
-    unsafe {
-        glDeleteVertexArrays(1, &vao);
-
-        glDeleteBuffers(1, &vbo);
-
-        glDeleteProgram(program);
-    }
-}
-
-fn log_error(object_id: u32, is_shader: bool) -> () {
-    let mut success = 0;
-
-    unsafe {
-        if is_shader {
-            glGetShaderiv(object_id, GL_COMPILE_STATUS, &mut success);
-        } else {
-            glGetProgramiv(object_id, GL_LINK_STATUS, &mut success);
-        }
-
-        if success == 0 {
-            let mut log_len = 0i32;
-
-            if is_shader {
-                glGetShaderiv(object_id, GL_INFO_LOG_LENGTH, &mut log_len);
-            } else {
-                glGetProgramiv(object_id, GL_INFO_LOG_LENGTH, &mut log_len);
+            // The same cubes either in one draw call or one call each. Both use
+            // the same program, so only the number of draw calls differs
+            let crowd_start = Instant::now();
+            if crowd != Crowd::Off {
+                instanced_program.use_program();
+                let shader = instanced_program.program();
+                shader.set_uniform("view", camera.view_matrix());
+                shader.set_uniform("projection", camera.projection_matrix(platform.aspect_ratio()));
+                // The lit program leaves the material maps on these units
+                texture_wooden_crate.bind_to_unit(0);
+                texture_face.bind_to_unit(1);
             }
-
-            let mut log_message: Vec<u8> = Vec::with_capacity(log_len as usize);
-
-            if is_shader {
-                glGetShaderInfoLog(
-                    object_id,
-                    log_message.capacity() as i32,
-                    &mut log_len,
-                    log_message.as_mut_ptr().cast(),
-                );
-            } else {
-                glGetProgramInfoLog(
-                    object_id,
-                    log_message.capacity() as i32,
-                    &mut log_len,
-                    log_message.as_mut_ptr().cast(),
-                );
+            match crowd {
+                Crowd::Off => {}
+                Crowd::Instanced => cube.draw_instances(&crowd_buffer),
+                Crowd::PerCube => {
+                    for instance in &crowd_instances {
+                        set_instance_attributes(instance);
+                        cube.draw();
+                    }
+                }
+            }
+            if crowd != Crowd::Off {
+                crowd_time += crowd_start.elapsed();
+                crowd_frames += 1;
+                if crowd_report.elapsed() >= Duration::from_secs(1) {
+                    let draw_calls = match crowd {
+                        Crowd::Instanced => 1,
+                        _ => crowd_instances.len(),
+                    };
+                    println!(
+                        "{:?}: {} cubes in {} draw calls, {:.3} ms a frame to issue",
+                        crowd,
+                        crowd_instances.len(),
+                        draw_calls,
+                        crowd_time.as_secs_f64() * 1000.0 / crowd_frames as f64,
+                    );
+                    crowd_time = Duration::ZERO;
+                    crowd_frames = 0;
+                    crowd_report = Instant::now();
+                }
             }
 
-            log_message.set_len(log_len.try_into().unwrap());
+            //glDrawArrays(GL_TRIANGLES, 0, 36);
 
-            if is_shader {
-                glDeleteShader(object_id);
+            let (width, height) = platform.size();
+            capture.end_frame(width, height);
+            platform.swap_buffers();
+        }
+        for result in capture.finished() {
+            match result {
+                Ok(path) if !capture.is_recording() => println!("Saved {}", path.display()),
+                Ok(_) => {}
+                Err(e) => eprintln!("Capture failed: {}", e),
             }
+        }
 
-            panic!(
-                "Shader Program Link Error: {}",
-                String::from_utf8_lossy(&log_message)
-            );
+        frames_drawn += 1;
+        if frame_limit == Some(frames_drawn) {
+            break 'main_loop;
         }
     }
+
 }
@@ -0,0 +1,212 @@
+use std::{any::Any, env, error::Error, fmt, panic};
+
+use beryllium::{
+    init::InitFlags,
+    video::{CreateWinArgs, GlProfile, GlWindow},
+    Sdl,
+};
+use gl33::global_loader::glViewport;
+
+use crate::learn_opengl::GlContext;
+
+/// Where the frames of a [`Platform`] go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    /// A window on the screen.
+    Window,
+    /// SDL's offscreen video driver, with a surfaceless EGL context. Needs
+    /// neither a display nor a GPU, Mesa falls back to llvmpipe.
+    Headless,
+}
+
+impl Surface {
+    /// [`Surface::Headless`] when there is no display to open a window on,
+    /// which on Linux means neither `DISPLAY` nor `WAYLAND_DISPLAY` is set.
+    pub fn detect() -> Self {
+        let has_display = !cfg!(target_os = "linux")
+            || env::var_os("DISPLAY").is_some()
+            || env::var_os("WAYLAND_DISPLAY").is_some();
+        if has_display {
+            Self::Window
+        } else {
+            Self::Headless
+        }
+    }
+}
+
+/// Errors of setting up SDL and the GL context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlatformError {
+    /// SDL could not be started, there is no display or no video driver.
+    Init(String),
+    /// An attribute of the GL context was refused.
+    ContextAttribute(String),
+    /// SDL could not make the window or the GL context.
+    CreateWindow(String),
+}
+
+impl PlatformError {
+    // beryllium's errors only implement Debug
+    fn context_attribute(e: impl fmt::Debug) -> Self {
+        Self::ContextAttribute(format!("{:?}", e))
+    }
+
+    fn init(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "SDL_Init failed".to_string(),
+            },
+        };
+        Self::Init(message)
+    }
+}
+
+impl fmt::Display for PlatformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlatformError::Init(e) => write!(f, "could not start SDL: {}", e),
+            PlatformError::ContextAttribute(e) => {
+                write!(f, "could not set up the GL context attributes: {}", e)
+            }
+            PlatformError::CreateWindow(e) => {
+                write!(f, "could not make a window and GL context: {}", e)
+            }
+        }
+    }
+}
+
+impl Error for PlatformError {}
+
+/// SDL with a GL 3.3 core context, either in a window or offscreen.
+///
+/// Both surfaces render into the default framebuffer and present with
+/// [`Platform::swap_buffers`], so nothing drawing to it has to know which one
+/// it got. The GL functions are loaded when the platform is made, GL objects
+/// borrow [`Platform::gl`] so they can't outlive the context.
+pub struct Platform {
+    gl: GlContext,
+    window: GlWindow,
+    sdl: Sdl,
+    surface: Surface,
+    width: u32,
+    height: u32,
+}
+
+impl Platform {
+    /// Starts SDL and makes the GL context. The size is in screen
+    /// coordinates, on high-DPI displays the framebuffer has more pixels, see
+    /// [`Platform::size`].
+    ///
+    /// SDL can only be started once at a time, so there can only be one
+    /// platform at a time. Should SDL fail to start, it can't be started again
+    /// in the same process.
+    pub fn new(
+        title: &str,
+        width: u32,
+        height: u32,
+        surface: Surface,
+    ) -> Result<Self, PlatformError> {
+        let flags = match surface {
+            Surface::Window => InitFlags::EVERYTHING,
+            Surface::Headless => {
+                // Has to be picked before SDL starts. Audio and controllers
+                // are left out, CI boxes tend to have neither.
+                env::set_var("SDL_VIDEODRIVER", "offscreen");
+                if env::var_os("EGL_PLATFORM").is_none() {
+                    env::set_var("EGL_PLATFORM", "surfaceless");
+                }
+                InitFlags::VIDEO | InitFlags::EVENTS
+            }
+        };
+        // beryllium panics when SDL_Init fails, a missing display is an error
+        // callers want to handle. The panic message still goes to stderr.
+        let sdl = panic::catch_unwind(|| Sdl::init(flags)).map_err(PlatformError::init)?;
+
+        sdl.set_gl_context_major_version(3)
+            .map_err(PlatformError::context_attribute)?;
+        sdl.set_gl_context_minor_version(3)
+            .map_err(PlatformError::context_attribute)?;
+        sdl.set_gl_profile(GlProfile::Core)
+            .map_err(PlatformError::context_attribute)?;
+        // Mac OS only hands out core contexts that drop everything deprecated
+        #[cfg(target_os = "macos")]
+        {
+            sdl.set_gl_context_flags(beryllium::video::GlContextFlags::FORWARD_COMPATIBLE)
+                .map_err(PlatformError::context_attribute)?;
+        }
+
+        let window = sdl
+            .create_gl_window(CreateWinArgs {
+                title,
+                width: width.try_into().unwrap(),
+                height: height.try_into().unwrap(),
+                allow_high_dpi: true,
+                borderless: false,
+                resizable: false,
+            })
+            .map_err(|e| PlatformError::CreateWindow(format!("{:?}", e)))?;
+
+        // The context SDL just made is current on this thread and lives as
+        // long as the window, which is dropped after the GlContext
+        let gl = unsafe { GlContext::load_with(|name| window.get_proc_address(name)) };
+
+        let (width, height) = window.get_drawable_size();
+        unsafe { glViewport(0, 0, width, height) };
+        let (width, height) = (width.try_into().unwrap(), height.try_into().unwrap());
+
+        Ok(Self {
+            gl,
+            window,
+            sdl,
+            surface,
+            width,
+            height,
+        })
+    }
+
+    pub fn gl(&self) -> &GlContext {
+        &self.gl
+    }
+
+    pub fn sdl(&self) -> &Sdl {
+        &self.sdl
+    }
+
+    pub fn window(&self) -> &GlWindow {
+        &self.window
+    }
+
+    pub fn surface(&self) -> Surface {
+        self.surface
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.surface == Surface::Headless
+    }
+
+    /// Size of the default framebuffer in pixels, the size the viewport and
+    /// pixel readback have to use. Larger than the size the platform was made
+    /// with on high-DPI displays.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    /// Hides the cursor and reports relative motion, does nothing without a
+    /// window since there is no mouse to grab.
+    pub fn grab_mouse(&self, grab: bool) {
+        if self.surface == Surface::Window && self.sdl.set_relative_mouse_mode(grab).is_err() {
+            eprintln!("Could not grab the mouse, relative mouse mode is not supported");
+        }
+    }
+
+    /// Presents the frame drawn into the default framebuffer.
+    pub fn swap_buffers(&self) {
+        self.window.swap_window();
+    }
+}
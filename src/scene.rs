@@ -0,0 +1,461 @@
+use glam::{Mat3, Mat4, Quat, Vec3};
+
+/// Where something is, which way it faces and how big it is, relative to its parent.
+///
+/// The local matrix is rebuilt whenever a part changes. The world and normal
+/// matrices depend on the parent, so changes only mark them dirty until
+/// [`Transform::update_world`] is called, which a [`SceneGraph`] does for
+/// every node that needs it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    local: Mat4,
+    world: Mat4,
+    normal: Mat3,
+    dirty: bool,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+        local: Mat4::IDENTITY,
+        world: Mat4::IDENTITY,
+        normal: Mat3::IDENTITY,
+        dirty: false,
+    };
+
+    /// A transform that places its object at `translation`, turns it by
+    /// `rotation` and scales it by `scale`, in that order from the outside in.
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        let mut transform = Self {
+            translation,
+            rotation,
+            scale,
+            ..Self::IDENTITY
+        };
+        transform.update_local();
+        transform.update_world(Mat4::IDENTITY);
+        transform
+    }
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self::new(translation, Quat::IDENTITY, Vec3::ONE)
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Self {
+        Self::new(Vec3::ZERO, rotation, Vec3::ONE)
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self::new(Vec3::ZERO, Quat::IDENTITY, scale)
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        self.translation
+    }
+
+    pub fn rotation(&self) -> Quat {
+        self.rotation
+    }
+
+    pub fn scale(&self) -> Vec3 {
+        self.scale
+    }
+
+    pub fn set_translation(&mut self, translation: Vec3) {
+        self.translation = translation;
+        self.update_local();
+    }
+
+    /// The rotation is normalized, so small errors don't pile up into a scale.
+    pub fn set_rotation(&mut self, rotation: Quat) {
+        self.rotation = rotation.normalize();
+        self.update_local();
+    }
+
+    pub fn set_scale(&mut self, scale: Vec3) {
+        self.scale = scale;
+        self.update_local();
+    }
+
+    /// Moves by `offset` in the space of the parent.
+    pub fn translate(&mut self, offset: Vec3) {
+        self.set_translation(self.translation + offset);
+    }
+
+    /// Turns by `rotation` in the space of the parent, after the current rotation.
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.set_rotation(rotation * self.rotation);
+    }
+
+    /// From the space of the object to that of its parent.
+    pub fn local_matrix(&self) -> Mat4 {
+        self.local
+    }
+
+    /// From the space of the object to world space, as of the last
+    /// [`Transform::update_world`].
+    pub fn world_matrix(&self) -> Mat4 {
+        self.world
+    }
+
+    /// Transforms normals to world space, keeping them perpendicular to
+    /// surfaces under non-uniform scales.
+    pub fn normal_matrix(&self) -> Mat3 {
+        self.normal
+    }
+
+    /// Whether the world and normal matrices are out of date with the parts.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Recomputes the world and normal matrices under the world matrix of the
+    /// parent, the identity for objects without one.
+    pub fn update_world(&mut self, parent: Mat4) {
+        self.world = parent * self.local;
+        let linear = Mat3::from_mat4(self.world);
+        // A zero scale flattens everything, there is no inverse to take
+        self.normal = if linear.determinant().abs() > f32::EPSILON {
+            linear.inverse().transpose()
+        } else {
+            linear
+        };
+        self.dirty = false;
+    }
+
+    fn update_local(&mut self) {
+        self.local =
+            Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
+        self.dirty = true;
+    }
+}
+
+/// Refers to a node of a [`SceneGraph`].
+///
+/// Ids of removed nodes are never given out again, so they can't end up
+/// referring to a different node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: u32,
+    generation: u32,
+}
+
+/// A node of a [`SceneGraph`], holding a transform and whatever the
+/// application wants to attach to it, like what to draw.
+#[derive(Debug, Clone)]
+pub struct Node<T> {
+    pub transform: Transform,
+    pub item: T,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    /// Handed out mutably since the last update, the transform may have been
+    /// replaced without being marked dirty.
+    touched: bool,
+}
+
+impl<T> Node<T> {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// The children, in the order they are drawn.
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u32,
+    node: Option<Node<T>>,
+}
+
+/// Nodes placed relative to their parents.
+///
+/// Changing a transform only marks it dirty. [`SceneGraph::update`] then
+/// recomputes the world matrices of the dirty nodes and everything below
+/// them, so the matrices are right again before drawing. Nodes are visited
+/// depth first, parents before their children, in the order they were added.
+#[derive(Debug, Clone)]
+pub struct SceneGraph<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    roots: Vec<NodeId>,
+    len: usize,
+}
+
+impl<T> Default for SceneGraph<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            roots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> SceneGraph<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The nodes without a parent, in the order they are drawn.
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    /// Adds a node at the top of the hierarchy.
+    pub fn add(&mut self, transform: Transform, item: T) -> NodeId {
+        let id = self.insert(Node {
+            transform,
+            item,
+            parent: None,
+            children: Vec::new(),
+            touched: false,
+        });
+        self.roots.push(id);
+        id
+    }
+
+    /// Adds a node as the last child of `parent`.
+    ///
+    /// Panics if `parent` is not in the graph.
+    pub fn add_child(&mut self, parent: NodeId, transform: Transform, item: T) -> NodeId {
+        assert!(
+            self.contains(parent),
+            "parent {:?} is not in the graph",
+            parent
+        );
+        let id = self.insert(Node {
+            transform,
+            item,
+            parent: Some(parent),
+            children: Vec::new(),
+            touched: false,
+        });
+        self.node_mut(parent).children.push(id);
+        id
+    }
+
+    /// Removes a node and everything below it, returning its item.
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        let node = self.get(id)?;
+        let parent = node.parent;
+        self.siblings_mut(parent).retain(|&sibling| sibling != id);
+
+        let mut removed = None;
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let slot = &mut self.slots[id.index as usize];
+            let node = slot
+                .node
+                .take()
+                .expect("children of a node are in the graph");
+            slot.generation += 1;
+            self.free.push(id.index);
+            self.len -= 1;
+            stack.extend(node.children);
+            removed.get_or_insert(node.item);
+        }
+        removed
+    }
+
+    /// Moves a node under `parent`, or to the top of the hierarchy for `None`,
+    /// as the last child. Its transform stays relative to its parent, so it
+    /// moves along with the new one.
+    ///
+    /// Returns `false` and leaves the graph as it was if either node is not in
+    /// the graph or `parent` is below the node, which would make a cycle.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+        if let Some(parent) = parent {
+            if !self.contains(parent) || self.ancestors(parent).any(|ancestor| ancestor == id) {
+                return false;
+            }
+        }
+
+        let old_parent = self.node_mut(id).parent;
+        self.siblings_mut(old_parent)
+            .retain(|&sibling| sibling != id);
+        self.siblings_mut(parent).push(id);
+        let node = self.node_mut(id);
+        node.parent = parent;
+        node.transform.dirty = true;
+        true
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node<T>> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_ref())
+    }
+
+    /// The node is updated by the next [`SceneGraph::update`], as its
+    /// transform may be replaced as a whole through the reference.
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node<T>> {
+        let node = self.find_mut(id)?;
+        node.touched = true;
+        Some(node)
+    }
+
+    /// Panics if the node is not in the graph.
+    pub fn transform(&self, id: NodeId) -> &Transform {
+        &self.node(id).transform
+    }
+
+    /// The node is updated by the next [`SceneGraph::update`], so replacing
+    /// the transform as a whole, like with
+    /// `*scene.transform_mut(id) = Transform::from_translation(..)`, is picked
+    /// up too even though a new transform is not dirty.
+    ///
+    /// Panics if the node is not in the graph.
+    pub fn transform_mut(&mut self, id: NodeId) -> &mut Transform {
+        let node = self.node_mut(id);
+        node.touched = true;
+        &mut node.transform
+    }
+
+    /// The node itself, its parent, its grandparent and so on.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.contains(id).then_some(id), |&id| self.node(id).parent)
+    }
+
+    /// Brings the world matrices of dirty nodes, and of everything below
+    /// them, up to date.
+    pub fn update(&mut self) {
+        let mut stack: Vec<(NodeId, Mat4, bool)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|&root| (root, Mat4::IDENTITY, false))
+            .collect();
+        while let Some((id, parent, parent_changed)) = stack.pop() {
+            let node = self.node_mut(id);
+            let changed = parent_changed || node.touched || node.transform.is_dirty();
+            node.touched = false;
+            if changed {
+                node.transform.update_world(parent);
+            }
+            let world = node.transform.world_matrix();
+            stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, world, changed)),
+            );
+        }
+    }
+
+    /// Every node, parents before their children, in the order they are drawn.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            graph: self,
+            stack: self.roots.iter().rev().copied().collect(),
+        }
+    }
+
+    fn insert(&mut self, mut node: Node<T>) -> NodeId {
+        // Its world matrix was made without the parent
+        node.transform.dirty = true;
+        self.len += 1;
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.node = Some(node);
+                NodeId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                NodeId {
+                    index: (self.slots.len() - 1) as u32,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    fn node(&self, id: NodeId) -> &Node<T> {
+        self.get(id)
+            .unwrap_or_else(|| panic!("node {:?} is not in the graph", id))
+    }
+
+    fn find_mut(&mut self, id: NodeId) -> Option<&mut Node<T>> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_mut())
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<T> {
+        self.find_mut(id)
+            .unwrap_or_else(|| panic!("node {:?} is not in the graph", id))
+    }
+
+    /// The children of `parent`, or the roots.
+    fn siblings_mut(&mut self, parent: Option<NodeId>) -> &mut Vec<NodeId> {
+        match parent {
+            Some(parent) => &mut self.node_mut(parent).children,
+            None => &mut self.roots,
+        }
+    }
+}
+
+/// Iterator over the nodes of a [`SceneGraph`] in draw order.
+pub struct Iter<'a, T> {
+    graph: &'a SceneGraph<T>,
+    stack: Vec<NodeId>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (NodeId, &'a Node<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.stack.pop()?;
+        let node = self.graph.node(id);
+        self.stack.extend(node.children.iter().rev());
+        Some((id, node))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SceneGraph<T> {
+    type Item = (NodeId, &'a Node<T>);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
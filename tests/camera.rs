@@ -0,0 +1,174 @@
+use glam::{Mat4, Vec2, Vec3};
+use opengl_chrno::camera::{Camera, CameraMovement, FlyCamera, OrbitCamera, OrbitMode};
+
+fn assert_mat_eq(a: Mat4, b: Mat4) {
+    assert!(a.abs_diff_eq(b, 1e-5), "{:?}\n!=\n{:?}", a, b);
+}
+
+fn look_at(camera: &FlyCamera) -> Mat4 {
+    Mat4::look_at_rh(
+        camera.position,
+        camera.position + camera.front(),
+        camera.world_up,
+    )
+}
+
+#[test]
+fn default_camera_looks_down_negative_z() {
+    let camera = FlyCamera::default();
+    assert!(camera.front().abs_diff_eq(Vec3::NEG_Z, 1e-6));
+    assert_mat_eq(camera.view_matrix(), Mat4::IDENTITY);
+}
+
+#[test]
+fn view_matrix_matches_look_at() {
+    let mut camera = FlyCamera::new(Vec3::new(1.0, -2.0, 3.5));
+    for (x, y) in [(0.0, 0.0), (120.0, -40.0), (-333.0, 250.0), (900.0, -900.0)] {
+        camera.process_mouse(x, y);
+        assert_mat_eq(camera.view_matrix(), look_at(&camera));
+    }
+}
+
+#[test]
+fn pitch_is_clamped() {
+    let mut camera = FlyCamera::default();
+    camera.process_mouse(0.0, -10_000.0);
+    assert_eq!(camera.pitch, FlyCamera::MAX_PITCH);
+    camera.process_mouse(0.0, 10_000.0);
+    assert_eq!(camera.pitch, -FlyCamera::MAX_PITCH);
+    assert_mat_eq(camera.view_matrix(), look_at(&camera));
+}
+
+#[test]
+fn movement_follows_the_view() {
+    let mut camera = FlyCamera {
+        speed: 2.0,
+        ..FlyCamera::default()
+    };
+
+    camera.process_movement(CameraMovement::Forward, 0.5);
+    assert!(camera.position.abs_diff_eq(Vec3::new(0.0, 0.0, -1.0), 1e-6));
+
+    camera.process_movement(CameraMovement::Right, 0.5);
+    assert!(camera.position.abs_diff_eq(Vec3::new(1.0, 0.0, -1.0), 1e-6));
+
+    camera.process_movement(CameraMovement::Backward, 0.5);
+    camera.process_movement(CameraMovement::Left, 0.5);
+    assert!(camera.position.abs_diff_eq(Vec3::ZERO, 1e-6));
+}
+
+#[test]
+fn zoom_is_clamped() {
+    let mut camera = FlyCamera::default();
+    camera.zoom(100.0);
+    assert_eq!(camera.fov, FlyCamera::MIN_FOV);
+    camera.zoom(-100.0);
+    assert_eq!(camera.fov, FlyCamera::MAX_FOV);
+}
+
+#[test]
+fn projection_matches_glam() {
+    let camera = FlyCamera::default();
+    assert_mat_eq(
+        camera.projection_matrix(800.0 / 600.0),
+        Mat4::perspective_rh_gl(45f32.to_radians(), 800.0 / 600.0, 0.1, 100.0),
+    );
+}
+
+fn orbit_look_at(camera: &OrbitCamera) -> Mat4 {
+    Mat4::look_at_rh(camera.eye(), camera.target, camera.up())
+}
+
+#[test]
+fn orbit_starts_on_positive_z() {
+    let camera = OrbitCamera::new(Vec3::new(1.0, 2.0, 3.0), 4.0);
+    assert!(camera.eye().abs_diff_eq(Vec3::new(1.0, 2.0, 7.0), 1e-5));
+    assert_mat_eq(
+        camera.view_matrix(),
+        Mat4::look_at_rh(Vec3::new(1.0, 2.0, 7.0), camera.target, Vec3::Y),
+    );
+}
+
+#[test]
+fn orbit_yaw_pitch_keeps_distance() {
+    let mut camera = OrbitCamera::new(Vec3::new(-1.0, 0.5, 2.0), 3.0);
+    for (x, y) in [(100.0, 0.0), (-30.0, 80.0), (500.0, -1000.0)] {
+        camera.orbit(x, y);
+        assert!((camera.eye().distance(camera.target) - 3.0).abs() < 1e-5);
+        assert!(camera.pitch.abs() <= OrbitCamera::MAX_PITCH);
+        assert_mat_eq(camera.view_matrix(), orbit_look_at(&camera));
+    }
+}
+
+#[test]
+fn orbit_pitch_raises_the_camera() {
+    let mut camera = OrbitCamera::new(Vec3::ZERO, 2.0);
+    camera.pitch = 90.0 - 45.0;
+    let eye = camera.eye();
+    assert!(eye.y > 0.0);
+    assert!((eye.y - eye.z).abs() < 1e-5);
+}
+
+#[test]
+fn arcball_keeps_distance_and_view() {
+    let mut camera = OrbitCamera::new(Vec3::ONE, 5.0);
+    camera.set_mode(OrbitMode::Arcball);
+    assert_mat_eq(
+        camera.view_matrix(),
+        OrbitCamera::new(Vec3::ONE, 5.0).view_matrix(),
+    );
+
+    camera.orbit(40.0, -25.0);
+    camera.arcball_rotate(Vec2::new(0.1, 0.2), Vec2::new(-0.4, 0.3));
+    assert!((camera.eye().distance(camera.target) - 5.0).abs() < 1e-4);
+    assert_mat_eq(camera.view_matrix(), orbit_look_at(&camera));
+}
+
+#[test]
+fn arcball_horizontal_drag_matches_yaw() {
+    let mut yaw_pitch = OrbitCamera::default();
+    let mut arcball = OrbitCamera::default();
+    arcball.set_mode(OrbitMode::Arcball);
+
+    yaw_pitch.orbit(60.0, 0.0);
+    arcball.orbit(60.0, 0.0);
+    assert!(yaw_pitch.eye().abs_diff_eq(arcball.eye(), 1e-4));
+}
+
+#[test]
+fn pan_moves_target_on_view_plane() {
+    let mut camera = OrbitCamera::default();
+    let eye = camera.eye();
+    camera.pan(100.0, 50.0);
+    let moved = camera.target;
+    assert!(moved.x < 0.0 && moved.y > 0.0);
+    assert!(moved.z.abs() < 1e-6);
+    assert!((camera.eye() - eye).abs_diff_eq(moved, 1e-6));
+}
+
+#[test]
+fn orbit_zoom_is_clamped() {
+    let mut camera = OrbitCamera::default();
+    camera.zoom(1.0);
+    assert!(camera.distance < 5.0);
+    camera.zoom(1000.0);
+    assert_eq!(camera.distance, camera.min_distance);
+    camera.zoom(-1000.0);
+    assert_eq!(camera.distance, camera.max_distance);
+}
+
+#[test]
+fn cameras_share_the_trait() {
+    let mut cameras: Vec<Box<dyn Camera>> = vec![
+        Box::new(FlyCamera::default()),
+        Box::new(OrbitCamera::default()),
+    ];
+    for camera in &mut cameras {
+        camera.process_mouse(10.0, 5.0);
+        camera.process_movement(CameraMovement::Forward, 0.1);
+        camera.zoom(1.0);
+        let view = camera.view_matrix();
+        let origin = view.transform_point3(camera.position());
+        assert!(origin.abs_diff_eq(Vec3::ZERO, 1e-4));
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use image::{Rgba, RgbaImage};
+use opengl_chrno::capture::{timestamp, Capture};
+
+mod common;
+
+use common::scratch;
+
+fn frame(shade: u8) -> RgbaImage {
+    RgbaImage::from_pixel(4, 3, Rgba([shade, shade, shade, 255]))
+}
+
+fn pngs_in(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".png"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn timestamps_are_utc_dates() {
+    assert_eq!(timestamp(UNIX_EPOCH), "19700101-000000-000");
+    assert_eq!(
+        timestamp(UNIX_EPOCH + Duration::from_millis(951_827_696_789)),
+        "20000229-123456-789"
+    );
+    assert_eq!(
+        timestamp(UNIX_EPOCH + Duration::from_secs(1_798_761_599)),
+        "20261231-235959-000"
+    );
+}
+
+#[test]
+fn screenshots_capture_one_frame() {
+    let dir = scratch("screenshots");
+    let mut capture = Capture::new(&dir);
+    assert!(!capture.wants_frame());
+
+    capture.request_screenshot();
+    assert!(capture.wants_frame());
+    capture.submit(frame(10));
+    assert!(!capture.wants_frame());
+    // Not asked for, so not saved
+    capture.submit(frame(20));
+    drop(capture);
+
+    let names = pngs_in(&dir);
+    assert_eq!(names.len(), 1);
+    assert!(names[0].starts_with("screenshot-"));
+    let saved = image::open(dir.join(&names[0])).unwrap().to_rgba8();
+    assert_eq!(saved, frame(10));
+}
+
+#[test]
+fn recordings_are_numbered_sequences() {
+    let dir = scratch("recordings");
+    let mut capture = Capture::new(&dir);
+
+    let recording = capture.start_recording(30.0).to_path_buf();
+    assert!(capture.is_recording());
+    assert_eq!(capture.recording_frame_rate(), Some(30.0));
+    for shade in 0..3 {
+        capture.submit(frame(shade * 50));
+    }
+    assert_eq!(capture.stop_recording(), Some(3));
+    assert_eq!(capture.stop_recording(), None);
+    capture.submit(frame(255));
+    drop(capture);
+
+    assert_eq!(
+        pngs_in(&recording),
+        ["frame-000000.png", "frame-000001.png", "frame-000002.png"]
+    );
+    let last = image::open(recording.join("frame-000002.png")).unwrap();
+    assert_eq!(last.to_rgba8(), frame(100));
+}
+
+#[test]
+fn finished_captures_are_reported() {
+    let dir = scratch("reports");
+    let mut capture = Capture::new(&dir);
+    capture.request_screenshot();
+    capture.start_recording(60.0);
+    // Both get the same frame
+    capture.submit(frame(7));
+
+    let mut written = Vec::new();
+    while written.len() < 2 {
+        written.extend(capture.finished().map(|result| result.unwrap()));
+        std::thread::yield_now();
+    }
+    assert!(written.iter().all(|path| path.exists()));
+}
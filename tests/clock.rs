@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use opengl_chrno::clock::{FrameClock, ManualTime};
+
+fn clock() -> (FrameClock<ManualTime>, ManualTime) {
+    let time = ManualTime::new();
+    let mut clock = FrameClock::with_time_source(time.clone());
+    clock.set_fixed_step(0.01);
+    (clock, time)
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-5, "{} != {}", a, b);
+}
+
+#[test]
+fn measures_frames() {
+    let (mut clock, time) = clock();
+    assert_eq!(clock.tick(), 0.0);
+
+    time.advance(Duration::from_millis(16));
+    assert_close(clock.tick(), 0.016);
+    time.advance(Duration::from_millis(20));
+    clock.tick();
+    assert_close(clock.delta(), 0.020);
+    assert_close(clock.time(), 0.036);
+    assert_eq!(clock.frame(), 3);
+}
+
+#[test]
+fn fixed_steps_with_alpha() {
+    let (mut clock, time) = clock();
+
+    time.advance_secs(0.025);
+    clock.tick();
+    let mut total = 0.0;
+    assert_eq!(clock.update(|step| total += step), 2);
+    assert_close(total, 0.02);
+    assert_close(clock.alpha(), 0.5);
+
+    time.advance_secs(0.007);
+    clock.tick();
+    assert_eq!(clock.update(|_| ()), 1);
+    assert_close(clock.alpha(), 0.2);
+
+    time.advance_secs(0.001);
+    clock.tick();
+    assert_eq!(clock.update(|_| ()), 0);
+    assert_close(clock.alpha(), 0.3);
+}
+
+#[test]
+fn long_frames_are_clamped() {
+    let (mut clock, time) = clock();
+    clock.set_max_delta(0.1);
+
+    time.advance_secs(5.0);
+    clock.tick();
+    assert_close(clock.real_delta(), 5.0);
+    assert_close(clock.delta(), 0.1);
+    assert_eq!(clock.update(|_| ()), 10);
+}
+
+#[test]
+fn pause_stops_game_time() {
+    let (mut clock, time) = clock();
+    time.advance_secs(0.05);
+    clock.tick();
+    clock.update(|_| ());
+
+    clock.pause();
+    time.advance_secs(0.05);
+    clock.tick();
+    assert_eq!(clock.delta(), 0.0);
+    assert_close(clock.real_delta(), 0.05);
+    assert_close(clock.time(), 0.05);
+    assert_close(clock.real_time(), 0.1);
+    assert_eq!(clock.update(|_| ()), 0);
+
+    clock.resume();
+    time.advance_secs(0.02);
+    clock.tick();
+    assert_close(clock.time(), 0.07);
+}
+
+#[test]
+fn single_step_while_paused() {
+    let (mut clock, time) = clock();
+    time.advance_secs(0.005);
+    clock.tick();
+    clock.pause();
+
+    clock.step();
+    time.advance_secs(1.0);
+    clock.tick();
+    assert_close(clock.delta(), 0.01);
+    assert_eq!(clock.update(|_| ()), 1);
+    assert_close(clock.alpha(), 0.5);
+
+    time.advance_secs(1.0);
+    clock.tick();
+    assert_eq!(clock.delta(), 0.0);
+    assert_eq!(clock.update(|_| ()), 0);
+}
+
+#[test]
+fn stepping_only_works_while_paused() {
+    let (mut clock, time) = clock();
+    clock.step();
+    time.advance_secs(0.002);
+    clock.tick();
+    assert_close(clock.delta(), 0.002);
+}
+
+#[test]
+fn time_scale() {
+    let (mut clock, time) = clock();
+    clock.set_time_scale(0.25);
+    time.advance_secs(0.08);
+    clock.tick();
+    assert_close(clock.delta(), 0.02);
+    assert_close(clock.real_delta(), 0.08);
+    assert_eq!(clock.update(|_| ()), 2);
+
+    clock.set_time_scale(-1.0);
+    assert_eq!(clock.time_scale(), 0.0);
+}
+
+#[test]
+fn fixed_frame_time_ignores_real_time() {
+    let (mut clock, time) = clock();
+    clock.tick();
+    clock.set_frame_time(Some(1.0 / 30.0));
+
+    // A slow frame and a fast one both count as a thirtieth of a second
+    time.advance(Duration::from_millis(200));
+    assert_close(clock.tick(), 1.0 / 30.0);
+    time.advance(Duration::from_millis(1));
+    clock.tick();
+    assert_close(clock.real_delta(), 1.0 / 30.0);
+    assert_close(clock.time(), 2.0 / 30.0);
+
+    clock.set_frame_time(None);
+    time.advance(Duration::from_millis(10));
+    assert_close(clock.tick(), 0.010);
+}
@@ -0,0 +1,41 @@
+//! Helpers shared by the integration tests. Every test crate that declares
+//! `mod common` compiles all of them, but only uses some.
+#![allow(dead_code)]
+
+use std::{env, fs, path::PathBuf, sync::Mutex};
+
+use opengl_chrno::platform::{Platform, Surface};
+
+/// Setting this environment variable to anything but `0` skips the tests
+/// that need a GL context on machines that can't make one. Without it they
+/// fail, so a CI box without GL can't quietly pass them.
+pub const SKIP_GL_VAR: &str = "SKIP_GL_TESTS";
+
+/// SDL can only be started once at a time, so the GL tests take turns.
+pub static GL: Mutex<()> = Mutex::new(());
+
+/// A scratch directory of its own for every test.
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+/// A headless platform of the given size for a test that needs GL, `None`
+/// if there is none and [`SKIP_GL_VAR`] asks to skip.
+///
+/// Panics if there is no headless GL context and skipping was not asked for.
+/// Hold the [`GL`] lock while the platform is alive.
+pub fn headless(title: &str, width: u32, height: u32) -> Option<Platform> {
+    match Platform::new(title, width, height, Surface::Headless) {
+        Ok(platform) => Some(platform),
+        Err(e) if env::var_os(SKIP_GL_VAR).is_some_and(|value| value != "0") => {
+            eprintln!("Skipping, there is no headless GL context: {}", e);
+            None
+        }
+        Err(e) => panic!(
+            "there is no headless GL context, set {}=1 to skip: {}",
+            SKIP_GL_VAR, e
+        ),
+    }
+}
@@ -0,0 +1,42 @@
+use gl33::{
+    GLenum, GL_FRAMEBUFFER_COMPLETE, GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT,
+    GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT, GL_FRAMEBUFFER_UNSUPPORTED,
+};
+use opengl_chrno::learn_opengl::FramebufferError;
+
+#[test]
+fn statuses_map_to_typed_errors() {
+    assert_eq!(FramebufferError::from_status(GL_FRAMEBUFFER_COMPLETE), None);
+    assert_eq!(
+        FramebufferError::from_status(GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT),
+        Some(FramebufferError::IncompleteAttachment)
+    );
+    assert_eq!(
+        FramebufferError::from_status(GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT),
+        Some(FramebufferError::MissingAttachment)
+    );
+    assert_eq!(
+        FramebufferError::from_status(GL_FRAMEBUFFER_UNSUPPORTED),
+        Some(FramebufferError::Unsupported)
+    );
+    assert_eq!(
+        FramebufferError::from_status(GLenum(0x1234)),
+        Some(FramebufferError::Unknown(0x1234))
+    );
+}
+
+#[test]
+fn errors_describe_the_problem() {
+    let error = FramebufferError::TooManyColorAttachments {
+        requested: 9,
+        max: 8,
+    };
+    assert_eq!(
+        error.to_string(),
+        "9 color attachments were requested, but at most 8 are supported"
+    );
+    assert_eq!(
+        FramebufferError::Unknown(0x1234).to_string(),
+        "unknown framebuffer status 0x1234"
+    );
+}
@@ -0,0 +1,347 @@
+use std::path::Path;
+
+use glam::{Mat4, Vec3, Vec4};
+use image::GenericImageView;
+use opengl_chrno::{
+    geometry::{
+        gltf::{AlphaMode, GltfError, GltfScene, TextureRef},
+        MeshData,
+    },
+    learn_opengl::{TextureFilter, TextureWrap},
+};
+
+fn model_path(name: &str) -> String {
+    format!("{}/assets/models/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn assert_winding_matches_normals(data: &MeshData) {
+    for triangle in data.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| data.vertices[triangle[i] as usize]);
+        let face = (b.position - a.position).cross(c.position - a.position);
+        for vertex in [a, b, c] {
+            assert!(
+                vertex.normal.dot(face) > 0.0,
+                "{} against {}",
+                vertex.normal,
+                face
+            );
+        }
+    }
+}
+
+/// A document with a single primitive drawn with `mode`, its three positions
+/// all zero.
+fn single_primitive(mode: u32, extensions_required: &str) -> String {
+    format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "extensionsRequired": [{extensions_required}],
+            "extensionsUsed": [{extensions_required}],
+            "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "mode": {mode} }}] }}],
+            "accessors": [{{
+                "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                "min": [0, 0, 0], "max": [0, 0, 0]
+            }}],
+            "bufferViews": [{{ "buffer": 0, "byteLength": 36 }}],
+            "buffers": [{{
+                "byteLength": 36,
+                "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"
+            }}]
+        }}"#
+    )
+}
+
+/// A document with a triangle of zero positions and normals, indexed by
+/// `[0, 1, 7]` when `indexed` is set, and with `normals` of its three normals
+/// in the NORMAL attribute.
+fn triangle(normals: u32, indexed: bool) -> String {
+    let indices = if indexed { r#", "indices": 2"# } else { "" };
+    format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "meshes": [
+                {{ "primitives": [] }},
+                {{ "primitives": [
+                    {{ "attributes": {{ "POSITION": 0 }} }},
+                    {{ "attributes": {{ "POSITION": 0, "NORMAL": 1 }}{indices} }}
+                ] }}
+            ],
+            "accessors": [
+                {{
+                    "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                    "min": [0, 0, 0], "max": [0, 0, 0]
+                }},
+                {{ "bufferView": 1, "componentType": 5126, "count": {normals}, "type": "VEC3" }},
+                {{ "bufferView": 2, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+            ],
+            "bufferViews": [
+                {{ "buffer": 0, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 36, "byteLength": 36 }},
+                {{ "buffer": 0, "byteOffset": 72, "byteLength": 6 }}
+            ],
+            "buffers": [{{
+                "byteLength": 80,
+                "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABAAcAAAA="
+            }}]
+        }}"#
+    )
+}
+
+/// A document with the given nodes, the scene made of `roots`.
+fn hierarchy(nodes: &str, roots: &str) -> String {
+    format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "nodes": [{nodes}],
+            "scenes": [{{ "nodes": [{roots}] }}]
+        }}"#
+    )
+}
+
+#[test]
+fn node_hierarchy_and_transforms() {
+    let scene = GltfScene::load(model_path("scene.gltf")).unwrap();
+    let names: Vec<_> = scene
+        .nodes
+        .iter()
+        .map(|node| node.name.as_deref())
+        .collect();
+    assert_eq!(
+        names,
+        [Some("root"), Some("quad"), Some("strip"), Some("unused")]
+    );
+    assert_eq!(scene.roots, [0]);
+    assert_eq!(scene.nodes[0].children, [1, 2]);
+
+    let transforms = scene.world_transforms();
+    // Scaled by 2, turned to face up and lifted by the root
+    let corner = transforms[1].transform_point3(Vec3::new(0.5, 0.5, 0.0));
+    assert!(
+        corner.abs_diff_eq(Vec3::new(1.0, 1.0, -1.0), 1e-5),
+        "{}",
+        corner
+    );
+    let normal = transforms[1].transform_vector3(Vec3::Z).normalize();
+    assert!(normal.abs_diff_eq(Vec3::Y, 1e-5), "{}", normal);
+    // Given as a matrix instead of separate parts
+    assert!(transforms[2].abs_diff_eq(Mat4::from_translation(Vec3::new(3.0, 1.0, 0.0)), 1e-6));
+    assert_eq!(transforms[3], Mat4::IDENTITY);
+
+    let instances = scene.mesh_instances();
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0], (0, transforms[1]));
+    assert_eq!(instances[1], (1, transforms[2]));
+}
+
+#[test]
+fn pbr_materials_and_textures() {
+    let scene = GltfScene::load(model_path("scene.gltf")).unwrap();
+    let material = &scene.materials[0];
+    assert_eq!(material.name.as_deref(), Some("checker"));
+    assert_eq!(material.base_color, Vec4::new(1.0, 0.5, 0.5, 1.0));
+    assert_eq!(
+        material.base_color_texture,
+        Some(TextureRef {
+            texture: 0,
+            tex_coord: 0
+        })
+    );
+    assert_eq!((material.metallic, material.roughness), (0.25, 0.75));
+    assert_eq!(material.normal_texture, None);
+    // Multiplied by the emissive strength extension
+    assert_eq!(material.emissive, Vec3::new(2.0, 1.0, 0.0));
+    assert_eq!(material.alpha_mode, AlphaMode::Mask);
+    assert_eq!(material.alpha_cutoff, 0.25);
+    assert!(material.double_sided);
+
+    let texture = scene.textures[0];
+    assert_eq!(texture.image, 0);
+    assert_eq!(texture.wrap_s, TextureWrap::ClampToEdge);
+    assert_eq!(texture.wrap_t, TextureWrap::MirroredRepeat);
+    assert_eq!(texture.min_filter, TextureFilter::Nearest);
+    assert_eq!(texture.mag_filter, TextureFilter::Nearest);
+    assert!(texture.mipmaps);
+
+    let image = &scene.images[0];
+    assert_eq!(image.dimensions(), (2, 2));
+    assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(1, 0).0, [255, 255, 255, 255]);
+
+    // The strip has no material of its own
+    let strip = &scene.meshes[1].primitives[0];
+    assert_eq!(strip.material, None);
+    let default = scene.material(strip);
+    assert_eq!((default.metallic, default.roughness), (1.0, 1.0));
+    assert_eq!(default.base_color, Vec4::ONE);
+}
+
+#[test]
+fn unsupported_extensions_are_reported() {
+    let scene = GltfScene::load(model_path("scene.gltf")).unwrap();
+    assert_eq!(scene.unsupported_extensions, ["KHR_materials_clearcoat"]);
+
+    let document = single_primitive(
+        4,
+        r#""KHR_materials_emissive_strength", "KHR_draco_mesh_compression""#,
+    );
+    match GltfScene::parse(document.as_bytes(), Path::new(".")) {
+        Err(GltfError::UnsupportedExtensions(extensions)) => {
+            assert_eq!(extensions, ["KHR_draco_mesh_compression"])
+        }
+        other => panic!("expected unsupported extensions, got {:?}", other),
+    }
+}
+
+#[test]
+fn primitives_become_triangles() {
+    let scene = GltfScene::load(model_path("scene.gltf")).unwrap();
+
+    let quad = &scene.meshes[0].primitives[0].data;
+    assert_eq!(scene.meshes[0].name.as_deref(), Some("quad"));
+    assert_eq!((quad.vertices.len(), quad.indices.len()), (4, 6));
+    assert_winding_matches_normals(quad);
+    // `v` points down the image, so against the bitangent of cross(normal, tangent)
+    for vertex in &quad.vertices {
+        assert!(vertex
+            .tangent
+            .abs_diff_eq(Vec4::new(1.0, 0.0, 0.0, -1.0), 1e-5));
+    }
+
+    // A strip of two triangles without normals gets flat ones
+    let strip = &scene.meshes[1].primitives[0].data;
+    assert_eq!(strip.triangle_count(), 2);
+    assert_eq!(strip.vertices.len(), 6);
+    assert!(strip.vertices.iter().all(|vertex| vertex.normal == Vec3::Z));
+    assert_winding_matches_normals(strip);
+}
+
+#[test]
+fn external_and_binary_buffers() {
+    let external = GltfScene::load(model_path("fan.gltf")).unwrap();
+    let binary = GltfScene::load(model_path("fan.glb")).unwrap();
+    assert_eq!(external.meshes, binary.meshes);
+    assert_eq!(external.nodes, binary.nodes);
+    // No default scene, so the first one is used
+    assert_eq!(external.roots, [0]);
+
+    let fan = &external.meshes[0].primitives[0].data;
+    assert_eq!(fan.triangle_count(), 2);
+    assert_winding_matches_normals(fan);
+    assert!(fan.vertices.iter().all(|vertex| vertex.normal == Vec3::Y));
+
+    // The binary file carries its texture in the buffer, with the default sampler
+    assert!(external.images.is_empty());
+    assert_eq!(binary.images[0].dimensions(), (2, 2));
+    let texture = binary.textures[0];
+    assert_eq!(
+        (texture.wrap_s, texture.wrap_t),
+        (TextureWrap::Repeat, TextureWrap::Repeat)
+    );
+    assert_eq!(texture.min_filter, TextureFilter::Linear);
+    assert!(texture.mipmaps);
+    assert_eq!(binary.materials[0].metallic, 0.0);
+}
+
+#[test]
+fn import_errors() {
+    match GltfScene::parse(single_primitive(1, "").as_bytes(), Path::new(".")) {
+        Err(
+            error @ GltfError::UnsupportedMode {
+                mesh: 0,
+                primitive: 0,
+                ..
+            },
+        ) => {
+            assert_eq!(
+                error.to_string(),
+                "primitive 0 of mesh 0 is drawn as Lines, only triangles are supported"
+            );
+        }
+        other => panic!("expected an unsupported mode, got {:?}", other),
+    }
+
+    assert!(matches!(
+        GltfScene::parse(b"{ \"asset\": {} }", Path::new(".")),
+        Err(GltfError::Gltf(_))
+    ));
+
+    match GltfScene::load(model_path("missing.gltf")) {
+        Err(GltfError::Read { path, .. }) => assert!(path.ends_with("missing.gltf")),
+        other => panic!("expected a read error, got {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_primitives_are_rejected() {
+    assert!(GltfScene::parse(triangle(3, false).as_bytes(), Path::new(".")).is_ok());
+
+    match GltfScene::parse(triangle(3, true).as_bytes(), Path::new(".")) {
+        Err(
+            error @ GltfError::IndexOutOfRange {
+                mesh: 1,
+                primitive: 1,
+                index: 7,
+                vertices: 3,
+            },
+        ) => {
+            assert_eq!(
+                error.to_string(),
+                "primitive 1 of mesh 1 uses index 7, but has only 3 vertices"
+            );
+        }
+        other => panic!("expected an index out of range, got {:?}", other),
+    }
+
+    match GltfScene::parse(triangle(2, false).as_bytes(), Path::new(".")) {
+        Err(
+            error @ GltfError::AttributeCount {
+                mesh: 1,
+                primitive: 1,
+                attribute: "NORMAL",
+                count: 2,
+                positions: 3,
+            },
+        ) => {
+            assert_eq!(
+                error.to_string(),
+                "primitive 1 of mesh 1 has 2 NORMAL values for 3 positions"
+            );
+        }
+        other => panic!("expected a short attribute, got {:?}", other),
+    }
+}
+
+#[test]
+fn node_hierarchies_have_to_be_trees() {
+    let tree = hierarchy(
+        r#"{ "children": [1, 2] }, {}, { "children": [3] }, {}"#,
+        "0",
+    );
+    let scene = GltfScene::parse(tree.as_bytes(), Path::new(".")).unwrap();
+    assert_eq!(scene.world_transforms().len(), 4);
+
+    let cycle = hierarchy(r#"{ "children": [1] }, { "children": [0] }"#, "0");
+    match GltfScene::parse(cycle.as_bytes(), Path::new(".")) {
+        Err(GltfError::NodeCycle { node }) => assert!(node < 2, "{}", node),
+        other => panic!("expected a cycle, got {:?}", other),
+    }
+
+    // Nothing leads into the cycle
+    let island = hierarchy(r#"{}, { "children": [2] }, { "children": [1] }"#, "0");
+    assert!(matches!(
+        GltfScene::parse(island.as_bytes(), Path::new(".")),
+        Err(GltfError::NodeCycle { .. })
+    ));
+
+    let shared = hierarchy(r#"{ "children": [2] }, { "children": [2] }, {}"#, "0, 1");
+    assert!(matches!(
+        GltfScene::parse(shared.as_bytes(), Path::new(".")),
+        Err(GltfError::MultipleParents { node: 2 })
+    ));
+
+    let child_root = hierarchy(r#"{ "children": [1] }, {}"#, "0, 1");
+    assert!(matches!(
+        GltfScene::parse(child_root.as_bytes(), Path::new(".")),
+        Err(GltfError::MultipleParents { node: 1 })
+    ));
+}
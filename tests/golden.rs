@@ -0,0 +1,188 @@
+use std::{f32::consts::PI, fs, path::PathBuf};
+
+use gl33::{
+    global_loader::{glClear, glClearColor, glEnable},
+    GL_COLOR_BUFFER_BIT, GL_DEPTH_BUFFER_BIT, GL_DEPTH_TEST,
+};
+use glam::{Quat, Vec3};
+use image::{Rgba, RgbaImage};
+use opengl_chrno::{
+    camera::OrbitCamera,
+    geometry::primitives,
+    golden::{compare, GoldenError, GoldenImages, Tolerance},
+    learn_opengl::{Framebuffer, ProgramBuilder, Texture2D},
+    platform::Platform,
+    scene::{SceneGraph, Transform},
+};
+
+mod common;
+
+use common::{headless, scratch, GL};
+
+fn asset(name: &str) -> PathBuf {
+    PathBuf::from(format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), name))
+}
+
+fn goldens() -> GoldenImages {
+    GoldenImages::new(
+        format!("{}/tests/golden", env!("CARGO_MANIFEST_DIR")),
+        format!("{}/golden", env!("CARGO_TARGET_TMPDIR")),
+    )
+}
+
+fn checkerboard(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| {
+        if (x + y) % 2 == 0 {
+            Rgba([255, 255, 255, 255])
+        } else {
+            Rgba([0, 0, 0, 255])
+        }
+    })
+}
+
+/// The textured cubes of the demo at a fixed point in time, rendered at the
+/// size of the platform.
+fn render_cubes(platform: &Platform, time: f32) -> RgbaImage {
+    let gl = platform.gl();
+    let (width, height) = platform.size();
+    let program = ProgramBuilder::new()
+        .vertex(fs::read_to_string(asset("shaders/cube.vert")).unwrap())
+        .fragment(fs::read_to_string(asset("shaders/cube.frag")).unwrap())
+        .build(gl)
+        .unwrap_or_else(|e| panic!("{}", e));
+    let wall = Texture2D::from_image(gl, &image::open(asset("wall.jpg")).unwrap()).unwrap();
+    let face = Texture2D::from_image(gl, &image::open(asset("awesomeface.png")).unwrap()).unwrap();
+    let cube = primitives::cube(Vec3::ONE, 1).upload(gl).unwrap();
+    let framebuffer = Framebuffer::new(gl, width, height).unwrap_or_else(|e| panic!("{}", e));
+
+    let mut scene = SceneGraph::new();
+    let angle = -PI / 3.0 * time;
+    for position in [
+        Vec3::ZERO,
+        Vec3::new(1.5, 1.0, -2.0),
+        Vec3::new(-1.5, -0.5, -1.0),
+    ] {
+        let mut transform = Transform::from_translation(position);
+        transform.set_rotation(Quat::from_rotation_x(angle));
+        scene.add(transform, ());
+    }
+    scene.update();
+    let camera = OrbitCamera::new(Vec3::ZERO, 5.0);
+
+    framebuffer.bind();
+    program.use_program();
+    program.set_uniform("texture1", 0);
+    program.set_uniform("texture2", 1);
+    program.set_uniform("view", camera.view_matrix());
+    program.set_uniform(
+        "projection",
+        camera.projection_matrix(width as f32 / height as f32),
+    );
+    wall.bind_to_unit(0);
+    face.bind_to_unit(1);
+    unsafe {
+        glEnable(GL_DEPTH_TEST);
+        glClearColor(0.2, 0.3, 0.3, 1.0);
+        glClear(GL_COLOR_BUFFER_BIT | GL_DEPTH_BUFFER_BIT);
+    }
+    for (_, node) in &scene {
+        program.set_uniform("model", node.transform.world_matrix());
+        cube.draw();
+    }
+    Framebuffer::clear_binding();
+
+    framebuffer.read_pixels(0)
+}
+
+#[test]
+fn identical_images_have_no_differing_pixels() {
+    let image = checkerboard(8, 4);
+    let diff = compare(&image, &image, 0);
+    assert_eq!(diff.differing_pixels, 0);
+    assert_eq!(diff.total_pixels, 32);
+    assert_eq!(diff.max_channel_difference, 0);
+    assert_eq!(diff.differing_ratio(), 0.0);
+}
+
+#[test]
+fn channel_tolerance_decides_what_differs() {
+    let expected = checkerboard(4, 4);
+    let mut actual = expected.clone();
+    // A little off everywhere, and far off in two pixels
+    for pixel in actual.pixels_mut() {
+        pixel[1] = pixel[1].saturating_add(3);
+    }
+    actual.put_pixel(0, 0, Rgba([200, 255, 255, 255]));
+    actual.put_pixel(3, 2, Rgba([0, 0, 90, 255]));
+
+    let diff = compare(&actual, &expected, 3);
+    assert_eq!(diff.differing_pixels, 2);
+    assert_eq!(diff.max_channel_difference, 90);
+    assert_eq!(diff.differing_ratio(), 2.0 / 16.0);
+    // Differing pixels show up red in the diff
+    assert_eq!(diff.image.get_pixel(3, 2).0[1..3], [0, 0]);
+    assert!(diff.image.get_pixel(3, 2).0[0] >= 128);
+
+    // White can't go up, so only the black pixels are off by 3
+    assert_eq!(compare(&actual, &expected, 2).differing_pixels, 9);
+}
+
+#[test]
+fn blessed_goldens_are_checked_against() {
+    let dir = scratch("blessed_goldens");
+    let goldens = GoldenImages::new(dir.join("golden"), dir.join("out")).tolerance(Tolerance {
+        channel: 0,
+        max_differing_ratio: 0.1,
+    });
+    let image = checkerboard(4, 4);
+
+    match goldens.clone().bless(false).check("board", &image) {
+        Err(GoldenError::Missing { actual, .. }) => assert!(actual.exists()),
+        result => panic!("expected a missing golden, got {:?}", result),
+    }
+    goldens.clone().bless(true).check("board", &image).unwrap();
+    assert!(goldens.golden_path("board").exists());
+
+    let goldens = goldens.bless(false);
+    goldens.check("board", &image).unwrap();
+
+    // One pixel of 16 is within the ratio, two are not
+    let mut changed = image.clone();
+    changed.put_pixel(1, 1, Rgba([10, 20, 30, 255]));
+    goldens.check("board", &changed).unwrap();
+    changed.put_pixel(2, 2, Rgba([10, 20, 30, 255]));
+    match goldens.check("board", &changed) {
+        Err(GoldenError::Mismatch {
+            differing_pixels: 2,
+            output_dir,
+            ..
+        }) => {
+            for kind in ["actual", "expected", "diff"] {
+                assert!(output_dir.join(format!("board-{}.png", kind)).exists());
+            }
+        }
+        result => panic!("expected a mismatch, got {:?}", result),
+    }
+
+    assert!(matches!(
+        goldens.check("board", &checkerboard(4, 2)),
+        Err(GoldenError::SizeMismatch {
+            expected: (4, 4),
+            actual: (4, 2),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn cubes_match_golden() {
+    let _gl = GL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(platform) = headless("golden", 320, 240) else {
+        return;
+    };
+
+    let image = render_cubes(&platform, 1.5);
+    goldens()
+        .check("cubes", &image)
+        .unwrap_or_else(|e| panic!("{}", e));
+}
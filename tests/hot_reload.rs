@@ -0,0 +1,144 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io,
+    rc::Rc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use opengl_chrno::learn_opengl::{
+    Preprocessor, ReloadableProgram, ShaderError, ShaderFileSystem, ShaderType,
+};
+
+mod common;
+
+use common::{headless, GL};
+
+/// An in-memory file system whose files can be edited while a program watches
+/// them. Every edit counts as one second later than the last one.
+#[derive(Clone, Default)]
+struct Files(Rc<RefCell<HashMap<String, (String, u64)>>>);
+
+impl Files {
+    fn write(&self, path: &str, text: &str) {
+        let mut files = self.0.borrow_mut();
+        let version = files.values().map(|&(_, version)| version).max();
+        let version = version.map_or(0, |version| version + 1);
+        files.insert(path.to_string(), (text.to_string(), version));
+    }
+}
+
+impl ShaderFileSystem for Files {
+    fn read(&self, path: &str) -> io::Result<String> {
+        self.0
+            .borrow()
+            .get(path)
+            .map(|(text, _)| text.clone())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+    }
+
+    fn modified(&self, path: &str) -> Option<SystemTime> {
+        self.0
+            .borrow()
+            .get(path)
+            .map(|&(_, version)| UNIX_EPOCH + Duration::from_secs(version))
+    }
+}
+
+fn triangle_shader() -> Files {
+    let files = Files::default();
+    files.write(
+        "shader.vert",
+        "#version 330 core\n\
+         #include \"common.glsl\"\n\
+         layout (location = 0) in vec3 aPos;\n\
+         void main() { gl_Position = vec4(aPos * SCALE, 1.0); }\n",
+    );
+    files.write("common.glsl", "const float SCALE = 1.0;\n");
+    files.write(
+        "shader.frag",
+        "#version 330 core\n\
+         out vec4 FragColor;\n\
+         void main() { FragColor = vec4(1.0); }\n",
+    );
+    files
+}
+
+const STAGES: [(ShaderType, &str); 2] = [
+    (ShaderType::Vertex, "shader.vert"),
+    (ShaderType::Fragment, "shader.frag"),
+];
+
+#[test]
+fn edited_includes_are_reloaded() {
+    let _gl = GL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(platform) = headless("hot reload", 64, 64) else {
+        return;
+    };
+    let files = triangle_shader();
+    let mut program =
+        ReloadableProgram::new(platform.gl(), Preprocessor::new(files.clone()), &STAGES)
+            .unwrap_or_else(|e| panic!("{}", e));
+    assert!(!program.changed());
+    assert!(!program.poll());
+
+    files.write("common.glsl", "const float SCALE = 0.5;\n");
+    assert!(program.changed());
+    assert!(program.poll());
+    assert!(!program.changed());
+}
+
+#[test]
+fn includes_reached_by_a_failed_reload_are_watched() {
+    let _gl = GL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(platform) = headless("hot reload", 64, 64) else {
+        return;
+    };
+    let files = triangle_shader();
+    let mut program =
+        ReloadableProgram::new(platform.gl(), Preprocessor::new(files.clone()), &STAGES)
+            .unwrap_or_else(|e| panic!("{}", e));
+    let good = program.program().id();
+
+    files.write(
+        "shader.frag",
+        "#version 330 core\n\
+         #include \"color.glsl\"\n\
+         out vec4 FragColor;\n\
+         void main() { FragColor = COLOR; }\n",
+    );
+    assert!(matches!(program.reload(), Err(ShaderError::Preprocess(_))));
+    assert_eq!(program.program().id(), good);
+    assert!(!program.changed());
+
+    // The missing include is watched now, and so are the files of the last
+    // good program.
+    files.write("color.glsl", "const vec4 COLOR = vec4(1.0);\n");
+    assert!(program.changed());
+    assert!(program.poll());
+    assert!(!program.changed());
+
+    files.write("common.glsl", "const float SCALE = 2.0;\n");
+    assert!(program.changed());
+    assert!(program.poll());
+}
+
+#[test]
+fn files_are_still_watched_after_a_failed_compile() {
+    let _gl = GL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(platform) = headless("hot reload", 64, 64) else {
+        return;
+    };
+    let files = triangle_shader();
+    let mut program =
+        ReloadableProgram::new(platform.gl(), Preprocessor::new(files.clone()), &STAGES)
+            .unwrap_or_else(|e| panic!("{}", e));
+
+    files.write("common.glsl", "const float SCALE = ;\n");
+    assert!(!program.poll());
+    assert!(!program.changed());
+
+    files.write("common.glsl", "const float SCALE = 3.0;\n");
+    assert!(program.changed());
+    assert!(program.poll());
+}
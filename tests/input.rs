@@ -0,0 +1,336 @@
+use glam::Vec2;
+use opengl_chrno::input::{
+    ActionMap, AxisSource, BindingError, Button, Deadzones, GamepadAxis, GamepadButton, Input,
+    InputEvent, MouseButton, Scancode,
+};
+
+fn key(scancode: Scancode, pressed: bool) -> InputEvent {
+    InputEvent::Key {
+        scancode,
+        pressed,
+        repeat: false,
+    }
+}
+
+fn pad_button(id: i32, button: GamepadButton, pressed: bool) -> InputEvent {
+    InputEvent::GamepadButton {
+        id,
+        button,
+        pressed,
+    }
+}
+
+fn pad_axis(id: i32, axis: GamepadAxis, value: i16) -> InputEvent {
+    InputEvent::GamepadAxis { id, axis, value }
+}
+
+fn input(bindings: &str) -> Input {
+    Input::new(ActionMap::parse(bindings).unwrap())
+}
+
+#[test]
+fn keys_are_held_across_frames() {
+    let mut input = input("jump = Space");
+
+    input.begin_frame();
+    input.handle_event(key(Scancode::SPACE, true));
+    assert!(input.was_pressed(Scancode::SPACE) && input.is_held(Scancode::SPACE));
+    assert!(input.action_pressed("jump") && input.action_held("jump"));
+
+    input.begin_frame();
+    input.handle_event(InputEvent::Key {
+        scancode: Scancode::SPACE,
+        pressed: true,
+        repeat: true,
+    });
+    assert!(!input.was_pressed(Scancode::SPACE) && input.is_held(Scancode::SPACE));
+    assert!(!input.action_pressed("jump") && input.action_held("jump"));
+
+    input.begin_frame();
+    input.handle_event(key(Scancode::SPACE, false));
+    assert!(input.was_released(Scancode::SPACE) && !input.is_held(Scancode::SPACE));
+    assert!(input.action_released("jump") && !input.action_held("jump"));
+}
+
+#[test]
+fn several_keys_are_held_together() {
+    let mut input = input("axis move_forward = S/W\naxis move_right = A/D");
+    input.handle_event(key(Scancode::W, true));
+    input.handle_event(key(Scancode::D, true));
+    assert_eq!(input.axis("move_forward"), 1.0);
+    assert_eq!(input.axis("move_right"), 1.0);
+
+    input.handle_event(key(Scancode::S, true));
+    assert_eq!(input.axis("move_forward"), 0.0);
+}
+
+#[test]
+fn action_with_several_buttons() {
+    let mut input = input("fire = MouseLeft, PadA");
+    input.handle_event(InputEvent::MouseButton {
+        button: MouseButton::Left,
+        pressed: true,
+    });
+    input.begin_frame();
+    input.handle_event(pad_button(0, GamepadButton::A, true));
+    assert!(!input.action_pressed("fire"), "already held by the mouse");
+
+    input.begin_frame();
+    input.handle_event(InputEvent::MouseButton {
+        button: MouseButton::Left,
+        pressed: false,
+    });
+    assert!(!input.action_released("fire"), "still held by the pad");
+    assert!(input.action_held("fire"));
+}
+
+#[test]
+fn parses_bindings() {
+    let map = ActionMap::parse(
+        "# comment\n\
+         move_forward = W, up # trailing comment\n\
+         move_forward = MouseX1\n\
+         fire = PadTriggerRight, PadLeftX-\n\
+         axis move_up = LCtrl/Space, -PadTriggerLeft\n\
+         deadzone stick = 0.3\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        map.buttons("move_forward"),
+        [
+            Button::Key(Scancode::W),
+            Button::Key(Scancode::UP),
+            Button::Mouse(MouseButton::X1)
+        ]
+    );
+    assert_eq!(
+        map.buttons("fire"),
+        [
+            Button::GamepadAxis {
+                axis: GamepadAxis::TriggerRight,
+                positive: true
+            },
+            Button::GamepadAxis {
+                axis: GamepadAxis::LeftX,
+                positive: false
+            },
+        ]
+    );
+    assert_eq!(
+        map.axis_sources("move_up"),
+        [
+            AxisSource::Buttons {
+                negative: Button::Key(Scancode::LCTRL),
+                positive: Button::Key(Scancode::SPACE),
+            },
+            AxisSource::Gamepad {
+                axis: GamepadAxis::TriggerLeft,
+                inverted: true
+            },
+        ]
+    );
+    assert_eq!(map.deadzones().stick, 0.3);
+    assert!(map.buttons("unknown").is_empty());
+
+    assert_eq!(ActionMap::parse(&map.to_string()).unwrap(), map);
+}
+
+#[test]
+fn key_names_round_trip() {
+    for code in 4..=69 {
+        let scancode = Scancode(code);
+        if let Some(name) = scancode.name() {
+            assert_eq!(Scancode::from_name(&name), Some(scancode), "{}", name);
+        }
+    }
+    assert_eq!(Scancode::from_name("f12"), Some(Scancode(69)));
+    assert_eq!(Scancode::from_name("F13"), None);
+}
+
+#[test]
+fn reports_binding_errors() {
+    assert!(matches!(
+        ActionMap::parse("jump Space"),
+        Err(BindingError::Malformed { line: 1 })
+    ));
+    assert!(matches!(
+        ActionMap::parse("\njump = Spcae"),
+        Err(BindingError::UnknownButton { line: 2, ref name }) if name == "Spcae"
+    ));
+    assert!(matches!(
+        ActionMap::parse("fire = PadLeftX"),
+        Err(BindingError::UnknownButton { line: 1, .. })
+    ));
+    assert!(matches!(
+        ActionMap::parse("deadzone stick = 1.5"),
+        Err(BindingError::InvalidDeadzone { line: 1, .. })
+    ));
+    assert!(matches!(
+        ActionMap::load("does/not/exist.cfg"),
+        Err(BindingError::Read { .. })
+    ));
+}
+
+#[test]
+fn press_threshold_has_to_be_above_zero() {
+    for value in ["0", "0.0", "-0.5", "1", "half"] {
+        match ActionMap::parse(&format!("deadzone press = {}", value)) {
+            Err(BindingError::InvalidPressThreshold {
+                line: 1,
+                value: bad,
+            }) => {
+                assert_eq!(bad, value)
+            }
+            result => panic!("{} was accepted: {:?}", value, result),
+        }
+    }
+    // The stick and trigger deadzones may be turned off
+    let map = ActionMap::parse("deadzone press = 0.25\ndeadzone stick = 0").unwrap();
+    assert_eq!(map.deadzones().press, 0.25);
+    assert_eq!(map.deadzones().stick, 0.0);
+}
+
+#[test]
+fn mouse_motion_and_wheel_add_up_within_a_frame() {
+    let mut input = input("");
+    input.handle_event(InputEvent::MouseMotion { x: 3.0, y: -1.0 });
+    input.handle_event(InputEvent::MouseMotion { x: 2.0, y: 4.0 });
+    input.handle_event(InputEvent::MouseWheel { x: 0.0, y: 1.0 });
+    input.handle_event(InputEvent::MouseWheel { x: 0.0, y: 1.0 });
+    assert_eq!(input.mouse_motion(), Vec2::new(5.0, 3.0));
+    assert_eq!(input.wheel(), Vec2::new(0.0, 2.0));
+
+    input.begin_frame();
+    assert_eq!(input.mouse_motion(), Vec2::ZERO);
+    assert_eq!(input.wheel(), Vec2::ZERO);
+}
+
+#[test]
+fn unbound_buttons_are_still_tracked() {
+    let mut input = input("jump = Space");
+    input.handle_event(InputEvent::MouseButton {
+        button: MouseButton::Right,
+        pressed: true,
+    });
+    assert!(input.was_pressed(MouseButton::Right) && input.is_held(MouseButton::Right));
+    assert!(!input.action_held("jump"));
+    assert!(!input.action_held("not bound"));
+    assert_eq!(input.axis("not bound"), 0.0);
+}
+
+#[test]
+fn gamepad_hot_plug() {
+    let mut input = input("jump = Space, PadA");
+    input.handle_event(InputEvent::GamepadAdded { id: 3 });
+    input.handle_event(InputEvent::GamepadAdded { id: 7 });
+    let mut ids: Vec<_> = input.gamepads().collect();
+    ids.sort();
+    assert_eq!(ids, [3, 7]);
+
+    input.handle_event(pad_button(3, GamepadButton::A, true));
+    input.handle_event(pad_button(7, GamepadButton::A, true));
+    input.handle_event(InputEvent::GamepadRemoved { id: 3 });
+    assert!(input.action_held("jump"), "still held on the other pad");
+
+    input.begin_frame();
+    input.handle_event(InputEvent::GamepadRemoved { id: 7 });
+    assert!(input.action_released("jump"));
+    assert_eq!(input.gamepads().count(), 0);
+}
+
+#[test]
+fn unplugging_recenters_axes() {
+    let mut input = input("axis move_right = A/D, PadLeftX");
+    input.handle_event(InputEvent::GamepadAdded { id: 0 });
+    input.handle_event(pad_axis(0, GamepadAxis::LeftX, i16::MAX));
+    assert_eq!(input.axis("move_right"), 1.0);
+
+    input.handle_event(InputEvent::GamepadRemoved { id: 0 });
+    assert_eq!(input.axis("move_right"), 0.0);
+    assert!(!input.is_held(Button::GamepadAxis {
+        axis: GamepadAxis::LeftX,
+        positive: true
+    }));
+}
+
+#[test]
+fn stick_deadzone_is_radial() {
+    let mut input = input("axis move_right = PadLeftX\naxis move_forward = -PadLeftY");
+    let deadzones = input.actions().deadzones();
+    input.actions_mut().set_deadzones(Deadzones {
+        stick: 0.25,
+        ..deadzones
+    });
+
+    input.handle_event(pad_axis(0, GamepadAxis::LeftX, 4000));
+    input.handle_event(pad_axis(0, GamepadAxis::LeftY, -4000));
+    assert_eq!(input.axis("move_right"), 0.0);
+    assert_eq!(input.axis("move_forward"), 0.0);
+
+    // Inside the deadzone on each axis alone, but past it together
+    input.handle_event(pad_axis(0, GamepadAxis::LeftX, 6000));
+    input.handle_event(pad_axis(0, GamepadAxis::LeftY, -6000));
+    assert!(input.axis("move_right") > 0.0);
+    assert!(input.axis("move_forward") > 0.0);
+
+    // Diagonal past the deadzone keeps its direction
+    input.handle_event(pad_axis(0, GamepadAxis::LeftX, 20000));
+    input.handle_event(pad_axis(0, GamepadAxis::LeftY, -20000));
+    let (x, y) = (input.axis("move_right"), input.axis("move_forward"));
+    assert!(x > 0.0 && (x - y).abs() < 1e-4);
+
+    // The edge of the deadzone reads 0 and full travel reads 1
+    input.handle_event(pad_axis(0, GamepadAxis::LeftY, 0));
+    input.handle_event(pad_axis(0, GamepadAxis::LeftX, (0.26 * 32767.0) as i16));
+    assert!(input.axis("move_right") < 0.02);
+    input.handle_event(pad_axis(0, GamepadAxis::LeftX, i16::MIN));
+    assert_eq!(input.axis("move_right"), -1.0);
+}
+
+#[test]
+fn triggers_as_buttons_and_axes() {
+    let mut input = input(
+        "fire = PadTriggerRight\n\
+         axis move_up = -PadTriggerLeft, PadTriggerRight\n\
+         deadzone trigger = 0.1\n\
+         deadzone press = 0.5",
+    );
+
+    input.handle_event(pad_axis(1, GamepadAxis::TriggerRight, 3000));
+    assert_eq!(input.axis("move_up"), 0.0);
+    assert!(!input.action_held("fire"));
+
+    input.begin_frame();
+    input.handle_event(pad_axis(1, GamepadAxis::TriggerRight, 16000));
+    assert!(input.axis("move_up") > 0.0);
+    assert!(
+        !input.action_held("fire"),
+        "not past the press threshold yet"
+    );
+
+    input.handle_event(pad_axis(1, GamepadAxis::TriggerRight, 30000));
+    assert!(input.action_pressed("fire"));
+
+    input.handle_event(pad_axis(1, GamepadAxis::TriggerLeft, i16::MAX));
+    assert!(input.axis("move_up") < 0.1);
+
+    input.begin_frame();
+    input.handle_event(pad_axis(1, GamepadAxis::TriggerRight, 0));
+    assert!(input.action_released("fire"));
+    assert_eq!(input.axis("move_up"), -1.0);
+}
+
+#[test]
+fn keyboard_and_pad_share_an_axis() {
+    let mut input = input("axis move_forward = S/W, -PadLeftY");
+    input.handle_event(pad_axis(0, GamepadAxis::LeftY, i16::MIN));
+    assert_eq!(input.axis("move_forward"), 1.0);
+
+    input.handle_event(key(Scancode::S, true));
+    assert!(input.axis("move_forward").abs() < 1e-6);
+
+    input.handle_event(key(Scancode::W, true));
+    input.handle_event(key(Scancode::S, false));
+    assert_eq!(input.axis("move_forward"), 1.0, "clamped");
+}
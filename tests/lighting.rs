@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use glam::Vec3;
+use opengl_chrno::{
+    learn_opengl::{DirectoryFileSystem, Preprocessor},
+    lighting::{
+        Attenuation, LightColor, LightLimits, LightUniform, Lights, PointLight, ShadingModel,
+        SpotLight, TooManyLights,
+    },
+};
+
+fn shaders() -> DirectoryFileSystem {
+    DirectoryFileSystem::new(format!("{}/assets/shaders", env!("CARGO_MANIFEST_DIR")))
+}
+
+#[test]
+fn limits_size_the_uniform_arrays() {
+    let limits = LightLimits {
+        directional: 2,
+        point: 8,
+        spot: 3,
+    };
+    let source = limits
+        .define(Preprocessor::new(shaders()))
+        .process("lit.frag")
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    let lines: Vec<_> = source.code.lines().collect();
+    assert_eq!(lines[0], "#version 330 core");
+    for define in [
+        "#define MAX_DIRECTIONAL_LIGHTS 2",
+        "#define MAX_POINT_LIGHTS 8",
+        "#define MAX_SPOT_LIGHTS 3",
+    ] {
+        assert!(lines[1..4].contains(&define), "{} is missing", define);
+    }
+    // The include is pulled in, and its defaults give way to the defines
+    let names: Vec<_> = source.files.iter().map(|file| file.name.as_str()).collect();
+    assert_eq!(names, ["lit.frag", "lighting.glsl"]);
+    assert!(source
+        .code
+        .contains("uniform PointLight point_lights[MAX_POINT_LIGHTS];"));
+    assert!(source.code.contains("#ifndef MAX_POINT_LIGHTS"));
+}
+
+#[test]
+fn any_program_can_include_the_lighting() {
+    let mut files = HashMap::new();
+    files.insert(
+        "lighting.glsl".to_string(),
+        std::fs::read_to_string(format!(
+            "{}/assets/shaders/lighting.glsl",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap(),
+    );
+    files.insert(
+        "toon.frag".to_string(),
+        "#version 330 core\n#include \"lighting.glsl\"\n#include \"lighting.glsl\"\n\
+         void main() {}\n"
+            .to_string(),
+    );
+
+    let source = LightLimits::default()
+        .define(Preprocessor::new(files))
+        .process("toon.frag")
+        .unwrap_or_else(|e| panic!("{}", e));
+    // Only included once
+    assert_eq!(source.code.matches("vec3 compute_lighting(").count(), 1);
+    assert_eq!(source.files.len(), 2);
+}
+
+#[test]
+fn attenuation_fades_with_distance() {
+    let attenuation = Attenuation::default();
+    assert_eq!(attenuation.at(0.0), 1.0);
+    let near = attenuation.at(1.0);
+    let far = attenuation.at(10.0);
+    assert!(near < 1.0 && far < near);
+    assert!((near - 1.0 / 1.122).abs() < 1e-6);
+
+    let constant = Attenuation {
+        constant: 2.0,
+        linear: 0.0,
+        quadratic: 0.0,
+    };
+    assert_eq!(constant.at(100.0), 0.5);
+}
+
+#[test]
+fn light_colors_and_defaults() {
+    let color = LightColor::new(Vec3::new(1.0, 0.5, 0.0));
+    assert_eq!(color.diffuse, Vec3::new(1.0, 0.5, 0.0));
+    assert_eq!(color.specular, color.diffuse);
+    assert!(color.ambient.abs_diff_eq(Vec3::new(0.1, 0.05, 0.0), 1e-6));
+
+    let lights = Lights::new();
+    assert!(lights.directional.is_empty() && lights.point.is_empty() && lights.spot.is_empty());
+    assert_eq!(lights.shading, ShadingModel::BlinnPhong);
+}
+
+#[test]
+fn too_many_lights_names_the_kind() {
+    let error = TooManyLights {
+        kind: "point",
+        count: 5,
+        max: 4,
+    };
+    assert_eq!(
+        error.to_string(),
+        "5 point lights, but there is only room for 4"
+    );
+}
+
+fn point_light(x: f32) -> PointLight {
+    PointLight {
+        position: Vec3::new(x, 0.0, 0.0),
+        color: LightColor::new(Vec3::ONE),
+        attenuation: Attenuation::default(),
+    }
+}
+
+#[test]
+fn zero_limits_still_leave_room_for_one_light() {
+    let limits = LightLimits {
+        directional: 0,
+        point: 0,
+        spot: 0,
+    };
+    let source = limits
+        .define(Preprocessor::new(shaders()))
+        .process("lit.frag")
+        .unwrap_or_else(|e| panic!("{}", e));
+    for define in [
+        "#define MAX_DIRECTIONAL_LIGHTS 1",
+        "#define MAX_POINT_LIGHTS 1",
+        "#define MAX_SPOT_LIGHTS 1",
+    ] {
+        assert!(source.code.contains(define), "{} is missing", define);
+    }
+
+    // But no lights fit
+    let mut lights = Lights::new();
+    assert!(lights.uniforms(&limits).is_ok());
+    lights.point.push(point_light(0.0));
+    assert_eq!(
+        lights.uniforms(&limits),
+        Err(TooManyLights {
+            kind: "point",
+            count: 1,
+            max: 0
+        })
+    );
+}
+
+#[test]
+fn too_many_lights_are_rejected_before_any_uniform() {
+    let limits = LightLimits::default();
+    let mut lights = Lights::new();
+    // Spot lights are checked last, so every other uniform could have been listed
+    lights.point.push(point_light(1.0));
+    lights.spot = vec![
+        SpotLight {
+            position: Vec3::ZERO,
+            direction: Vec3::NEG_Z,
+            inner_cutoff: 0.1,
+            outer_cutoff: 0.2,
+            color: LightColor::new(Vec3::ONE),
+            attenuation: Attenuation::default(),
+        };
+        limits.spot + 1
+    ];
+    assert_eq!(
+        lights.uniforms(&limits),
+        Err(TooManyLights {
+            kind: "spot",
+            count: 3,
+            max: 2
+        })
+    );
+}
+
+#[test]
+fn uniforms_cover_every_light() {
+    let mut lights = Lights::new();
+    lights.shading = ShadingModel::Phong;
+    lights.point = vec![point_light(1.0), point_light(2.0)];
+    let uniforms = lights.uniforms(&LightLimits::default()).unwrap();
+    let value = |name: &str| {
+        uniforms
+            .iter()
+            .find(|(uniform, _)| uniform == name)
+            .map(|&(_, value)| value)
+    };
+
+    assert_eq!(value("blinn_phong"), Some(LightUniform::Bool(false)));
+    assert_eq!(value("point_light_count"), Some(LightUniform::Int(2)));
+    assert_eq!(value("spot_light_count"), Some(LightUniform::Int(0)));
+    assert_eq!(
+        value("point_lights[1].position"),
+        Some(LightUniform::Vec3(Vec3::new(2.0, 0.0, 0.0)))
+    );
+    assert_eq!(
+        value("point_lights[1].quadratic"),
+        Some(LightUniform::Float(0.032))
+    );
+    assert_eq!(value("point_lights[2].position"), None);
+}
+
+#[test]
+fn spot_cutoffs_are_uploaded_as_cosines() {
+    let mut lights = Lights::new();
+    lights.spot.push(SpotLight {
+        position: Vec3::ZERO,
+        direction: Vec3::new(0.0, 0.0, -2.0),
+        inner_cutoff: 12.5f32.to_radians(),
+        outer_cutoff: 60f32.to_radians(),
+        color: LightColor::new(Vec3::ONE),
+        attenuation: Attenuation::default(),
+    });
+    let uniforms = lights.uniforms(&LightLimits::default()).unwrap();
+    let value = |name: &str| {
+        uniforms
+            .iter()
+            .find(|(uniform, _)| uniform == name)
+            .map(|&(_, value)| value)
+            .unwrap()
+    };
+
+    let LightUniform::Float(inner) = value("spot_lights[0].inner_cutoff") else {
+        panic!("the inner cutoff is a float");
+    };
+    assert!((inner - 12.5f32.to_radians().cos()).abs() < 1e-6);
+    let LightUniform::Float(outer) = value("spot_lights[0].outer_cutoff") else {
+        panic!("the outer cutoff is a float");
+    };
+    assert!((outer - 0.5).abs() < 1e-6);
+    // The direction is normalized too
+    assert_eq!(
+        value("spot_lights[0].direction"),
+        LightUniform::Vec3(Vec3::NEG_Z)
+    );
+}
@@ -0,0 +1,176 @@
+use glam::Vec3;
+use opengl_chrno::geometry::obj::{ObjError, ObjErrorKind, ObjModel};
+
+fn model_path(name: &str) -> String {
+    format!("{}/assets/models/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn parse_error(text: &str) -> (usize, ObjErrorKind) {
+    match ObjModel::parse(text, "test.obj") {
+        Err(ObjError::Parse { file, line, kind }) => {
+            assert_eq!(file, "test.obj");
+            (line, kind)
+        }
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn groups_and_materials_split_meshes() {
+    let model = ObjModel::load(model_path("cube.obj")).unwrap();
+    assert_eq!(model.material_libraries, ["cube.mtl"]);
+
+    let names: Vec<_> = model
+        .meshes
+        .iter()
+        .map(|mesh| (mesh.name.as_str(), mesh.material.as_deref()))
+        .collect();
+    assert_eq!(names, [("body", Some("grey")), ("top", Some("red"))]);
+    assert_eq!(model.meshes[0].data.triangle_count(), 10);
+    assert_eq!(model.meshes[1].data.triangle_count(), 2);
+
+    let grey = model.mesh_material(&model.meshes[0]).unwrap();
+    assert_eq!(grey.diffuse, Vec3::splat(0.5));
+    assert_eq!(grey.shininess, 16.0);
+    assert_eq!(grey.diffuse_map.as_deref(), Some("container.jpg"));
+
+    let red = model.mesh_material(&model.meshes[1]).unwrap();
+    assert_eq!(red.diffuse, Vec3::new(0.8, 0.1, 0.1));
+    assert_eq!(red.opacity, 0.5);
+    assert_eq!(red.normal_map.as_deref(), Some("bricks_normal.png"));
+    assert_eq!(red.ambient, Vec3::ZERO);
+}
+
+#[test]
+fn shared_corners_are_deduplicated() {
+    let model = ObjModel::load(model_path("cube.obj")).unwrap();
+    // Every face has its own normal, so only corners within a face are shared
+    for mesh in &model.meshes {
+        assert_eq!(mesh.data.vertices.len(), mesh.data.triangle_count() * 2);
+    }
+
+    let hexagon = ObjModel::load(model_path("hexagon.obj")).unwrap();
+    let data = &hexagon.meshes[0].data;
+    assert_eq!(hexagon.meshes[0].name, "");
+    assert_eq!(hexagon.meshes[0].material, None);
+    assert_eq!(data.vertices.len(), 6);
+    assert_eq!(data.triangle_count(), 4);
+    // A fan around the first corner
+    for (i, triangle) in data.indices.chunks_exact(3).enumerate() {
+        assert_eq!(triangle, [0, i as u32 + 1, i as u32 + 2]);
+    }
+}
+
+#[test]
+fn faces_wind_like_their_normals() {
+    let model = ObjModel::load(model_path("cube.obj")).unwrap();
+    let mut volume = 0.0;
+    for mesh in &model.meshes {
+        for triangle in mesh.data.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.data.vertices[triangle[i] as usize]);
+            let face = (b.position - a.position).cross(c.position - a.position);
+            assert!(a.normal.dot(face) > 0.0);
+            volume += a.position.dot(b.position.cross(c.position)) / 6.0;
+        }
+    }
+    assert!((volume - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn missing_normals_are_generated() {
+    let model = ObjModel::load(model_path("pyramid.obj")).unwrap();
+    assert_eq!(model.meshes.len(), 1);
+    let data = &model.meshes[0].data;
+    assert_eq!(model.meshes[0].name, "pyramid");
+    assert_eq!(data.triangle_count(), 6);
+    // No texture coordinates or normals, so one vertex per position
+    assert_eq!(data.vertices.len(), 5);
+
+    for vertex in &data.vertices {
+        assert!((vertex.normal.length() - 1.0).abs() < 1e-5);
+        assert!(vertex.tangent.truncate().dot(vertex.normal).abs() < 1e-5);
+        if vertex.position.y > 0.0 {
+            assert!(vertex.normal.abs_diff_eq(Vec3::Y, 1e-5));
+        } else {
+            // Corners are shared by the base and the sides, so they point
+            // out between them
+            let outwards = vertex.position.normalize();
+            assert!(vertex.normal.dot(outwards) > 0.5, "{}", vertex.normal);
+        }
+    }
+}
+
+#[test]
+fn indices_resolve_relative_to_the_end() {
+    let text = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nv 0 0 1\nf -4 -2 -3\n";
+    let model = ObjModel::parse(text, "test.obj").unwrap();
+    let data = &model.meshes[0].data;
+    let positions: Vec<_> = data
+        .indices
+        .iter()
+        .map(|&index| data.vertices[index as usize].position)
+        .collect();
+    assert_eq!(
+        positions,
+        [Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::ZERO, Vec3::Y, Vec3::X]
+    );
+}
+
+#[test]
+fn errors_name_the_line() {
+    assert_eq!(
+        parse_error("v 0 0 0\nv 1 0\n"),
+        (
+            2,
+            ObjErrorKind::MissingValues {
+                statement: "v".to_string(),
+                expected: 3
+            }
+        )
+    );
+    assert_eq!(
+        parse_error("# comment\n\nv 0 zero 0\n"),
+        (3, ObjErrorKind::InvalidNumber("zero".to_string()))
+    );
+    assert_eq!(
+        parse_error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"),
+        (4, ObjErrorKind::IndexOutOfRange { index: 4, count: 3 })
+    );
+    assert_eq!(
+        parse_error("v 0 0 0\nf -2 1 1\n"),
+        (
+            2,
+            ObjErrorKind::IndexOutOfRange {
+                index: -2,
+                count: 1
+            }
+        )
+    );
+    assert_eq!(
+        parse_error("v 0 0 0\nv 1 0 0\nf 1 2\n"),
+        (3, ObjErrorKind::FaceTooSmall(2))
+    );
+    assert_eq!(
+        parse_error("v 0 0 0\nf 1/1/1/1 1 1\n"),
+        (2, ObjErrorKind::InvalidFaceVertex("1/1/1/1".to_string()))
+    );
+    // A texture coordinate that was never defined
+    assert_eq!(
+        parse_error("v 0 0 0\nf 1/1 1/1 1/1\n"),
+        (2, ObjErrorKind::IndexOutOfRange { index: 1, count: 0 })
+    );
+
+    let error = ObjModel::parse("v 0 0 0\nf 0 1 1\n", "bad.obj").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "bad.obj:2: index 0 is out of range, there are 1 so far"
+    );
+}
+
+#[test]
+fn missing_files_are_read_errors() {
+    match ObjModel::load(model_path("missing.obj")) {
+        Err(ObjError::Read { path, .. }) => assert!(path.ends_with("missing.obj")),
+        other => panic!("expected a read error, got {:?}", other),
+    }
+}
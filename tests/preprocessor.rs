@@ -0,0 +1,157 @@
+use std::{collections::HashMap, io};
+
+use opengl_chrno::learn_opengl::{
+    PreprocessError, Preprocessor, ShaderError, ShaderType, SourceFile,
+};
+
+fn files(files: &[(&str, &str)]) -> HashMap<String, String> {
+    files
+        .iter()
+        .map(|&(path, text)| (path.to_string(), text.to_string()))
+        .collect()
+}
+
+fn lit_shader() -> HashMap<String, String> {
+    files(&[
+        (
+            "lit.frag",
+            "#version 330 core\n#include \"lib/common.glsl\"\nvoid main() {}\n",
+        ),
+        (
+            "lib/common.glsl",
+            "#pragma once\n#include \"math.glsl\"\nfloat common() {}\n",
+        ),
+        ("lib/math.glsl", "const float PI = 3.14;\n"),
+    ])
+}
+
+#[test]
+fn nested_includes_are_wrapped_in_line_directives() {
+    let source = Preprocessor::new(lit_shader()).process("lit.frag").unwrap();
+    assert_eq!(
+        source.code,
+        "#version 330 core\n\
+         #line 2 0\n\
+         #line 1 1\n\
+         \n\
+         #line 1 2\n\
+         const float PI = 3.14;\n\
+         #line 3 1\n\
+         float common() {}\n\
+         #line 3 0\n\
+         void main() {}\n"
+    );
+    let names: Vec<_> = source.files.iter().map(|file| file.name.as_str()).collect();
+    assert_eq!(names, ["lit.frag", "lib/common.glsl", "lib/math.glsl"]);
+}
+
+#[test]
+fn defines_go_right_after_the_version() {
+    let source = Preprocessor::new(lit_shader())
+        .define("MAX_LIGHTS", "4")
+        .flag("USE_NORMAL_MAP")
+        .process_source(
+            "inline.frag",
+            "// lighting\n#version 330 core\nvoid main() {}\n",
+        )
+        .unwrap();
+    assert_eq!(
+        source.code,
+        "// lighting\n\
+         #version 330 core\n\
+         #define MAX_LIGHTS 4\n\
+         #define USE_NORMAL_MAP\n\
+         #line 3 0\n\
+         void main() {}\n"
+    );
+
+    // Without a version the defines lead the file
+    let source = Preprocessor::new(lit_shader())
+        .flag("USE_NORMAL_MAP")
+        .process("lib/math.glsl")
+        .unwrap();
+    assert_eq!(
+        source.code,
+        "#define USE_NORMAL_MAP\n#line 1 0\nconst float PI = 3.14;\n"
+    );
+}
+
+#[test]
+fn include_cycles_are_reported_with_the_chain() {
+    let cyclic = files(&[
+        ("main.vert", "#include \"a.glsl\"\n"),
+        ("a.glsl", "#include \"b.glsl\"\n"),
+        ("b.glsl", "#include \"./a.glsl\"\n"),
+    ]);
+    match Preprocessor::new(cyclic).process("main.vert") {
+        Err(PreprocessError::Cycle { path, chain }) => {
+            assert_eq!(path, "a.glsl");
+            assert_eq!(chain, ["main.vert", "a.glsl", "b.glsl"]);
+        }
+        other => panic!("expected a cycle, got {:?}", other),
+    }
+}
+
+#[test]
+fn missing_files_report_where_they_were_included() {
+    let shader = files(&[(
+        "main.vert",
+        "#version 330 core\n\n#include \"../missing.glsl\"\n",
+    )]);
+    match Preprocessor::new(shader).process("shaders/main.vert") {
+        Err(PreprocessError::Read {
+            path,
+            included_from: None,
+            error,
+        }) => {
+            assert_eq!(path, "shaders/main.vert");
+            assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        }
+        other => panic!("expected a read error, got {:?}", other),
+    }
+
+    let shader = files(&[(
+        "shaders/main.vert",
+        "#version 330 core\n\n#include \"../missing.glsl\"\n",
+    )]);
+    match Preprocessor::new(shader).process("shaders/main.vert") {
+        Err(PreprocessError::Read {
+            path,
+            included_from,
+            error,
+        }) => {
+            assert_eq!(path, "missing.glsl");
+            assert_eq!(included_from, Some(("shaders/main.vert".to_string(), 3)));
+            assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        }
+        other => panic!("expected a read error, got {:?}", other),
+    }
+}
+
+#[test]
+fn compile_errors_point_into_the_included_file() {
+    let source = Preprocessor::new(lit_shader()).process("lit.frag").unwrap();
+    let log = "1:3(7): error: syntax error, unexpected IDENTIFIER\n".to_string();
+    let error = ShaderError::compile(ShaderType::Fragment, &source.code, log)
+        .with_files(source.files.clone());
+
+    let ShaderError::Compile {
+        diagnostics, files, ..
+    } = &error
+    else {
+        panic!("expected a compile error");
+    };
+    assert_eq!(diagnostics[0].file.as_deref(), Some("lib/common.glsl"));
+    assert_eq!(diagnostics[0].line, Some(3));
+    assert_eq!(
+        files[1],
+        SourceFile {
+            name: "lib/common.glsl".to_string(),
+            text: "#pragma once\n#include \"math.glsl\"\nfloat common() {}\n".to_string(),
+        }
+    );
+
+    let rendered = error.to_string();
+    assert!(rendered.contains("--> lib/common.glsl:3:7"), "{}", rendered);
+    assert!(rendered.contains("3 | float common() {}"), "{}", rendered);
+}
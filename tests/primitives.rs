@@ -0,0 +1,244 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use glam::{UVec2, Vec2, Vec3};
+use opengl_chrno::geometry::{primitives, MeshData};
+
+fn closed_shapes() -> Vec<(&'static str, MeshData, f32)> {
+    vec![
+        ("cube", primitives::cube(Vec3::new(1.0, 2.0, 3.0), 3), 6.0),
+        (
+            "uv sphere",
+            primitives::uv_sphere(1.5, 48, 24),
+            4.0 / 3.0 * PI * 1.5f32.powi(3),
+        ),
+        (
+            "icosphere",
+            primitives::icosphere(1.5, 4),
+            4.0 / 3.0 * PI * 1.5f32.powi(3),
+        ),
+        (
+            "cylinder",
+            primitives::cylinder(0.5, 2.0, 64, 3),
+            PI * 0.25 * 2.0,
+        ),
+        ("cone", primitives::cone(1.0, 3.0, 64, 4), PI * 3.0 / 3.0),
+        (
+            "torus",
+            primitives::torus(2.0, 0.5, 64, 32),
+            2.0 * PI * PI * 2.0 * 0.25,
+        ),
+        (
+            "capsule",
+            primitives::capsule(0.5, 3.0, 48, 12),
+            PI * 0.25 * 2.0 + 4.0 / 3.0 * PI * 0.125,
+        ),
+    ]
+}
+
+/// Gives vertices at the same position the same id, so texture seams and
+/// hard edges don't count as holes.
+fn welded_indices(mesh: &MeshData) -> Vec<usize> {
+    let mut ids = HashMap::new();
+    let vertex_ids: Vec<usize> = mesh
+        .vertices
+        .iter()
+        .map(|vertex| {
+            let key = (vertex.position * 1e4).round().as_ivec3().to_array();
+            let next = ids.len();
+            *ids.entry(key).or_insert(next)
+        })
+        .collect();
+    mesh.indices
+        .iter()
+        .map(|&index| vertex_ids[index as usize])
+        .collect()
+}
+
+fn face_normal([a, b, c]: [Vec3; 3]) -> Vec3 {
+    (b - a).cross(c - a)
+}
+
+#[test]
+fn closed_shapes_are_watertight() {
+    for (name, mesh, _) in closed_shapes() {
+        let indices = welded_indices(&mesh);
+        let mut edges = HashMap::new();
+        for triangle in indices.chunks_exact(3) {
+            for i in 0..3 {
+                *edges
+                    .entry((triangle[i], triangle[(i + 1) % 3]))
+                    .or_insert(0) += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            assert_eq!(
+                count, 1,
+                "{}: edge {}-{} is used {} times",
+                name, a, b, count
+            );
+            assert_eq!(
+                edges.get(&(b, a)),
+                Some(&1),
+                "{}: edge {}-{} has no opposite",
+                name,
+                a,
+                b
+            );
+        }
+    }
+}
+
+#[test]
+fn closed_shapes_face_outwards() {
+    for (name, mesh, volume) in closed_shapes() {
+        // The signed volume is only positive when every triangle winds outwards
+        let signed_volume: f32 = mesh
+            .triangles()
+            .map(|[a, b, c]| a.dot(b.cross(c)) / 6.0)
+            .sum();
+        assert!(
+            (signed_volume - volume).abs() < volume * 0.02,
+            "{}: volume {} instead of {}",
+            name,
+            signed_volume,
+            volume
+        );
+    }
+}
+
+#[test]
+fn normals_match_winding() {
+    let mut shapes = closed_shapes();
+    shapes.push((
+        "plane",
+        primitives::plane(Vec2::new(4.0, 2.0), UVec2::new(3, 5)),
+        0.0,
+    ));
+
+    for (name, mesh, _) in shapes {
+        for triangle in mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]) {
+            let vertices = triangle.map(|index| mesh.vertices[index as usize]);
+            let face = face_normal(vertices.map(|vertex| vertex.position));
+            assert!(face.length() > 0.0, "{}: degenerate triangle", name);
+            for vertex in vertices {
+                assert!((vertex.normal.length() - 1.0).abs() < 1e-4, "{}", name);
+                assert!(
+                    vertex.normal.dot(face.normalize()) > 0.0,
+                    "{}: normal {} against face {}",
+                    name,
+                    vertex.normal,
+                    face
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn curved_normals_point_away_from_the_center() {
+    for mesh in [
+        primitives::uv_sphere(2.0, 16, 8),
+        primitives::icosphere(2.0, 2),
+    ] {
+        for vertex in &mesh.vertices {
+            assert!(vertex.normal.abs_diff_eq(vertex.position / 2.0, 1e-5));
+        }
+    }
+
+    for vertex in &primitives::torus(3.0, 1.0, 16, 8).vertices {
+        let ring = (vertex.position * Vec3::new(1.0, 0.0, 1.0)).normalize() * 3.0;
+        assert!(vertex.normal.abs_diff_eq(vertex.position - ring, 1e-4));
+    }
+}
+
+#[test]
+fn tangents_follow_texture_coordinates() {
+    let mut shapes = closed_shapes();
+    shapes.push(("plane", primitives::plane(Vec2::ONE, UVec2::ONE), 0.0));
+
+    for (name, mesh, _) in shapes {
+        for vertex in &mesh.vertices {
+            let tangent = vertex.tangent.truncate();
+            assert!((tangent.length() - 1.0).abs() < 1e-4, "{}", name);
+            assert!(tangent.dot(vertex.normal).abs() < 1e-4, "{}", name);
+            assert!(vertex.tangent.w.abs() == 1.0, "{}", name);
+        }
+
+        for triangle in mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]) {
+            let [a, b, c] = triangle.map(|index| mesh.vertices[index as usize]);
+            let (edge1, edge2) = (b.position - a.position, c.position - a.position);
+            let (duv1, duv2) = (b.tex_coord - a.tex_coord, c.tex_coord - a.tex_coord);
+            let determinant = duv1.perp_dot(duv2);
+            if determinant.abs() < 1e-9 {
+                continue;
+            }
+            let dp_du = (edge1 * duv2.y - edge2 * duv1.y) / determinant;
+            let dp_dv = (edge2 * duv1.x - edge1 * duv2.x) / determinant;
+            // Texture space must not be mirrored against the winding
+            assert!(determinant > 0.0, "{}: mirrored texture coordinates", name);
+
+            for vertex in [a, b, c] {
+                let bitangent = vertex.normal.cross(vertex.tangent.truncate()) * vertex.tangent.w;
+                assert!(
+                    vertex.tangent.truncate().dot(dp_du) > 0.0,
+                    "{}: tangent",
+                    name
+                );
+                assert!(bitangent.dot(dp_dv) > 0.0, "{}: bitangent", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn texture_coordinates_are_in_range() {
+    let mut shapes = closed_shapes();
+    shapes.push(("plane", primitives::plane(Vec2::ONE, UVec2::new(2, 2)), 0.0));
+
+    for (name, mesh, _) in shapes {
+        // The icosphere seam runs through triangles, so u goes a little past 1 there
+        let max_u = if name == "icosphere" { 1.1 } else { 1.0 };
+        for vertex in &mesh.vertices {
+            let uv = vertex.tex_coord;
+            assert!(
+                (0.0..=max_u).contains(&uv.x) && (0.0..=1.0).contains(&uv.y),
+                "{}: {}",
+                name,
+                uv
+            );
+        }
+    }
+}
+
+#[test]
+fn sizes_and_subdivisions() {
+    let cube = primitives::cube(Vec3::new(2.0, 4.0, 6.0), 2);
+    assert_eq!(cube.vertices.len(), 6 * 9);
+    assert_eq!(cube.triangle_count(), 6 * 8);
+    let max = cube
+        .vertices
+        .iter()
+        .fold(Vec3::ZERO, |max, vertex| max.max(vertex.position));
+    assert_eq!(max, Vec3::new(1.0, 2.0, 3.0));
+
+    let plane = primitives::plane(Vec2::new(4.0, 2.0), UVec2::new(4, 1));
+    assert_eq!(plane.triangle_count(), 8);
+    assert!(plane.vertices.iter().all(|vertex| vertex.normal == Vec3::Y));
+
+    assert_eq!(primitives::icosphere(1.0, 0).triangle_count(), 20);
+    assert_eq!(primitives::icosphere(1.0, 2).triangle_count(), 320);
+    // Two triangles per quad, one at each pole
+    assert_eq!(
+        primitives::uv_sphere(1.0, 8, 4).triangle_count(),
+        8 * (2 * 4 - 2)
+    );
+
+    let capsule = primitives::capsule(1.0, 5.0, 16, 4);
+    let (low, high) = capsule
+        .vertices
+        .iter()
+        .fold((0.0f32, 0.0f32), |(low, high), vertex| {
+            (low.min(vertex.position.y), high.max(vertex.position.y))
+        });
+    assert!((low + 2.5).abs() < 1e-5 && (high - 2.5).abs() < 1e-5);
+}
@@ -0,0 +1,92 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec4;
+use opengl_chrno::{
+    geometry::{Instance, Vertex},
+    learn_opengl::{ActiveAttribute, GlslType, LayoutMismatch, ProgramInterface, VertexLayout},
+    vertex_layout,
+};
+
+vertex_layout! {
+    /// Feeds the first column of the model matrix only.
+    #[derive(Debug, Clone, Copy, Pod, Zeroable)]
+    struct Translation {
+        #[attrib(location = 4, divisor = 1)]
+        offset: Vec4,
+        #[attrib(location = 8, divisor = 1)]
+        color: Vec4,
+    }
+}
+
+fn attribute(name: &str, glsl_type: GlslType, size: i32, location: i32) -> ActiveAttribute {
+    ActiveAttribute {
+        name: name.to_owned(),
+        glsl_type,
+        size,
+        location,
+    }
+}
+
+fn instanced_interface() -> ProgramInterface {
+    ProgramInterface {
+        attributes: vec![
+            attribute("gl_InstanceID", GlslType::Int, 1, -1),
+            attribute("position", GlslType::Vec3, 1, 0),
+            attribute("tex_coord", GlslType::Vec2, 1, 1),
+            attribute("model", GlslType::Mat4, 1, 4),
+            attribute("color", GlslType::Vec4, 1, 8),
+        ],
+        ..ProgramInterface::default()
+    }
+}
+
+#[test]
+fn instanced_programs_are_fed_by_both_layouts() {
+    let interface = instanced_interface();
+    assert_eq!(
+        interface.check_layouts(&[Vertex::ATTRIBUTES, Instance::ATTRIBUTES]),
+        Ok(())
+    );
+}
+
+#[test]
+fn every_column_of_a_matrix_has_to_be_provided() {
+    let interface = instanced_interface();
+    let Err(mismatches) = interface.check_layouts(&[Vertex::ATTRIBUTES, Translation::ATTRIBUTES])
+    else {
+        panic!("only the first column is provided");
+    };
+    let missing: Vec<_> = mismatches
+        .iter()
+        .map(|mismatch| match mismatch {
+            LayoutMismatch::Missing { name, location } => (name.as_str(), *location),
+            other => panic!("unexpected {:?}", other),
+        })
+        .collect();
+    assert_eq!(missing, [("model", 5), ("model", 6), ("model", 7)]);
+}
+
+#[test]
+fn array_elements_take_a_location_each() {
+    let interface = ProgramInterface {
+        attributes: vec![attribute("weights", GlslType::Vec3, 3, 1)],
+        ..ProgramInterface::default()
+    };
+    // Location 1 is the vec2 texture coordinate, 2 is the normal and 3 the vec4 tangent
+    assert_eq!(
+        interface.check_layout::<Vertex>(),
+        Err(vec![
+            LayoutMismatch::Components {
+                name: "weights".to_owned(),
+                location: 1,
+                expected: 3,
+                found: 2
+            },
+            LayoutMismatch::Components {
+                name: "weights".to_owned(),
+                location: 3,
+                expected: 3,
+                found: 4
+            },
+        ])
+    );
+}
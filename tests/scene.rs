@@ -0,0 +1,213 @@
+use std::f32::consts::FRAC_PI_2;
+
+use glam::{Mat3, Mat4, Quat, Vec3};
+use opengl_chrno::scene::{SceneGraph, Transform};
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!(a.abs_diff_eq(b, 1e-5), "{} != {}", a, b);
+}
+
+#[test]
+fn transform_matrices() {
+    let mut transform = Transform::new(
+        Vec3::new(1.0, 2.0, 3.0),
+        Quat::from_rotation_y(FRAC_PI_2),
+        Vec3::new(2.0, 1.0, 1.0),
+    );
+    assert!(!transform.is_dirty());
+    // Scaled, then turned, then moved
+    assert_close(
+        transform.local_matrix().transform_point3(Vec3::X),
+        Vec3::new(1.0, 2.0, 1.0),
+    );
+    assert_eq!(transform.world_matrix(), transform.local_matrix());
+
+    transform.translate(Vec3::Y);
+    assert!(transform.is_dirty());
+    assert_close(transform.translation(), Vec3::new(1.0, 3.0, 3.0));
+    assert_close(
+        transform.local_matrix().transform_point3(Vec3::ZERO),
+        Vec3::new(1.0, 3.0, 3.0),
+    );
+    // The world matrix waits for the parent
+    assert_close(
+        transform.world_matrix().transform_point3(Vec3::ZERO),
+        Vec3::new(1.0, 2.0, 3.0),
+    );
+    transform.update_world(Mat4::from_translation(Vec3::X));
+    assert!(!transform.is_dirty());
+    assert_close(
+        transform.world_matrix().transform_point3(Vec3::ZERO),
+        Vec3::new(2.0, 3.0, 3.0),
+    );
+}
+
+#[test]
+fn normal_matrix_keeps_normals_perpendicular() {
+    let mut transform = Transform::from_scale(Vec3::new(4.0, 1.0, 1.0));
+    transform.rotate(Quat::from_rotation_z(0.3));
+    transform.update_world(Mat4::IDENTITY);
+
+    // A slanted surface, squashed by the scale
+    let tangent = Vec3::new(1.0, 1.0, 0.0);
+    let normal = Vec3::new(1.0, -1.0, 0.0);
+    let world_tangent = transform.world_matrix().transform_vector3(tangent);
+    let world_normal = transform.normal_matrix() * normal;
+    assert!(world_tangent.dot(world_normal).abs() < 1e-5);
+    // Without the inverse transpose the normal would lean
+    assert!(
+        world_tangent
+            .dot(transform.world_matrix().transform_vector3(normal))
+            .abs()
+            > 1.0
+    );
+
+    let rotation = Transform::from_rotation(Quat::from_rotation_x(1.0));
+    assert!(rotation
+        .normal_matrix()
+        .abs_diff_eq(Mat3::from_quat(Quat::from_rotation_x(1.0)), 1e-5));
+}
+
+#[test]
+fn children_follow_their_parents() {
+    let mut scene = SceneGraph::new();
+    let parent = scene.add(
+        Transform::from_translation(Vec3::new(0.0, 0.0, -5.0)),
+        "parent",
+    );
+    let child = scene.add_child(parent, Transform::from_translation(Vec3::X), "child");
+    let grandchild = scene.add_child(child, Transform::from_scale(Vec3::splat(0.5)), "grandchild");
+    scene.update();
+
+    let origin = |scene: &SceneGraph<&str>, id| {
+        scene
+            .transform(id)
+            .world_matrix()
+            .transform_point3(Vec3::ZERO)
+    };
+    assert_close(origin(&scene, child), Vec3::new(1.0, 0.0, -5.0));
+    assert_close(origin(&scene, grandchild), Vec3::new(1.0, 0.0, -5.0));
+
+    // Only the parent changes, the children are updated with it
+    scene
+        .transform_mut(parent)
+        .set_rotation(Quat::from_rotation_y(FRAC_PI_2));
+    assert!(scene.transform(parent).is_dirty());
+    assert!(!scene.transform(child).is_dirty());
+    scene.update();
+    assert!(!scene.transform(parent).is_dirty());
+    assert_close(origin(&scene, child), Vec3::new(0.0, 0.0, -6.0));
+    let corner = scene
+        .transform(grandchild)
+        .world_matrix()
+        .transform_point3(Vec3::Z);
+    assert_close(corner, Vec3::new(0.5, 0.0, -6.0));
+}
+
+#[test]
+fn iterates_in_draw_order() {
+    let mut scene = SceneGraph::new();
+    let a = scene.add(Transform::IDENTITY, "a");
+    let b = scene.add(Transform::IDENTITY, "b");
+    scene.add_child(a, Transform::IDENTITY, "a1");
+    let a2 = scene.add_child(a, Transform::IDENTITY, "a2");
+    scene.add_child(a2, Transform::IDENTITY, "a2x");
+    scene.add_child(b, Transform::IDENTITY, "b1");
+
+    let order: Vec<_> = scene.iter().map(|(_, node)| node.item).collect();
+    assert_eq!(order, ["a", "a1", "a2", "a2x", "b", "b1"]);
+    assert_eq!(scene.len(), 6);
+    assert_eq!(scene.roots(), [a, b]);
+    assert_eq!(scene.get(a2).unwrap().parent(), Some(a));
+    let ancestors: Vec<_> = scene.ancestors(a2).collect();
+    assert_eq!(ancestors, [a2, a]);
+}
+
+#[test]
+fn reparenting_moves_the_subtree() {
+    let mut scene = SceneGraph::new();
+    let a = scene.add(Transform::from_translation(Vec3::X), "a");
+    let b = scene.add(Transform::from_translation(Vec3::Y), "b");
+    let child = scene.add_child(a, Transform::from_translation(Vec3::Z), "child");
+    scene.update();
+
+    assert!(scene.set_parent(child, Some(b)));
+    scene.update();
+    assert_close(
+        scene
+            .transform(child)
+            .world_matrix()
+            .transform_point3(Vec3::ZERO),
+        Vec3::new(0.0, 1.0, 1.0),
+    );
+    assert!(scene.get(a).unwrap().children().is_empty());
+    assert_eq!(scene.get(b).unwrap().children(), [child]);
+
+    // A node can't go below itself
+    assert!(!scene.set_parent(b, Some(child)));
+    assert!(!scene.set_parent(b, Some(b)));
+    assert_eq!(scene.get(b).unwrap().parent(), None);
+
+    assert!(scene.set_parent(child, None));
+    scene.update();
+    assert_eq!(scene.roots(), [a, b, child]);
+    assert_close(
+        scene
+            .transform(child)
+            .world_matrix()
+            .transform_point3(Vec3::ZERO),
+        Vec3::Z,
+    );
+}
+
+#[test]
+fn removing_takes_the_subtree() {
+    let mut scene = SceneGraph::new();
+    let a = scene.add(Transform::IDENTITY, "a");
+    let a1 = scene.add_child(a, Transform::IDENTITY, "a1");
+    let a1x = scene.add_child(a1, Transform::IDENTITY, "a1x");
+    let b = scene.add(Transform::IDENTITY, "b");
+
+    assert_eq!(scene.remove(a1), Some("a1"));
+    assert_eq!(scene.len(), 2);
+    assert!(!scene.contains(a1) && !scene.contains(a1x));
+    assert!(scene.get(a).unwrap().children().is_empty());
+    assert_eq!(scene.remove(a1), None);
+
+    // Reused slots don't bring old ids back to life
+    let c = scene.add(Transform::IDENTITY, "c");
+    assert!(!scene.contains(a1) && !scene.contains(a1x));
+    assert_eq!(scene.get(c).unwrap().item, "c");
+    let order: Vec<_> = scene.iter().map(|(_, node)| node.item).collect();
+    assert_eq!(order, ["a", "b", "c"]);
+    assert!(scene.contains(b));
+}
+
+#[test]
+fn replaced_transforms_are_updated_under_their_parent() {
+    let mut scene = SceneGraph::new();
+    let parent = scene.add(Transform::from_translation(Vec3::new(0.0, 2.0, 0.0)), ());
+    let child = scene.add_child(parent, Transform::IDENTITY, ());
+    scene.update();
+
+    // A fresh transform is clean, with the world matrix of a root
+    *scene.transform_mut(child) = Transform::from_translation(Vec3::X);
+    scene.update();
+    assert_close(
+        scene
+            .transform(child)
+            .world_matrix()
+            .transform_point3(Vec3::ZERO),
+        Vec3::new(1.0, 2.0, 0.0),
+    );
+
+    scene.get_mut(child).unwrap().transform = Transform::from_scale(Vec3::splat(2.0));
+    scene.update();
+    assert_close(
+        scene
+            .transform(child)
+            .world_matrix()
+            .transform_point3(Vec3::ONE),
+        Vec3::new(2.0, 4.0, 2.0),
+    );
+}
@@ -0,0 +1,159 @@
+use opengl_chrno::learn_opengl::{parse_info_log, Diagnostic, Severity, ShaderError, ShaderType};
+
+fn diagnostic(
+    source_string: Option<u32>,
+    line: Option<u32>,
+    column: Option<u32>,
+    severity: Severity,
+    message: &str,
+) -> Diagnostic {
+    Diagnostic {
+        source_string,
+        file: None,
+        line,
+        column,
+        severity,
+        message: message.to_string(),
+    }
+}
+
+#[test]
+fn parses_mesa_logs() {
+    let log = "0:12(5): error: `colour' undeclared\n\
+               0:3(10): warning: extension `GL_ARB_foo' unsupported\n";
+    assert_eq!(
+        parse_info_log(log),
+        [
+            diagnostic(
+                Some(0),
+                Some(12),
+                Some(5),
+                Severity::Error,
+                "`colour' undeclared"
+            ),
+            diagnostic(
+                Some(0),
+                Some(3),
+                Some(10),
+                Severity::Warning,
+                "extension `GL_ARB_foo' unsupported"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn parses_nvidia_logs() {
+    let log = "0(7) : error C1008: undefined variable \"colour\"\n\
+               \n\
+               2(40) : warning C7022: unrecognized profile specifier \"foo\"";
+    assert_eq!(
+        parse_info_log(log),
+        [
+            diagnostic(
+                Some(0),
+                Some(7),
+                None,
+                Severity::Error,
+                "undefined variable \"colour\""
+            ),
+            diagnostic(
+                Some(2),
+                Some(40),
+                None,
+                Severity::Warning,
+                "unrecognized profile specifier \"foo\""
+            ),
+        ]
+    );
+}
+
+#[test]
+fn parses_prefixed_logs() {
+    let log = "ERROR: 0:5: 'colour' : undeclared identifier\n\
+               WARNING: 1:2: 'x' : unused\n";
+    assert_eq!(
+        parse_info_log(log),
+        [
+            diagnostic(
+                Some(0),
+                Some(5),
+                None,
+                Severity::Error,
+                "'colour' : undeclared identifier"
+            ),
+            diagnostic(Some(1), Some(2), None, Severity::Warning, "'x' : unused"),
+        ]
+    );
+}
+
+#[test]
+fn keeps_lines_it_cannot_parse() {
+    let log = "error: vertex shader output `uv' not read by the fragment shader\n\
+               Something went wrong: 42";
+    assert_eq!(
+        parse_info_log(log),
+        [
+            diagnostic(
+                None,
+                None,
+                None,
+                Severity::Error,
+                "vertex shader output `uv' not read by the fragment shader"
+            ),
+            // No severity to go by, the whole line is kept as an error
+            diagnostic(
+                None,
+                None,
+                None,
+                Severity::Error,
+                "Something went wrong: 42"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn renders_the_offending_line_with_a_caret() {
+    let source = "#version 330 core\n\
+                  out vec4 color;\n\
+                  void main() {\n    \
+                      color = colour;\n\
+                  }\n";
+
+    let with_column = ShaderError::compile(
+        ShaderType::Fragment,
+        source,
+        "0:4(13): error: `colour' undeclared\n".to_string(),
+    );
+    assert_eq!(
+        with_column.to_string(),
+        "fragment shader failed to compile\n\
+         error: `colour' undeclared\n \
+          --> fragment shader:4:13\n  \
+           |\n\
+         4 |     color = colour;\n  \
+           |             ^\n"
+    );
+
+    // Without a column the whole line is underlined, leading whitespace aside
+    let without_column = ShaderError::compile(
+        ShaderType::Fragment,
+        source,
+        "0(4) : error C1008: undefined variable \"colour\"".to_string(),
+    );
+    assert_eq!(
+        without_column.to_string(),
+        "fragment shader failed to compile\n\
+         error: undefined variable \"colour\"\n \
+          --> fragment shader:4\n  \
+           |\n\
+         4 |     color = colour;\n  \
+           |     ^^^^^^^^^^^^^^^\n"
+    );
+
+    // A log that can't be matched to the source is shown as it is
+    let unparsed = ShaderError::compile(ShaderType::Vertex, source, "\n".to_string());
+    assert!(unparsed.diagnostics().is_empty());
+    assert_eq!(unparsed.to_string(), "vertex shader failed to compile\n");
+}
@@ -0,0 +1,100 @@
+use gl33::{
+    GL_FLOAT, GL_GREEN, GL_ONE, GL_R16, GL_R8, GL_RED, GL_RG, GL_RG8, GL_RGB, GL_RGB16, GL_RGB32F,
+    GL_RGB8, GL_RGBA, GL_RGBA16, GL_UNSIGNED_BYTE, GL_UNSIGNED_SHORT,
+};
+use image::{DynamicImage, GrayImage, Luma, Rgb, Rgb32FImage, RgbImage, Rgba, RgbaImage};
+use opengl_chrno::learn_opengl::{unpack_alignment, PixelFormat};
+
+#[test]
+fn eight_bit_images_keep_their_channels() {
+    let rgb = DynamicImage::ImageRgb8(RgbImage::from_pixel(3, 2, Rgb([1, 2, 3])));
+    let (format, bytes) = PixelFormat::of_image(&rgb);
+    assert_eq!(
+        (format.internal_format, format.format, format.data_type),
+        (GL_RGB8, GL_RGB, GL_UNSIGNED_BYTE)
+    );
+    assert_eq!(format.bytes_per_pixel, 3);
+    assert_eq!(format.swizzle, None);
+    assert_eq!(bytes.len(), 3 * 2 * 3);
+    assert_eq!(bytes[..3], [1, 2, 3]);
+
+    let rgba = DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba([1, 2, 3, 4])));
+    let (format, bytes) = PixelFormat::of_image(&rgba);
+    assert_eq!(format, PixelFormat::RGBA8);
+    assert_eq!(bytes.len(), 2 * 2 * 4);
+}
+
+#[test]
+fn sixteen_bit_images_upload_shorts() {
+    let gray = DynamicImage::ImageLuma16(image::ImageBuffer::from_pixel(2, 2, Luma([1000u16])));
+    let (format, bytes) = PixelFormat::of_image(&gray);
+    assert_eq!(
+        (format.internal_format, format.format, format.data_type),
+        (GL_R16, GL_RED, GL_UNSIGNED_SHORT)
+    );
+    assert_eq!(format.bytes_per_pixel, 2);
+    assert_eq!(bytes.len(), 2 * 2 * 2);
+
+    let rgb = DynamicImage::ImageRgb16(image::ImageBuffer::from_pixel(1, 1, Rgb([1u16, 2, 3])));
+    let (format, _) = PixelFormat::of_image(&rgb);
+    assert_eq!(
+        (format.internal_format, format.bytes_per_pixel),
+        (GL_RGB16, 6)
+    );
+
+    let rgba = DynamicImage::ImageRgba16(image::ImageBuffer::new(1, 1));
+    let (format, _) = PixelFormat::of_image(&rgba);
+    assert_eq!(
+        (format.internal_format, format.format),
+        (GL_RGBA16, GL_RGBA)
+    );
+    assert_eq!(format.bytes_per_pixel, 8);
+}
+
+#[test]
+fn float_images_upload_floats() {
+    let rgb = DynamicImage::ImageRgb32F(Rgb32FImage::from_pixel(2, 1, Rgb([0.5, 1.5, 2.0])));
+    let (format, bytes) = PixelFormat::of_image(&rgb);
+    assert_eq!(
+        (format.internal_format, format.format, format.data_type),
+        (GL_RGB32F, GL_RGB, GL_FLOAT)
+    );
+    assert_eq!(format.bytes_per_pixel, 12);
+    assert_eq!(bytes.len(), 2 * 12);
+    assert_eq!(bytes[4..8], 1.5f32.to_ne_bytes());
+
+    let rgba = DynamicImage::ImageRgba32F(image::ImageBuffer::new(1, 1));
+    let (format, _) = PixelFormat::of_image(&rgba);
+    assert_eq!(format, PixelFormat::RGBA32F);
+}
+
+#[test]
+fn gray_images_sample_as_gray() {
+    let gray = DynamicImage::ImageLuma8(GrayImage::from_pixel(5, 1, Luma([7])));
+    let (format, bytes) = PixelFormat::of_image(&gray);
+    assert_eq!((format.internal_format, format.format), (GL_R8, GL_RED));
+    assert_eq!(format.swizzle, Some([GL_RED, GL_RED, GL_RED, GL_ONE]));
+    assert_eq!(*bytes, [7; 5]);
+
+    let gray_alpha = DynamicImage::ImageLumaA8(image::ImageBuffer::new(1, 1));
+    let (format, _) = PixelFormat::of_image(&gray_alpha);
+    assert_eq!((format.internal_format, format.format), (GL_RG8, GL_RG));
+    assert_eq!(format.bytes_per_pixel, 2);
+    // Alpha is stored in green
+    assert_eq!(format.swizzle, Some([GL_RED, GL_RED, GL_RED, GL_GREEN]));
+}
+
+#[test]
+fn unpack_alignment_divides_the_rows() {
+    // RGBA rows are always a multiple of 4 bytes
+    assert_eq!(unpack_alignment(1, 4), 4);
+    assert_eq!(unpack_alignment(2, 4), 8);
+    // Tightly packed RGB and gray rows of odd widths are not
+    assert_eq!(unpack_alignment(3, 3), 1);
+    assert_eq!(unpack_alignment(5, 1), 1);
+    assert_eq!(unpack_alignment(3, 2), 2);
+    assert_eq!(unpack_alignment(6, 3), 2);
+    assert_eq!(unpack_alignment(4, 3), 4);
+    assert_eq!(unpack_alignment(8, 1), 8);
+    assert_eq!(unpack_alignment(0, 3), 8);
+}
@@ -0,0 +1,111 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat3, Vec2, Vec3};
+use opengl_chrno::{
+    geometry::{Instance, Vertex},
+    learn_opengl::{AttributeType, VertexAttribute, VertexLayout},
+    vertex_layout,
+};
+
+vertex_layout! {
+    #[derive(Debug, Clone, Copy, Pod, Zeroable)]
+    struct SkinnedVertex {
+        #[attrib(location = 0)]
+        position: Vec3,
+        #[attrib(location = 1, normalized)]
+        color: [u8; 4],
+        #[attrib(location = 2)]
+        tex_coord: Vec2,
+        #[attrib(location = 3, integer)]
+        bones: [u16; 2],
+        #[attrib(location = 4)]
+        weight: f32,
+    }
+}
+
+vertex_layout! {
+    #[derive(Debug, Clone, Copy, Pod, Zeroable)]
+    struct Decal {
+        #[attrib(location = 5, divisor = 2)]
+        transform: Mat3,
+        #[attrib(location = 8, divisor = 2)]
+        tint: Vec3,
+    }
+}
+
+#[test]
+fn offsets_and_stride_follow_the_struct() {
+    let offsets: Vec<usize> = SkinnedVertex::ATTRIBUTES
+        .iter()
+        .map(|attribute| attribute.offset)
+        .collect();
+    assert_eq!(offsets, [0, 12, 16, 24, 28]);
+    assert_eq!(SkinnedVertex::stride(), 32);
+}
+
+#[test]
+fn attributes_take_their_format_from_the_field_type() {
+    let [position, color, tex_coord, bones, weight] = SkinnedVertex::ATTRIBUTES else {
+        panic!("expected five attributes");
+    };
+    assert_eq!(*position, VertexAttribute::of::<Vec3>(0, 0));
+    assert_eq!(
+        (position.components, position.attribute_type),
+        (3, AttributeType::F32)
+    );
+    assert_eq!(
+        (color.location, color.components, color.attribute_type),
+        (1, 4, AttributeType::U8)
+    );
+    assert!(color.normalized && !color.integer);
+    assert_eq!((tex_coord.location, tex_coord.components), (2, 2));
+    assert_eq!(
+        (bones.location, bones.components, bones.attribute_type),
+        (3, 2, AttributeType::U16)
+    );
+    assert!(bones.integer && !bones.normalized);
+    assert_eq!((weight.location, weight.components), (4, 1));
+    assert!(SkinnedVertex::ATTRIBUTES
+        .iter()
+        .all(|attribute| attribute.divisor == 0 && attribute.slots == 1));
+}
+
+#[test]
+fn matrices_take_a_location_per_column() {
+    let [transform, tint] = Decal::ATTRIBUTES else {
+        panic!("expected two attributes");
+    };
+    assert_eq!((transform.location, transform.slots), (5, 3));
+    assert_eq!((transform.components, transform.slot_size()), (3, 12));
+    assert_eq!((tint.location, tint.offset), (8, 36));
+    assert!(Decal::ATTRIBUTES
+        .iter()
+        .all(|attribute| attribute.divisor == 2));
+    assert_eq!(Decal::stride(), 48);
+}
+
+#[test]
+fn instance_attributes_step_per_instance_after_the_vertex() {
+    let vertex_locations: Vec<u32> = Vertex::ATTRIBUTES
+        .iter()
+        .flat_map(|attribute| attribute.location..attribute.location + attribute.slots)
+        .collect();
+    let instance_locations: Vec<u32> = Instance::ATTRIBUTES
+        .iter()
+        .flat_map(|attribute| attribute.location..attribute.location + attribute.slots)
+        .collect();
+    assert_eq!(instance_locations, [4, 5, 6, 7, 8]);
+    assert!(instance_locations
+        .iter()
+        .all(|location| !vertex_locations.contains(location)));
+
+    // One column of the matrix per slot, then the color
+    let [model, color] = Instance::ATTRIBUTES else {
+        panic!("expected two attributes");
+    };
+    assert_eq!((model.components, model.slot_size()), (4, 16));
+    assert_eq!((model.offset, color.offset), (0, 64));
+    assert!(Instance::ATTRIBUTES
+        .iter()
+        .all(|attribute| attribute.divisor == 1));
+    assert_eq!(Instance::stride(), 80);
+}